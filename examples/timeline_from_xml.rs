@@ -3,7 +3,7 @@ use std::time::Duration;
 use bevy::prelude::*;
 
 use bevy_egui::{egui, EguiContexts, EguiPlugin};
-use timeline_rs::{easing::{self, EasingFunction, EasingType}, loader::TimelineXMLLoader, Keyframe, Timeline, TimelineTrack, Track, TrackGetter};
+use timeline_rs::{easing::{self, EasingFunction, EasingType}, loader::TimelineXMLLoader, Keyframe, player::{LoopMode, TimelinePlayer}, Timeline, TimelineTrack, Track, TrackGetter};
 // use egui_dropdown::DropDownBox;
 // use lazy_static::lazy_static;
 
@@ -93,21 +93,20 @@ fn create_timeline() -> Timeline {
 
 #[derive(Resource)]
 struct TimelineData {
-    pub timeline: Timeline,
-    pub t : f32,
+    pub player: TimelinePlayer,
     pub x : f32,
     pub y : f32,
-    pub looped: bool,
 }
 
 impl Default for TimelineData {
     fn default() -> Self {
+        let mut player = TimelinePlayer::new(create_timeline());
+        player.set_loop_mode(LoopMode::Loop).play();
+
         TimelineData {
-            timeline: create_timeline(),
-            t: 0.0,
+            player,
             x: 0.0,
             y: 0.0,
-            looped: true,
         }
     }
 }
@@ -135,12 +134,21 @@ fn update_egui (
 ) {
     egui::Window::new("Easing").show(contexts.ctx_mut(), |ui| {
         // display looped as checkbox
-        ui.checkbox(&mut data.looped, "Looped");
+        let mut looped = data.player.get_loop_mode() == LoopMode::Loop;
+        if ui.checkbox(&mut looped, "Looped").changed() {
+            data.player.set_loop_mode(if looped { LoopMode::Loop } else { LoopMode::Once });
+            if looped {
+                data.player.play();
+            }
+        }
 
-        let duration: f32 = data.timeline.get_max_duration().as_secs_f32();
+        let duration: f32 = data.player.get_out_point().as_secs_f32();
 
         // display t as slider
-        ui.add(egui::Slider::new(&mut data.t, 0.0..=duration).text("t"));         
+        let mut t = data.player.get_time().as_secs_f32();
+        if ui.add(egui::Slider::new(&mut t, 0.0..=duration).text("t")).changed() {
+            data.player.seek(s(t));
+        }
 
         // display x as slider
         ui.add(egui::Slider::new(&mut data.x, 0.0..=1.0).text("x"));
@@ -155,25 +163,10 @@ fn update(
     time: Res<Time>,
     mut data: ResMut<TimelineData>,
 ) {
-    let duration: f32 = data.timeline.get_max_duration().as_secs_f32();
-
-    data.t += time.delta_seconds();
-
-    if data.looped {
-        if data.t > duration {
-            data.t = 0.0;
-        }
-    } else {
-        if data.t > duration {
-            // data.t = duration;
-
-            // do nothing
-
-        }
-    }
+    data.player.update(time.delta());
 
-    let vx = data.timeline.get_value("x", Duration::from_secs_f32(data.t)).into();
-    let vy = data.timeline.get_value("y", Duration::from_secs_f32(data.t)).into();
+    let vx = data.player.get_value("x").into();
+    let vy = data.player.get_value("y").into();
     data.x = vx;
     data.y = vy;
 
@@ -0,0 +1,582 @@
+use std::f32::consts::TAU;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::easing::{self, EasingFunction, EasingType};
+use crate::{DuplicateTimePolicy, Extrapolation, Keyframe, Rounding, TimelineError, TimelineTrackImpl, Track, TrackInterpolation};
+
+/// below this saturation / chroma the hue is meaningless and is taken from the other color
+const ACHROMATIC: f32 = 1e-4;
+
+/// RGBA color with sRGB-encoded components in 0..1
+///
+/// Can be deserialized from a hex string (`"#rrggbb"` / `"#rrggbbaa"`),
+/// an ofColor string with 0..255 components (`"255, 128, 0, 255"`),
+/// an object `{r, g, b, a}` or an array `[r, g, b, a]` with 0..1 components (alpha is optional).
+#[derive(Debug, Default, Copy, Clone, PartialEq, serde::Serialize)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+/// Space a `ColorTrack` interpolates in
+///
+/// Components are interpolated linearly in that space (with the keyframe easing),
+/// hues go around the shorter way.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSpace {
+    /// gamma-encoded components, as stored. cheap, but midpoints get dark and muddy
+    Srgb,
+    /// physically linear light
+    #[default]
+    LinearRgb,
+    Hsv,
+    Hsl,
+    /// perceptually uniform lightness and hue
+    Oklab,
+    /// polar Oklab, interpolates the hue
+    OkLch,
+}
+
+impl ColorSpace {
+    /// indices of the hue and of the saturation / chroma in the components
+    fn hue_indices(self) -> Option<(usize, usize)> {
+        match self {
+            ColorSpace::Hsv | ColorSpace::Hsl => Some((0, 1)),
+            ColorSpace::OkLch => Some((2, 1)),
+            _ => None,
+        }
+    }
+
+    /// moves the hue of `c` within half a turn of the hue of `reference`
+    fn align_hue(self, reference: &[f32; 4], c: &mut [f32; 4]) {
+        if let Some((h, chroma)) = self.hue_indices() {
+            if c[chroma] < ACHROMATIC {
+                c[h] = reference[h];
+            } else {
+                c[h] = reference[h] + (c[h] - reference[h] + 0.5).rem_euclid(1.0) - 0.5;
+            }
+        }
+    }
+}
+
+impl Color {
+    pub const BLACK: Color = Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 };
+    pub const WHITE: Color = Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 };
+
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color { r, g, b, a }
+    }
+
+    pub fn rgb(r: f32, g: f32, b: f32) -> Color {
+        Color { r, g, b, a: 1.0 }
+    }
+
+    pub fn from_rgba8(r: u8, g: u8, b: u8, a: u8) -> Color {
+        Color::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a as f32 / 255.0)
+    }
+
+    /// `#rrggbb` or `#rrggbbaa`, the `#` is optional
+    pub fn from_hex(hex: &str) -> Result<Color, TimelineError> {
+        let invalid = || TimelineError::InvalidColor(hex.to_string());
+        let digits = hex.trim().trim_start_matches('#');
+        if !(digits.len() == 6 || digits.len() == 8) || !digits.is_ascii() {
+            return Err(invalid());
+        }
+        let channel = |i: usize| u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16).map_err(|_| invalid());
+        let a = if digits.len() == 8 { channel(3)? } else { 255 };
+        Ok(Color::from_rgba8(channel(0)?, channel(1)?, channel(2)?, a))
+    }
+
+    /// `#rrggbb`, or `#rrggbbaa` if not opaque
+    pub fn to_hex(self) -> String {
+        let [r, g, b, a] = [self.r, self.g, self.b, self.a].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        if a == 255 {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+        }
+    }
+
+    /// components in the given space, alpha last. hues are in turns (0..1)
+    pub fn to_space(self, space: ColorSpace) -> [f32; 4] {
+        let [r, g, b] = match space {
+            ColorSpace::Srgb => [self.r, self.g, self.b],
+            ColorSpace::LinearRgb => [self.r, self.g, self.b].map(srgb_to_linear),
+            ColorSpace::Hsv => rgb_to_hsv(self.r, self.g, self.b),
+            ColorSpace::Hsl => rgb_to_hsl(self.r, self.g, self.b),
+            ColorSpace::Oklab => linear_to_oklab([self.r, self.g, self.b].map(srgb_to_linear)),
+            ColorSpace::OkLch => {
+                let [l, a, b] = linear_to_oklab([self.r, self.g, self.b].map(srgb_to_linear));
+                [l, a.hypot(b), (b.atan2(a) / TAU).rem_euclid(1.0)]
+            }
+        };
+        [r, g, b, self.a]
+    }
+
+    /// inverse of `to_space`. out of gamut results are clamped
+    pub fn from_space(c: [f32; 4], space: ColorSpace) -> Color {
+        let [r, g, b] = match space {
+            ColorSpace::Srgb => [c[0], c[1], c[2]],
+            ColorSpace::LinearRgb => [c[0], c[1], c[2]].map(linear_to_srgb),
+            ColorSpace::Hsv => hsv_to_rgb(c[0].rem_euclid(1.0), c[1], c[2]),
+            ColorSpace::Hsl => hsl_to_rgb(c[0].rem_euclid(1.0), c[1], c[2]),
+            ColorSpace::Oklab => oklab_to_linear([c[0], c[1], c[2]]).map(linear_to_srgb),
+            ColorSpace::OkLch => {
+                let (sin, cos) = (c[2] * TAU).sin_cos();
+                oklab_to_linear([c[0], c[1] * cos, c[1] * sin]).map(linear_to_srgb)
+            }
+        };
+        let [r, g, b, a] = [r, g, b, c[3]].map(|v| v.clamp(0.0, 1.0));
+        Color::new(r, g, b, a)
+    }
+
+    /// interpolates in the given space. `t` outside 0..1 extrapolates (and gets clamped to the gamut)
+    pub fn mix(self, other: Color, t: f32, space: ColorSpace) -> Color {
+        let mut a = self.to_space(space);
+        let mut b = other.to_space(space);
+        if let Some((h, chroma)) = space.hue_indices() {
+            if a[chroma] < ACHROMATIC {
+                a[h] = b[h];
+            }
+        }
+        space.align_hue(&a, &mut b);
+        Color::from_space([0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t), space)
+    }
+}
+
+impl FromStr for Color {
+    type Err = TimelineError;
+
+    /// hex (`#rrggbb[aa]`) or ofColor (`r, g, b[, a]` in 0..255)
+    fn from_str(s: &str) -> Result<Color, TimelineError> {
+        if !s.contains(',') {
+            return Color::from_hex(s);
+        }
+
+        let invalid = || TimelineError::InvalidColor(s.to_string());
+        let values = s.split(',')
+            .map(|v| v.trim().parse::<f32>().map(|v| v / 255.0).map_err(|_| invalid()))
+            .collect::<Result<Vec<f32>, TimelineError>>()?;
+        match values[..] {
+            [r, g, b] => Ok(Color::rgb(r, g, b)),
+            [r, g, b, a] => Ok(Color::new(r, g, b, a)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+fn default_alpha() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorEntity {
+    String(String),
+    Rgba {
+        r: f32,
+        g: f32,
+        b: f32,
+        #[serde(default = "default_alpha")]
+        a: f32,
+    },
+    Array(Vec<f32>),
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match ColorEntity::deserialize(deserializer)? {
+            ColorEntity::String(s) => s.parse().map_err(serde::de::Error::custom),
+            ColorEntity::Rgba { r, g, b, a } => Ok(Color::new(r, g, b, a)),
+            ColorEntity::Array(v) => match v[..] {
+                [r, g, b] => Ok(Color::rgb(r, g, b)),
+                [r, g, b, a] => Ok(Color::new(r, g, b, a)),
+                _ => Err(serde::de::Error::invalid_length(v.len(), &"3 or 4 values")),
+            },
+        }
+    }
+}
+
+#[cfg(feature="bevy")]
+impl From<Color> for bevy::render::color::Color {
+    fn from(c: Color) -> Self {
+        bevy::render::color::Color::rgba(c.r, c.g, c.b, c.a)
+    }
+}
+
+#[cfg(feature="bevy")]
+impl From<bevy::render::color::Color> for Color {
+    fn from(c: bevy::render::color::Color) -> Self {
+        let [r, g, b, a] = c.as_rgba_f32();
+        Color::new(r, g, b, a)
+    }
+}
+
+/// Color track, interpolated in a selectable `ColorSpace`
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ColorTrack {
+    pub track: Track<Color>,
+    pub space: ColorSpace,
+}
+
+impl ColorTrack {
+    pub fn new(space: ColorSpace) -> ColorTrack {
+        ColorTrack { track: Track::default(), space }
+    }
+
+    pub fn set_space(&mut self, space: ColorSpace) -> &mut Self {
+        self.space = space;
+        self
+    }
+
+    pub fn get_space(&self) -> ColorSpace {
+        self.space
+    }
+}
+
+/// interpolated in the default space (`ColorSpace::LinearRgb`)
+impl From<Track<Color>> for ColorTrack {
+    fn from(track: Track<Color>) -> Self {
+        ColorTrack { track, space: ColorSpace::default() }
+    }
+}
+
+impl TimelineTrackImpl<Color> for ColorTrack {
+    fn get_easing_value_wrap(
+        &self,
+        time: f64, start_value: Color, next_value: Color,
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType) -> Color
+    {
+        let t = easing::easing(time, 0.0, 1.0, duration, easing_function, easing_type) as f32;
+        start_value.mix(next_value, t, self.space)
+    }
+
+    fn get_offset_value_wrap(&self, value: Color, from_value: Color, to_value: Color, count: i64) -> Color {
+        let v = value.to_space(self.space);
+        let from = from_value.to_space(self.space);
+        let mut to = to_value.to_space(self.space);
+        self.space.align_hue(&from, &mut to);
+        Color::from_space([0, 1, 2, 3].map(|i| v[i] + (to[i] - from[i]) * count as f32), self.space)
+    }
+
+    fn get_linear_combination_wrap(&self, terms: &[(Color, f64)]) -> Color {
+        let components: Vec<([f32; 4], f64)> = terms.iter().map(|(c, w)| (c.to_space(self.space), *w)).collect();
+        let chroma = self.space.hue_indices().map(|(_, chroma)| chroma);
+        let reference = components.iter()
+            .map(|(c, _)| *c)
+            .find(|c| !matches!(chroma, Some(i) if c[i] < ACHROMATIC))
+            .unwrap_or([0.0; 4]);
+
+        let mut sum = [0.0; 4];
+        for (mut c, w) in components {
+            self.space.align_hue(&reference, &mut c);
+            for i in 0..4 {
+                sum[i] += c[i] as f64 * w;
+            }
+        }
+        Color::from_space(sum.map(|v| v as f32), self.space)
+    }
+
+    /// euclidean distance of the components in the track's space
+    fn get_distance_wrap(&self, a: Color, b: Color) -> f32 {
+        let a = a.to_space(self.space);
+        let mut b = b.to_space(self.space);
+        self.space.align_hue(&a, &mut b);
+        (0..4).map(|i| (a[i] - b[i]).powi(2)).sum::<f32>().sqrt()
+    }
+
+    fn get_keyframes(&self) -> &Vec<Keyframe<Color>> {
+        &self.track.keyframes
+    }
+
+    fn get_keyframes_mut(&mut self) -> &mut Vec<Keyframe<Color>> {
+        &mut self.track.keyframes
+    }
+
+    fn get_pre_extrapolation(&self) -> Extrapolation {
+        self.track.pre_extrapolation
+    }
+
+    fn get_post_extrapolation(&self) -> Extrapolation {
+        self.track.post_extrapolation
+    }
+
+    fn get_interpolation(&self) -> TrackInterpolation {
+        self.track.interpolation
+    }
+
+    fn get_rounding(&self) -> Rounding {
+        self.track.rounding
+    }
+
+    fn get_duplicate_policy(&self) -> DuplicateTimePolicy {
+        self.track.duplicate_policy
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    let c = c.max(0.0);
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// hue in turns, 0 if the color is gray
+fn rgb_to_hue(r: f32, g: f32, b: f32, max: f32, delta: f32) -> f32 {
+    if delta == 0.0 {
+        0.0
+    } else if max == r {
+        ((g - b) / delta).rem_euclid(6.0) / 6.0
+    } else if max == g {
+        ((b - r) / delta + 2.0) / 6.0
+    } else {
+        ((r - g) / delta + 4.0) / 6.0
+    }
+}
+
+/// rgb from hue (turns), chroma and the amount added to every channel
+fn hue_to_rgb(h: f32, chroma: f32, m: f32) -> [f32; 3] {
+    let h6 = h * 6.0;
+    let x = chroma * (1.0 - (h6.rem_euclid(2.0) - 1.0).abs());
+    let [r, g, b] = match h6 as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+    [r + m, g + m, b + m]
+}
+
+fn rgb_to_hsv(r: f32, g: f32, b: f32) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let delta = max - r.min(g).min(b);
+    let s = if max > 0.0 { delta / max } else { 0.0 };
+    [rgb_to_hue(r, g, b, max, delta), s, max]
+}
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> [f32; 3] {
+    let chroma = v * s;
+    hue_to_rgb(h, chroma, v - chroma)
+}
+
+fn rgb_to_hsl(r: f32, g: f32, b: f32) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let l = (max + min) / 2.0;
+    let s = if delta == 0.0 { 0.0 } else { delta / (1.0 - (2.0 * l - 1.0).abs()) };
+    [rgb_to_hue(r, g, b, max, delta), s, l]
+}
+
+fn hsl_to_rgb(h: f32, s: f32, l: f32) -> [f32; 3] {
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    hue_to_rgb(h, chroma, l - chroma / 2.0)
+}
+
+/// https://bottosson.github.io/posts/oklab/
+#[allow(clippy::excessive_precision)] // coefficients are kept as published
+fn linear_to_oklab([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+}
+
+#[allow(clippy::excessive_precision)]
+fn oklab_to_linear([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+    [
+        4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_,
+        -1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_,
+        -0.0041960863 * l_ - 0.7034186147 * m_ + 1.7076147010 * s_,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use assert_float_eq::assert_float_absolute_eq;
+    use assert_float_eq::afe_is_absolute_eq;
+    use assert_float_eq::afe_absolute_error_msg;
+    use assert_float_eq::afe_abs;
+
+    use crate::loader::{TimelineXMLLoader, XMLTrackLoader};
+    use crate::{Timeline, TimelineTrack, TrackGetter};
+
+    use super::*;
+
+    fn assert_color_eq(a: Color, b: Color) {
+        assert_float_absolute_eq!(a.r, b.r, 0.002);
+        assert_float_absolute_eq!(a.g, b.g, 0.002);
+        assert_float_absolute_eq!(a.b, b.b, 0.002);
+        assert_float_absolute_eq!(a.a, b.a, 0.002);
+    }
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(Color::from_hex("#ff8000").unwrap(), Color::from_rgba8(255, 128, 0, 255));
+        assert_eq!(Color::from_hex("ff800080").unwrap(), Color::from_rgba8(255, 128, 0, 128));
+        assert_eq!("255, 128, 0".parse::<Color>().unwrap(), Color::from_rgba8(255, 128, 0, 255));
+        assert_eq!("255, 128, 0, 64".parse::<Color>().unwrap(), Color::from_rgba8(255, 128, 0, 64));
+        assert_eq!(Color::from_hex("#ff80"), Err(TimelineError::InvalidColor("#ff80".to_string())));
+        assert!("1, 2".parse::<Color>().is_err());
+        assert_eq!(Color::from_rgba8(255, 128, 0, 255).to_hex(), "#ff8000");
+        assert_eq!(Color::from_rgba8(255, 128, 0, 16).to_hex(), "#ff800010");
+    }
+
+    #[test]
+    fn space_round_trip_test() {
+        let c = Color::new(0.9, 0.3, 0.1, 0.5);
+        for space in [ColorSpace::Srgb, ColorSpace::LinearRgb, ColorSpace::Hsv, ColorSpace::Hsl, ColorSpace::Oklab, ColorSpace::OkLch] {
+            assert_color_eq(Color::from_space(c.to_space(space), space), c);
+        }
+    }
+
+    #[test]
+    fn mix_linear_rgb_test() {
+        // gamma-correct midpoint of red and green is brighter than the sRGB one
+        let red = Color::rgb(1.0, 0.0, 0.0);
+        let green = Color::rgb(0.0, 1.0, 0.0);
+        assert_color_eq(red.mix(green, 0.5, ColorSpace::Srgb), Color::rgb(0.5, 0.5, 0.0));
+        let c = red.mix(green, 0.5, ColorSpace::LinearRgb);
+        assert_float_absolute_eq!(c.r, 0.7354, 0.001);
+        assert_float_absolute_eq!(c.g, 0.7354, 0.001);
+    }
+
+    #[test]
+    fn mix_hue_wrap_test() {
+        // hue 0.9 -> 0.1 goes through red (0.0), not through cyan (0.5)
+        let a = Color::from_space([0.9, 1.0, 1.0, 1.0], ColorSpace::Hsv);
+        let b = Color::from_space([0.1, 1.0, 1.0, 1.0], ColorSpace::Hsv);
+        assert_color_eq(a.mix(b, 0.5, ColorSpace::Hsv), Color::rgb(1.0, 0.0, 0.0));
+        assert_color_eq(a.mix(b, 0.5, ColorSpace::Hsl), Color::rgb(1.0, 0.0, 0.0));
+
+        // gray takes the hue of the other color
+        let c = Color::rgb(0.5, 0.5, 0.5).mix(Color::rgb(0.0, 0.0, 1.0), 0.5, ColorSpace::Hsv);
+        assert_float_absolute_eq!(c.to_space(ColorSpace::Hsv)[0], 2.0 / 3.0, 0.001);
+    }
+
+    #[test]
+    fn mix_oklab_test() {
+        let black = Color::BLACK;
+        let white = Color::WHITE;
+        // perceptual middle gray
+        let c = black.mix(white, 0.5, ColorSpace::Oklab);
+        assert_float_absolute_eq!(c.to_space(ColorSpace::Oklab)[0], 0.5, 0.001);
+        assert_float_absolute_eq!(c.r, c.g, 0.001);
+        assert_float_absolute_eq!(c.g, c.b, 0.001);
+
+        // OkLCh keeps the chroma between two saturated colors
+        let red = Color::rgb(1.0, 0.0, 0.0);
+        let blue = Color::rgb(0.0, 0.0, 1.0);
+        let lab = red.mix(blue, 0.5, ColorSpace::Oklab).to_space(ColorSpace::OkLch)[1];
+        let lch = red.mix(blue, 0.5, ColorSpace::OkLch).to_space(ColorSpace::OkLch)[1];
+        assert!(lch > lab);
+    }
+
+    #[test]
+    fn color_track_test() {
+        let mut t = ColorTrack::new(ColorSpace::Srgb);
+        t.add_keyframe(Keyframe::new(Duration::from_secs(0), Color::rgb(1.0, 0.0, 0.0)));
+        t.add_keyframe(Keyframe::new(Duration::from_secs(1), Color::rgb(0.0, 1.0, 0.0)));
+        assert_color_eq(t.get_value(Duration::from_millis(500)), Color::rgb(0.5, 0.5, 0.0));
+
+        t.set_space(ColorSpace::Hsv);
+        assert_color_eq(t.get_value(Duration::from_millis(500)), Color::rgb(1.0, 1.0, 0.0));
+        assert_color_eq(t.get_value(Duration::from_secs(2)), Color::rgb(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn color_xml_load_test() {
+        let xml = r##"
+<keyframes>
+    <key>
+        <easefunc>0</easefunc>
+        <easetype>0</easetype>
+        <time>00:00:00:000</time>
+        <value>#ff0000</value>
+    </key>
+    <key>
+        <easefunc>0</easefunc>
+        <easetype>0</easetype>
+        <time>00:00:01:000</time>
+        <value>#0000ff80</value>
+    </key>
+</keyframes>"##;
+
+        let track = Track::<Color>::load_xml_str(xml).unwrap();
+        assert_eq!(track.keyframes[1].value, Color::from_rgba8(0, 0, 255, 128));
+
+        let mut tl = Timeline::new();
+        tl.load_xml_str::<Color>("color", xml).unwrap();
+        tl.try_get_mut("color").unwrap().as_color_track_mut().set_space(ColorSpace::Srgb);
+        let c: Color = tl.get_value("color", Duration::from_millis(500)).into();
+        assert_color_eq(c, Color::new(0.5, 0.0, 0.5, (1.0 + 128.0 / 255.0) / 2.0));
+    }
+
+    #[test]
+    fn of_color_track_load_test() {
+        // ofxTLColorTrack keys: palette sample position and the sampled ofColor, no easing
+        let xml = r#"
+<keyframes>
+    <key>
+        <time>00:00:00:000</time>
+        <colorx>0.1</colorx>
+        <colory>0.5</colory>
+        <color>255, 255, 255, 255</color>
+    </key>
+    <key>
+        <time>00:00:02:000</time>
+        <colorx>0.8</colorx>
+        <colory>0.2</colory>
+        <color>0, 0, 0, 255</color>
+    </key>
+</keyframes>"#;
+
+        let mut tl = Timeline::new();
+        tl.load_xml_str::<Color>("color", xml).unwrap();
+        let track = tl.try_get("color").unwrap().as_color_track();
+        assert_eq!(track.get_space(), ColorSpace::LinearRgb);
+        assert_eq!(track.get_keyframe(0).unwrap().value, Color::WHITE);
+        let c = track.get_value(Duration::from_secs(1));
+        assert_float_absolute_eq!(c.r, 0.7354, 0.001);
+    }
+
+    #[test]
+    fn color_json_value_test() {
+        let c: Color = serde_json::from_str(r#"{"r": 1, "g": 0.5, "b": 0}"#).unwrap();
+        assert_eq!(c, Color::rgb(1.0, 0.5, 0.0));
+        let c: Color = serde_json::from_str("[0, 0, 1, 0.5]").unwrap();
+        assert_eq!(c, Color::new(0.0, 0.0, 1.0, 0.5));
+        let c: Color = serde_json::from_str(r##""#00ff00""##).unwrap();
+        assert_eq!(c, Color::rgb(0.0, 1.0, 0.0));
+        assert!(serde_json::from_str::<Color>(r#""nope""#).is_err());
+    }
+}
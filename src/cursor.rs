@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use crate::{get_value_at_index, keyframe_index, Keyframe, TimelineError, TimelineTrackImpl};
+
+/// how many neighbouring segments are tried before falling back to binary search
+const LINEAR_SEARCH_STEPS: usize = 4;
+
+/// Stateful sampler for sequential playback.
+///
+/// Remembers the keyframe segment of the last query, so sampling a track with
+/// monotonically advancing (or rewinding) time is amortized O(1).
+/// Results are always the same as `TimelineTrack::get_value`.
+///
+/// A cursor does not borrow the track, so it can be stored next to the `Timeline`.
+/// Use one cursor per track.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TrackCursor {
+    index: usize,
+}
+
+impl TrackCursor {
+    pub fn new() -> TrackCursor {
+        TrackCursor { index: 0 }
+    }
+
+    /// forget the cached segment
+    pub fn reset(&mut self) {
+        self.index = 0;
+    }
+
+    /// panics if the track has no keyframes (see `try_get_value`)
+    pub fn get_value<U, T>(&mut self, track: &U, time: Duration) -> T
+    where
+        U: TimelineTrackImpl<T> + ?Sized,
+        T: Copy
+    {
+        self.try_get_value(track, time).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_value<U, T>(&mut self, track: &U, time: Duration) -> Result<T, TimelineError>
+    where
+        U: TimelineTrackImpl<T> + ?Sized,
+        T: Copy
+    {
+        self.index = self.seek(track.get_keyframes(), time);
+        get_value_at_index(track, self.index, time)
+    }
+
+    fn seek<T>(&self, keyframes: &[Keyframe<T>], time: Duration) -> usize {
+        let n = keyframes.len();
+        let mut index = self.index;
+
+        // the track may have been edited since the last query
+        if index > n {
+            return keyframe_index(keyframes, time);
+        }
+
+        for _ in 0..LINEAR_SEARCH_STEPS {
+            if index < n && keyframes[index].time < time {
+                index += 1;
+            } else if index > 0 && keyframes[index - 1].time >= time {
+                index -= 1;
+            } else {
+                return index;
+            }
+        }
+
+        keyframe_index(keyframes, time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::easing::{EasingFunction, EasingType};
+    use crate::{TimelineTrack, Track};
+
+    use super::*;
+
+    fn create_track() -> Track<f32> {
+        let mut t = Track::<f32>::default();
+        for i in 0..100 {
+            t.add_keyframe(Keyframe {
+                time: Duration::from_millis(i * 100 + 50),
+                value: (i % 7) as f32,
+                easing_function: EasingFunction::Cubic,
+                easing_type: EasingType::InOut,
+                ..Default::default()
+            });
+        }
+        t
+    }
+
+    #[test]
+    fn cursor_forward_test() {
+        let t = create_track();
+        let mut cursor = TrackCursor::new();
+        for ms in 0..11000 {
+            let time = Duration::from_millis(ms);
+            assert_eq!(cursor.get_value(&t, time), t.get_value(time));
+        }
+    }
+
+    #[test]
+    fn cursor_backward_test() {
+        let t = create_track();
+        let mut cursor = TrackCursor::new();
+        for ms in (0..11000).rev() {
+            let time = Duration::from_millis(ms);
+            assert_eq!(cursor.get_value(&t, time), t.get_value(time));
+        }
+    }
+
+    #[test]
+    fn cursor_jump_test() {
+        let t = create_track();
+        let mut cursor = TrackCursor::new();
+        for ms in [5000, 20, 9999, 9999, 3, 7777, 0, 12000, 4321] {
+            let time = Duration::from_millis(ms);
+            assert_eq!(cursor.get_value(&t, time), t.get_value(time));
+        }
+    }
+
+    #[test]
+    fn cursor_track_edited_test() {
+        let mut t = create_track();
+        let mut cursor = TrackCursor::new();
+        let time = Duration::from_millis(9000);
+        assert_eq!(cursor.get_value(&t, time), t.get_value(time));
+
+        t.keyframes.truncate(10);
+        assert_eq!(cursor.get_value(&t, time), t.get_value(time));
+
+        t.keyframes.clear();
+        assert_eq!(cursor.try_get_value(&t, time), Err(TimelineError::EmptyTrack));
+    }
+}
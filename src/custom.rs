@@ -0,0 +1,204 @@
+use std::any::{type_name, Any};
+use std::fmt;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+
+use crate::{Interpolate, TimelineError, TimelineTrack, Track};
+
+/// A type-erased track, to keep tracks of user types in a `Timeline`
+///
+/// Get the track back with `downcast_ref` / `downcast_mut`, or sample a `Track<T>`
+/// directly with `Timeline::get_custom_value`.
+///
+/// Custom tracks can not be serialized, `Timeline::to_json_document` fails on a timeline with one.
+pub struct CustomTrack {
+    track: Box<dyn Any + Send + Sync>,
+    type_name: &'static str,
+    duration: fn(&dyn Any) -> Duration,
+    clone: fn(&dyn Any) -> Box<dyn Any + Send + Sync>,
+    eq: fn(&dyn Any, &dyn Any) -> bool,
+}
+
+impl CustomTrack {
+    /// wraps any `TimelineTrack`, usually a `Track<T>` of a type implementing `Interpolate`.
+    /// the track must be `Send + Sync`, so a `Timeline` can be shared between threads (a bevy `Resource`)
+    pub fn new<U, T>(track: U) -> CustomTrack
+    where U: TimelineTrack<T> + Clone + PartialEq + Send + Sync + 'static
+    {
+        CustomTrack {
+            track: Box::new(track),
+            type_name: type_name::<U>(),
+            duration: |track| track.downcast_ref::<U>().map(U::get_duration).unwrap_or_default(),
+            clone: |track| Box::new(track.downcast_ref::<U>().cloned().expect("wrapped type")),
+            eq: |a, b| a.downcast_ref::<U>() == b.downcast_ref::<U>(),
+        }
+    }
+
+    /// type name of the wrapped track (used in error messages)
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    pub fn get_duration(&self) -> Duration {
+        (self.duration)(self.track.as_ref())
+    }
+
+    pub fn is<U: 'static>(&self) -> bool {
+        self.track.is::<U>()
+    }
+
+    pub fn downcast_ref<U: 'static>(&self) -> Option<&U> {
+        self.track.downcast_ref()
+    }
+
+    pub fn downcast_mut<U: 'static>(&mut self) -> Option<&mut U> {
+        self.track.downcast_mut()
+    }
+
+    /// same as `downcast_ref`, but returns `TimelineError::TypeMismatch` instead of `None`
+    pub fn try_downcast_ref<U: 'static>(&self) -> Result<&U, TimelineError> {
+        let found = self.type_name;
+        self.downcast_ref().ok_or(TimelineError::TypeMismatch { expected: type_name::<U>(), found })
+    }
+
+    /// same as `downcast_mut`, but returns `TimelineError::TypeMismatch` instead of `None`
+    pub fn try_downcast_mut<U: 'static>(&mut self) -> Result<&mut U, TimelineError> {
+        let found = self.type_name;
+        self.downcast_mut().ok_or(TimelineError::TypeMismatch { expected: type_name::<U>(), found })
+    }
+
+    /// samples the wrapped track, which must be a `Track<T>`
+    pub fn try_get_value<T>(&self, time: Duration) -> Result<T, TimelineError>
+    where T: Interpolate + DeserializeOwned + 'static
+    {
+        self.try_downcast_ref::<Track<T>>()?.try_get_value(time)
+    }
+}
+
+impl Clone for CustomTrack {
+    fn clone(&self) -> Self {
+        CustomTrack { track: (self.clone)(self.track.as_ref()), ..*self }
+    }
+}
+
+/// tracks of different types are not equal
+impl PartialEq for CustomTrack {
+    fn eq(&self, other: &Self) -> bool {
+        (self.eq)(self.track.as_ref(), other.track.as_ref())
+    }
+}
+
+impl fmt::Debug for CustomTrack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomTrack").field("type_name", &self.type_name).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_float_eq::{afe_is_absolute_eq, afe_absolute_error_msg, afe_abs, assert_float_absolute_eq};
+    use serde::Deserialize;
+
+    use crate::easing::{EasingFunction, EasingType};
+    use crate::{Extrapolation, Keyframe, Timeline, TrackGetter, TrackInterpolation, TrackValueGetter};
+    use crate::test_util::ms;
+
+    use super::*;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+    struct Point {
+        x: f32,
+        y: f32,
+    }
+
+    impl Interpolate for Point {
+        fn get_linear_combination(terms: &[(Point, f64)]) -> Point {
+            Point {
+                x: terms.iter().map(|(p, w)| p.x * *w as f32).sum(),
+                y: terms.iter().map(|(p, w)| p.y * *w as f32).sum(),
+            }
+        }
+
+        fn get_distance(a: Point, b: Point) -> f32 {
+            (a.x - b.x).hypot(a.y - b.y)
+        }
+    }
+
+    /// discrete value, overrides `lerp` to step
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+    enum Mode {
+        #[default]
+        Off,
+        On,
+    }
+
+    impl Interpolate for Mode {
+        fn get_linear_combination(terms: &[(Mode, f64)]) -> Mode {
+            terms[0].0
+        }
+
+        fn lerp(a: Mode, b: Mode, t: f64) -> Mode {
+            if t < 1.0 { a } else { b }
+        }
+    }
+
+    fn create_point_track() -> Track<Point> {
+        let mut track = Track::<Point>::default();
+        track.add_keyframe(Keyframe { time: ms(0), value: Point { x: 0.0, y: 0.0 }, ..Default::default() })
+            .add_keyframe(Keyframe { time: ms(1000), value: Point { x: 2.0, y: 4.0 }, ..Default::default() });
+        track
+    }
+
+    #[test]
+    fn custom_value_track_test() {
+        let mut track = create_point_track();
+        track.get_keyframe_mut(0).unwrap().easing_function = EasingFunction::Linear;
+        track.get_keyframe_mut(0).unwrap().easing_type = EasingType::In;
+        assert_eq!(track.get_value(ms(500)), Point { x: 1.0, y: 2.0 });
+
+        // default offset is a linear combination
+        track.post_extrapolation = Extrapolation::CycleWithOffset;
+        assert_eq!(track.get_value(ms(1500)), Point { x: 3.0, y: 6.0 });
+        let far = Point::get_offset_value(Point::default(), Point::default(), Point { x: 1.0, y: 0.0 }, i64::MIN);
+        assert_eq!(far.x, i64::MIN as f32);
+
+        track.interpolation = TrackInterpolation::CatmullRom;
+        let p = track.get_value(ms(500));
+        assert_float_absolute_eq!(p.x, 1.0, 1e-4);
+        assert_float_absolute_eq!(p.y, 2.0, 1e-4);
+    }
+
+    #[test]
+    fn custom_lerp_test() {
+        let mut track = Track::<Mode>::default();
+        track.add_keyframe(Keyframe { time: ms(0), value: Mode::Off, ..Default::default() })
+            .add_keyframe(Keyframe { time: ms(1000), value: Mode::On, ..Default::default() });
+        assert_eq!(track.get_value(ms(999)), Mode::Off);
+        assert_eq!(track.get_value(ms(1000)), Mode::On);
+    }
+
+    #[test]
+    fn timeline_custom_track_test() {
+        let mut tl = Timeline::new();
+        tl.add("point", CustomTrack::new(create_point_track()));
+
+        assert_eq!(tl.try_get("point").unwrap().get_duration(), ms(1000));
+        assert_eq!(tl.get_max_duration(), ms(1000));
+        assert_eq!(tl.get_custom_value::<Point>("point", ms(1000)), Point { x: 2.0, y: 4.0 });
+        assert!(tl.try_get_value("point", ms(0)).is_err());
+        assert!(tl.try_get_custom_value::<Mode>("point", ms(0)).is_err());
+
+        let custom = tl.try_get_mut("point").unwrap().as_custom_track_mut();
+        assert!(custom.is::<Track<Point>>());
+        custom.downcast_mut::<Track<Point>>().unwrap().keyframes[1].value = Point { x: 1.0, y: 1.0 };
+        assert_eq!(tl.get_custom_value::<Point>("point", ms(1000)), Point { x: 1.0, y: 1.0 });
+    }
+    #[test]
+    fn send_sync_test() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<CustomTrack>();
+        assert_send_sync::<Timeline>();
+        assert_send_sync::<crate::player::TimelinePlayer>();
+    }
+}
@@ -0,0 +1,213 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::loader::read_to_string;
+use crate::{Timeline, TimelineError};
+
+/// Version of the native document format, written in every document
+pub const DOCUMENT_VERSION: u32 = 1;
+
+/// the document written by `to_json_document` and `to_binary_document`
+#[derive(Serialize)]
+struct DocumentRef<'a> {
+    version: u32,
+    timeline: &'a Timeline,
+}
+
+/// `version` is checked separately with `DocumentVersion`
+#[derive(Deserialize)]
+struct Document {
+    timeline: Timeline,
+}
+
+/// read first, so a newer document fails with `UnsupportedDocumentVersion` instead of a parse error
+#[derive(Deserialize)]
+struct DocumentVersion {
+    version: u32,
+}
+
+fn check_version(version: u32) -> Result<(), TimelineError> {
+    if version > DOCUMENT_VERSION {
+        return Err(TimelineError::UnsupportedDocumentVersion(version));
+    }
+    Ok(())
+}
+
+impl Timeline {
+    /// Native, lossless JSON document of the whole timeline
+    ///
+    /// The same document is written as MessagePack by `to_binary_document`. Unlike the ofxTimeline formats
+    /// it keeps the track settings, bezier handles and full `Duration` precision.
+    /// Fails with `TimelineError::Format` if the timeline has a custom track (`TrackVariant::Custom`).
+    pub fn to_json_document(&self) -> Result<String, TimelineError> {
+        Ok(serde_json::to_string_pretty(&DocumentRef { version: DOCUMENT_VERSION, timeline: self })?)
+    }
+
+    pub fn from_json_document(json: &str) -> Result<Timeline, TimelineError> {
+        check_version(serde_json::from_str::<DocumentVersion>(json)?.version)?;
+        let document: Document = serde_json::from_str(json)?;
+        Ok(document.timeline)
+    }
+
+    /// MessagePack, see `to_json_document`
+    pub fn to_binary_document(&self) -> Result<Vec<u8>, TimelineError> {
+        Ok(rmp_serde::to_vec_named(&DocumentRef { version: DOCUMENT_VERSION, timeline: self })?)
+    }
+
+    pub fn from_binary_document(bytes: &[u8]) -> Result<Timeline, TimelineError> {
+        check_version(rmp_serde::from_slice::<DocumentVersion>(bytes)?.version)?;
+        let document: Document = rmp_serde::from_slice(bytes)?;
+        Ok(document.timeline)
+    }
+
+    pub fn save_json_document(&self, path: impl AsRef<Path>) -> Result<(), TimelineError> {
+        std::fs::write(path, self.to_json_document()?)?;
+        Ok(())
+    }
+
+    pub fn load_json_document(path: impl AsRef<Path>) -> Result<Timeline, TimelineError> {
+        Timeline::load_json_document_reader(File::open(path)?)
+    }
+
+    pub fn load_json_document_reader(reader: impl Read) -> Result<Timeline, TimelineError> {
+        Timeline::from_json_document(&read_to_string(reader)?)
+    }
+
+    pub fn save_binary_document(&self, path: impl AsRef<Path>) -> Result<(), TimelineError> {
+        std::fs::write(path, self.to_binary_document()?)?;
+        Ok(())
+    }
+
+    pub fn load_binary_document(path: impl AsRef<Path>) -> Result<Timeline, TimelineError> {
+        Timeline::load_binary_document_reader(File::open(path)?)
+    }
+
+    pub fn load_binary_document_reader(mut reader: impl Read) -> Result<Timeline, TimelineError> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        Timeline::from_binary_document(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::easing::{EasingFunction, EasingType};
+    use crate::{
+        BezierHandle, BezierHandles, Color, ColorSpace, ColorTrack, CustomTrack, EnumKeyframe, EnumTrack, EventTrack, Extrapolation,
+        HandleType, Interpolation, Keyframe, Quat, Rounding, SwitchRange, SwitchTrack, TextKeyframe, TextTrack,
+        TimelineEvent, TimelineTrack, Track, TrackInterpolation,
+    };
+    use crate::test_util::{ms, temp_path};
+
+    use super::*;
+
+    fn create_timeline() -> Timeline {
+        let mut tl = Timeline::new();
+
+        let mut float = Track::<f32> {
+            post_extrapolation: Extrapolation::PingPong,
+            ..Default::default()
+        };
+        float.add_keyframe(Keyframe {
+            easing_function: EasingFunction::Elastic,
+            easing_type: EasingType::InOut,
+            ..Keyframe::new(Duration::new(0, 1), 0.1)
+        });
+        float.add_keyframe(Keyframe { interpolation: Interpolation::Bezier, ..Keyframe::new(ms(1000), 1.0 / 3.0) });
+        float.add_keyframe(Keyframe::new(ms(2000), -2.5));
+        float.keyframes[1].handles = Some(BezierHandles { handle_type: HandleType::Broken, ..Default::default() });
+        float.set_out_handle(1, BezierHandle::new(0.25, 0.5));
+        tl.add("float", float);
+
+        let mut int = Track::<i64> { rounding: Rounding::Nearest, ..Default::default() };
+        int.add_keyframe(Keyframe { interpolation: Interpolation::StepEnd, ..Keyframe::new(ms(0), i64::MAX - 1) });
+        tl.add("int", int);
+
+        let mut vec3 = Track::<(f32, f32, f32)> {
+            interpolation: TrackInterpolation::KochanekBartels { tension: 0.5, continuity: 0.0, bias: -0.5 },
+            ..Default::default()
+        };
+        vec3.add_keyframe(Keyframe::new(ms(0), (1.0, 2.0, 3.0)));
+        tl.add("vec3", vec3);
+
+        let mut quat = Track::<Quat>::default();
+        quat.add_keyframe(Keyframe::new(ms(500), Quat::from_euler_degrees(10.0, 20.0, 30.0)));
+        tl.add("quat", quat);
+
+        let mut color = ColorTrack::new(ColorSpace::OkLch);
+        color.add_keyframe(Keyframe::new(ms(0), Color::new(1.0, 0.5, 0.25, 0.125)));
+        tl.add("color", color);
+
+        let mut bool = Track::<bool>::default();
+        bool.add_keyframe(Keyframe::new(ms(0), true));
+        tl.add("bool", bool);
+
+        let mut switches = SwitchTrack::new();
+        switches.add_range(SwitchRange::named(ms(100), ms(200), "a"));
+        tl.add("switch", switches);
+
+        let mut events = EventTrack::new();
+        events.add_event(TimelineEvent::labeled(ms(300), "bang"));
+        tl.add("event", events);
+
+        let mut text = TextTrack::new();
+        text.add_keyframe(TextKeyframe::reveal(ms(0), "hello \"world\""));
+        tl.add("text", text);
+
+        let mut enums = EnumTrack::new();
+        enums.set_name(2, "two").add_keyframe(EnumKeyframe::new(ms(0), 2));
+        tl.add("enum", enums);
+
+        tl
+    }
+
+    #[test]
+    fn json_round_trip_test() {
+        let tl = create_timeline();
+        let json = tl.to_json_document().unwrap();
+        assert!(json.contains("\"version\": 1"));
+        assert_eq!(Timeline::from_json_document(&json).unwrap(), tl);
+    }
+
+    #[test]
+    fn binary_round_trip_test() {
+        let tl = create_timeline();
+        let bytes = tl.to_binary_document().unwrap();
+        assert_eq!(Timeline::from_binary_document(&bytes).unwrap(), tl);
+    }
+
+    #[test]
+    fn unsupported_version_test() {
+        let json = r#"{"version": 2, "timeline": {"tracks": {"x": {"future_track": {}}}}}"#;
+        let err = Timeline::from_json_document(json).unwrap_err();
+        assert_eq!(err, TimelineError::UnsupportedDocumentVersion(2));
+    }
+
+    #[test]
+    fn custom_track_not_serializable_test() {
+        let mut tl = Timeline::new();
+        tl.add("custom", CustomTrack::new(Track::<f32>::default()));
+        assert!(tl.to_json_document().is_err());
+        assert_eq!(tl.clone(), tl);
+    }
+
+    #[test]
+    fn save_load_file_test() {
+        let tl = create_timeline();
+        let path = temp_path("document_test.json");
+        tl.save_json_document(&path).unwrap();
+        assert_eq!(Timeline::load_json_document(&path).unwrap(), tl);
+        assert_eq!(Timeline::load_json_document_reader(File::open(&path).unwrap()).unwrap(), tl);
+        std::fs::remove_file(&path).unwrap();
+
+        let path = path.with_extension("msgpack");
+        tl.save_binary_document(&path).unwrap();
+        assert_eq!(Timeline::load_binary_document(&path).unwrap(), tl);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -17,9 +17,10 @@
 //             ( see https://github.com/arturoc/ofxTween/blob/master/LICENSE )
 // 
 
-use std::f32::consts::{FRAC_PI_2, PI};
+use std::ops::Sub;
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EasingFunction {
     #[default]
     Linear = 0,
@@ -54,7 +55,8 @@ impl From<u8> for EasingFunction {
     }
 }
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EasingType {
     #[default]
     In = 0,
@@ -73,16 +75,22 @@ impl From<u8> for EasingType {
     }
 }
 
-trait Easing {
+trait Easing<F> {
     /// Calculate the easing value
     /// t: time
     /// b: beginning value (start value)
     /// c: changing value (= end value - start value)
     /// d: duration
-    fn ease(t: f32, b: f32, c: f32, d: f32, easing_type: EasingType) -> f32;
+    fn ease(t: F, b: F, c: F, d: F, easing_type: EasingType) -> F;
 }
 
-pub fn map(v: f32, min_in: f32, max_in: f32, min_out: f32, max_out: f32, easing_function: EasingFunction, easing_type: EasingType) -> f32 {
+/// Floating point types the easing functions are implemented for (`f32` and `f64`)
+pub trait EasingFloat: Copy + PartialOrd + Sub<Output = Self> {
+    /// see `easing`
+    fn easing(time: Self, beginning_value: Self, changing_value: Self, duration: Self, easing_function: EasingFunction, easing_type: EasingType) -> Self;
+}
+
+pub fn map<F: EasingFloat>(v: F, min_in: F, max_in: F, min_out: F, max_out: F, easing_function: EasingFunction, easing_type: EasingType) -> F {
     let t = v - min_in;
     let c = max_out - min_out;
     let d = max_in - min_in;
@@ -90,369 +98,386 @@ pub fn map(v: f32, min_in: f32, max_in: f32, min_out: f32, max_out: f32, easing_
     easing(t, b, c, d, easing_function, easing_type)
 }
 
-pub fn map_clamp(v: f32, min_in: f32, max_in: f32, min_out: f32, max_out: f32, easing_function: EasingFunction, easing_type: EasingType) -> f32 {
-    let v = v.min(max_in).max(min_in);
+pub fn map_clamp<F: EasingFloat>(v: F, min_in: F, max_in: F, min_out: F, max_out: F, easing_function: EasingFunction, easing_type: EasingType) -> F {
+    let v = if v > max_in { max_in } else if v < min_in { min_in } else { v };
     map(v, min_in, max_in, min_out, max_out, easing_function, easing_type)
 }
 
 pub struct EasingLinear;
-impl Easing for EasingLinear {
-    fn ease(t: f32, b: f32, c: f32, d: f32, _easing_type: EasingType) -> f32 {
-        c * t / d + b
-    }
-}
-
 pub struct EasingSine;
-impl Easing for EasingSine {
-    fn ease(t: f32, b: f32, c: f32, d: f32, easing_type: EasingType) -> f32 {
-        match easing_type {
-            EasingType::In => -c * (t / d * FRAC_PI_2).cos() + c + b,
-            EasingType::Out => c * (t / d * FRAC_PI_2).sin() + b,
-            EasingType::InOut => -c / 2.0 * ((PI * t / d).cos() - 1.0) + b,
-        }
-    }
-}
-
 pub struct EasingCircular;
-impl Easing for EasingCircular {
-    // original C++ code:
-    // inline static float easeIn (float t,float b , float c, float d) {
-	// 	return -c * (sqrt(1 - (t/=d)*t) - 1) + b;
-	// }
-	// inline static float easeOut(float t,float b , float c, float d) {
-	// 	return c * sqrt(1 - (t=t/d-1)*t) + b;
-	// }
-
-	// inline static float easeInOut(float t,float b , float c, float d) {
-	// 	if ((t/=d/2) < 1) return c/2 * (1 - sqrt(1 - t*t)) + b;
-	// 	return c/2 * (sqrt(1 - (t-=2)*t) + 1) + b;
-	// }
-
-	// NOTE: (t/=d)*t) is equivalent to (t / d) * (t / d), because of the precedence of the operators
-
-    fn ease(t: f32, b: f32, c: f32, d: f32, easing_type: EasingType) -> f32 {
-        match easing_type {
-            EasingType::In => {
-                let m = t / d;
-                -c * ((1.0 - m * m).sqrt() - 1.0) + b
+pub struct EasingQuadratic;
+pub struct EasingCubic;
+pub struct EasingQuartic;
+pub struct EasingQuintic;
+pub struct EasingExponential;
+pub struct EasingBack;
+pub struct EasingBounce;
+pub struct EasingElastic;
+
+// `Easing` is implemented for `f32` and `f64` from the same code
+macro_rules! impl_easing {
+    ($f:ident, $module:ident) => {
+        mod $module {
+            use std::$f::consts::{FRAC_PI_2, PI};
+
+            use super::*;
+
+            impl Easing<$f> for EasingLinear {
+                fn ease(t: $f, b: $f, c: $f, d: $f, _easing_type: EasingType) -> $f {
+                    c * t / d + b
+                }
             }
-            EasingType::Out => {
-                let m = t / d - 1.0;
-                c * (1.0 - m * m).sqrt() + b
+
+            impl Easing<$f> for EasingSine {
+                fn ease(t: $f, b: $f, c: $f, d: $f, easing_type: EasingType) -> $f {
+                    match easing_type {
+                        EasingType::In => -c * (t / d * FRAC_PI_2).cos() + c + b,
+                        EasingType::Out => c * (t / d * FRAC_PI_2).sin() + b,
+                        EasingType::InOut => -c / 2.0 * ((PI * t / d).cos() - 1.0) + b,
+                    }
+                }
             }
-            EasingType::InOut => {
-                let m = t / d * 2.0;
-                if m < 1.0 {
-                    -c / 2.0 * ((1.0 - m * m).sqrt() - 1.0) + b
-                } else {
-                    let post_fix = m - 2.0;
-                    c / 2.0 * ((1.0 - post_fix * post_fix).sqrt() + 1.0) + b
+
+            impl Easing<$f> for EasingCircular {
+                // original C++ code:
+                // inline static float easeIn (float t,float b , float c, float d) {
+            	// 	return -c * (sqrt(1 - (t/=d)*t) - 1) + b;
+            	// }
+            	// inline static float easeOut(float t,float b , float c, float d) {
+            	// 	return c * sqrt(1 - (t=t/d-1)*t) + b;
+            	// }
+
+            	// inline static float easeInOut(float t,float b , float c, float d) {
+            	// 	if ((t/=d/2) < 1) return c/2 * (1 - sqrt(1 - t*t)) + b;
+            	// 	return c/2 * (sqrt(1 - (t-=2)*t) + 1) + b;
+            	// }
+
+            	// NOTE: (t/=d)*t) is equivalent to (t / d) * (t / d), because of the precedence of the operators
+
+                fn ease(t: $f, b: $f, c: $f, d: $f, easing_type: EasingType) -> $f {
+                    match easing_type {
+                        EasingType::In => {
+                            let m = t / d;
+                            -c * ((1.0 - m * m).sqrt() - 1.0) + b
+                        }
+                        EasingType::Out => {
+                            let m = t / d - 1.0;
+                            c * (1.0 - m * m).sqrt() + b
+                        }
+                        EasingType::InOut => {
+                            let m = t / d * 2.0;
+                            if m < 1.0 {
+                                -c / 2.0 * ((1.0 - m * m).sqrt() - 1.0) + b
+                            } else {
+                                let post_fix = m - 2.0;
+                                c / 2.0 * ((1.0 - post_fix * post_fix).sqrt() + 1.0) + b
+                            }
+                        }
+                    }
                 }
             }
-        }
-    }
-}
 
-pub struct EasingQuadratic;
-impl Easing for EasingQuadratic {
-    fn ease(t: f32, b: f32, c: f32, d: f32, easing_type: EasingType) -> f32 {
-        match easing_type {
-            EasingType::In => c * (t / d).powi(2) + b,
-            EasingType::Out => -c * (t / d) * (t / d - 2.0) + b,
-            // original C++ code:
-            // inline static float easeInOut(float t,float b , float c, float d) {
-            //     if ((t/=d/2) < 1) return c/2*t*t + b;
-            //     return -c/2 * ((--t)*(t-2) - 1) + b;
-            // 
-            //     /*
-            // 
-            //     originally return -c/2 * (((t-2)*(--t)) - 1) + b;
-            //
-            //     I've had to swap (--t)*(t-2) due to diffence in behaviour in
-            //     pre-increment operators between java and c++, after hours
-            //     of joy
-            //
-            //      James George:: The fix refered to above actually broke the equation,
-            //      it would land at 50% all the time at the end
-            //      copying back the original equation from online fixed it...
-            //
-            //      potentially compiler dependent.
-            // */
-            // }
-            EasingType::InOut => {
-                let m = t / d * 2.0;
-                if m < 1.0 {
-                    c / 2.0 * m * m + b
-                } else {
-                    let post_fix = m - 1.0;
-                    -c / 2.0 * (post_fix * (post_fix - 2.0) - 1.0) + b
+            impl Easing<$f> for EasingQuadratic {
+                fn ease(t: $f, b: $f, c: $f, d: $f, easing_type: EasingType) -> $f {
+                    match easing_type {
+                        EasingType::In => c * (t / d).powi(2) + b,
+                        EasingType::Out => -c * (t / d) * (t / d - 2.0) + b,
+                        // original C++ code:
+                        // inline static float easeInOut(float t,float b , float c, float d) {
+                        //     if ((t/=d/2) < 1) return c/2*t*t + b;
+                        //     return -c/2 * ((--t)*(t-2) - 1) + b;
+                        // 
+                        //     /*
+                        // 
+                        //     originally return -c/2 * (((t-2)*(--t)) - 1) + b;
+                        //
+                        //     I've had to swap (--t)*(t-2) due to diffence in behaviour in
+                        //     pre-increment operators between java and c++, after hours
+                        //     of joy
+                        //
+                        //      James George:: The fix refered to above actually broke the equation,
+                        //      it would land at 50% all the time at the end
+                        //      copying back the original equation from online fixed it...
+                        //
+                        //      potentially compiler dependent.
+                        // */
+                        // }
+                        EasingType::InOut => {
+                            let m = t / d * 2.0;
+                            if m < 1.0 {
+                                c / 2.0 * m * m + b
+                            } else {
+                                let post_fix = m - 1.0;
+                                -c / 2.0 * (post_fix * (post_fix - 2.0) - 1.0) + b
+                            }
+                        }
+                    }
                 }
             }
-        }
-    }
-}
 
-pub struct EasingCubic;
-impl Easing for EasingCubic {
-    fn ease(t: f32, b: f32, c: f32, d: f32, easing_type: EasingType) -> f32 {
-        match easing_type {
-            EasingType::In => c * (t / d).powi(3) + b,
-            EasingType::Out => c * ((t / d - 1.0).powi(3) + 1.0) + b,
-            EasingType::InOut => {
-                let m = t / d * 2.0;
-                if m < 1.0 {
-                    c / 2.0 * m.powi(3) + b
-                } else {
-                    let post_fix = m - 2.0;
-                    c / 2.0 * (post_fix.powi(3) + 2.0) + b
+            impl Easing<$f> for EasingCubic {
+                fn ease(t: $f, b: $f, c: $f, d: $f, easing_type: EasingType) -> $f {
+                    match easing_type {
+                        EasingType::In => c * (t / d).powi(3) + b,
+                        EasingType::Out => c * ((t / d - 1.0).powi(3) + 1.0) + b,
+                        EasingType::InOut => {
+                            let m = t / d * 2.0;
+                            if m < 1.0 {
+                                c / 2.0 * m.powi(3) + b
+                            } else {
+                                let post_fix = m - 2.0;
+                                c / 2.0 * (post_fix.powi(3) + 2.0) + b
+                            }
+                        }
+                    }
                 }
             }
-        }
-    }
-}
 
-pub struct EasingQuartic;
-impl Easing for EasingQuartic {
-    fn ease(t: f32, b: f32, c: f32, d: f32, easing_type: EasingType) -> f32 {
-        match easing_type {
-            EasingType::In => c * (t / d).powi(4) + b,
-            EasingType::Out => -c * ((t / d - 1.0).powi(4) - 1.0) + b,
-            EasingType::InOut => {
-                let m = t / d * 2.0;
-                if m < 1.0 {
-                    c / 2.0 * m.powi(4) + b
-                } else {
-                    let post_fix = m - 2.0;
-                    -c / 2.0 * (post_fix.powi(4) - 2.0) + b
+            impl Easing<$f> for EasingQuartic {
+                fn ease(t: $f, b: $f, c: $f, d: $f, easing_type: EasingType) -> $f {
+                    match easing_type {
+                        EasingType::In => c * (t / d).powi(4) + b,
+                        EasingType::Out => -c * ((t / d - 1.0).powi(4) - 1.0) + b,
+                        EasingType::InOut => {
+                            let m = t / d * 2.0;
+                            if m < 1.0 {
+                                c / 2.0 * m.powi(4) + b
+                            } else {
+                                let post_fix = m - 2.0;
+                                -c / 2.0 * (post_fix.powi(4) - 2.0) + b
+                            }
+                        }
+                    }
                 }
             }
-        }
-    }
-}
 
-pub struct EasingQuintic;
-impl Easing for EasingQuintic {
-    fn ease(t: f32, b: f32, c: f32, d: f32, easing_type: EasingType) -> f32 {
-        match easing_type {
-            EasingType::In => c * (t / d).powi(5) + b,
-            EasingType::Out => c * ((t / d - 1.0).powi(5) + 1.0) + b,
-            EasingType::InOut => {
-                let m = t / d * 2.0;
-                if m < 1.0 {
-                    c / 2.0 * m.powi(5) + b
-                } else {
-                    let post_fix = m - 2.0;
-                    c / 2.0 * (post_fix.powi(5) + 2.0) + b
+            impl Easing<$f> for EasingQuintic {
+                fn ease(t: $f, b: $f, c: $f, d: $f, easing_type: EasingType) -> $f {
+                    match easing_type {
+                        EasingType::In => c * (t / d).powi(5) + b,
+                        EasingType::Out => c * ((t / d - 1.0).powi(5) + 1.0) + b,
+                        EasingType::InOut => {
+                            let m = t / d * 2.0;
+                            if m < 1.0 {
+                                c / 2.0 * m.powi(5) + b
+                            } else {
+                                let post_fix = m - 2.0;
+                                c / 2.0 * (post_fix.powi(5) + 2.0) + b
+                            }
+                        }
+                    }
                 }
             }
-        }
-    }
-}
 
-pub struct EasingExponential;
-impl Easing for EasingExponential {
-    fn ease(t: f32, b: f32, c: f32, d: f32, easing_type: EasingType) -> f32 {
-        match easing_type {
-            EasingType::In => {
-                if t == 0.0 {
-                    b
-                } else {
-                    c * 2.0f32.powf(10.0 * (t / d - 1.0)) + b
+            impl Easing<$f> for EasingExponential {
+                fn ease(t: $f, b: $f, c: $f, d: $f, easing_type: EasingType) -> $f {
+                    match easing_type {
+                        EasingType::In => {
+                            if t == 0.0 {
+                                b
+                            } else {
+                                c * $f::powf(2.0, 10.0 * (t / d - 1.0)) + b
+                            }
+                        }
+                        EasingType::Out => {
+                            if t == d {
+                                b + c
+                            } else {
+                                c * (-$f::powf(2.0, -10.0 * t / d) + 1.0) + b
+                            }
+                        }
+                        EasingType::InOut => {
+                            let m = t / d * 2.0;
+                            if t == 0.0 {
+                                b
+                            } else if t == d {
+                                b + c
+                            } else if m < 1.0 {
+                                c / 2.0 * $f::powf(2.0, 10.0 * (m - 1.0)) + b
+                            } else {
+                                let post_fix = m - 1.0;
+                                c / 2.0 * (-$f::powf(2.0, -10.0 * post_fix / d) + 2.0) + b
+                            }
+                        }
+                    }
                 }
             }
-            EasingType::Out => {
-                if t == d {
-                    b + c
-                } else {
-                    c * (-2.0f32.powf(-10.0 * t / d) + 1.0) + b
+
+            impl Easing<$f> for EasingBack {
+                fn ease(t: $f, b: $f, c: $f, d: $f, easing_type: EasingType) -> $f {
+                    match easing_type {
+                        EasingType::In => back_ease_in_s(t, b, c, d, 1.70158),
+                        EasingType::Out => back_ease_out_s(t, b, c, d, 1.70158),
+                        EasingType::InOut => back_ease_in_out_s(t, b, c, d, 1.70158),
+                    }
                 }
             }
-            EasingType::InOut => {
+
+            fn back_ease_in_s(t: $f, b: $f, c: $f, d: $f, s: $f) -> $f {
+                let post_fix = t / d;
+                c * post_fix * post_fix * ((s + 1.0) * post_fix - s) + b
+            }
+
+            fn back_ease_out_s(t: $f, b: $f, c: $f, d: $f, s: $f) -> $f {
+                let post_fix = t / d - 1.0;
+                c * (post_fix * post_fix * ((s + 1.0) * post_fix + s) + 1.0) + b
+            }
+
+            fn back_ease_in_out_s(t: $f, b: $f, c: $f, d: $f, s: $f) -> $f {
+                let s = s * 1.525;
                 let m = t / d * 2.0;
-                if t == 0.0 {
-                    b
-                } else if t == d {
-                    b + c
-                } else if m < 1.0 {
-                    c / 2.0 * 2.0f32.powf(10.0 * (m - 1.0)) + b
+                if m < 1.0 {
+                    c / 2.0 * m * m * ((s + 1.0) * m - s) + b
                 } else {
-                    let post_fix = m - 1.0;
-                    c / 2.0 * (-2.0f32.powf(-10.0 * post_fix / d) + 2.0) + b
+                    let post_fix = m - 2.0;
+                    c / 2.0 * (post_fix * post_fix * ((s + 1.0) * post_fix + s) + 2.0) + b
                 }
             }
-        }
-    }
-}
-
-pub struct EasingBack;
-impl Easing for EasingBack {
-    fn ease(t: f32, b: f32, c: f32, d: f32, easing_type: EasingType) -> f32 {
-        match easing_type {
-            EasingType::In => EasingBack::ease_in_s(t, b, c, d, 1.70158),
-            EasingType::Out => EasingBack::ease_out_s(t, b, c, d, 1.70158),
-            EasingType::InOut => EasingBack::ease_in_out_s(t, b, c, d, 1.70158),
-        }
-    }
-}
 
-impl EasingBack {
-    fn ease_in_s(t: f32, b: f32, c: f32, d: f32, s: f32) -> f32 {
-        let post_fix = t / d;
-        c * post_fix * post_fix * ((s + 1.0) * post_fix - s) + b
-    }
+            impl Easing<$f> for EasingBounce {
+                fn ease(t: $f, b: $f, c: $f, d: $f, easing_type: EasingType) -> $f {
+                    match easing_type {
+                        EasingType::In => c - EasingBounce::ease(d-t, 0.0, c, d, EasingType::Out) + b,
+                        // original C++ code:
+
+                        // inline static float easeOut(float t,float b , float c, float d) {
+                        //     if ((t/=d) < (1/2.75f)) {
+                        //         return c*(7.5625f*t*t) + b;
+                        //     } else if (t < (2/2.75f)) {
+                        //         float postFix = t-=(1.5f/2.75f);
+                        //         return c*(7.5625f*(postFix)*t + .75f) + b;
+                        //     } else if (t < (2.5/2.75)) {
+                        //             float postFix = t-=(2.25f/2.75f);
+                        //         return c*(7.5625f*(postFix)*t + .9375f) + b;
+                        //     } else {
+                        //         float postFix = t-=(2.625f/2.75f);
+                        //         return c*(7.5625f*(postFix)*t + .984375f) + b;
+                        //     }
+                        // }
+
+                        EasingType::Out => {
+                            let m = t/d;
+                            if m < 1.0 / 2.75 {
+                                c * (7.5625 * m * m) + b
+                            } else if m < 2.0 / 2.75 {
+                                let p = m - 1.5 / 2.75;
+                                c * (7.5625 * p * p + 0.75) + b
+                            } else if m < 2.5 / 2.75 {
+                                let p = m - 2.25 / 2.75;
+                                c * (7.5625 * p * p + 0.9375) + b
+                            } else {
+                                let p = m - 2.625 / 2.75;
+                                c * (7.5625 * p * p + 0.984375) + b
+                            }
+                        }
+                        EasingType::InOut => {
+                            if t < d / 2.0 {
+                                EasingBounce::ease(t * 2.0, 0.0, c, d, EasingType::In) * 0.5 + b 
+                            } else {
+                                EasingBounce::ease(t * 2.0 - d, 0.0, c, d, EasingType::Out) * 0.5 + c * 0.5 + b
+                            }
+                        }
+                    }
+                }
+            }
 
-    fn ease_out_s(t: f32, b: f32, c: f32, d: f32, s: f32) -> f32 {
-        let post_fix = t / d - 1.0;
-        c * (post_fix * post_fix * ((s + 1.0) * post_fix + s) + 1.0) + b
-    }
+            impl Easing<$f> for EasingElastic {
+                fn ease(t: $f, b: $f, c: $f, d: $f, easing_type: EasingType) -> $f {
+                    match easing_type {
+                        EasingType::In => elastic_ease_in_pow(t, b, c, d, 10.0),
+                        EasingType::Out => elastic_ease_out_pow(t, b, c, d, 10.0),
+                        EasingType::InOut => elastic_ease_in_out_pow(t, b, c, d, 10.0),
+                    }
+                }
+            }
 
-    fn ease_in_out_s(t: f32, b: f32, c: f32, d: f32, s: f32) -> f32 {
-        let s = s * 1.525;
-        let m = t / d * 2.0;
-        if m < 1.0 {
-            c / 2.0 * m * m * ((s + 1.0) * m - s) + b
-        } else {
-            let post_fix = m - 2.0;
-            c / 2.0 * (post_fix * post_fix * ((s + 1.0) * post_fix + s) + 2.0) + b
-        }
-    }
-    
-}
+            fn elastic_ease_in_pow(t: $f, b: $f, c: $f, d: $f, power: $f) -> $f {
+                if t == 0.0 {
+                    return b;
+                }
+                if t == d {
+                    return b + c;
+                }
+                let p = d * 0.3;
+                let a = c;
+                let s = p / 4.0;
+                let post_fix = a * $f::powf(2.0, power * (t - 1.0));
+                -(post_fix * ((t * d - s) * (2.0 * PI) / p).sin()) + b
+            }
 
-pub struct EasingBounce;
-impl Easing for EasingBounce {
-    fn ease(t: f32, b: f32, c: f32, d: f32, easing_type: EasingType) -> f32 {
-        match easing_type {
-            EasingType::In => c - EasingBounce::ease(d-t, 0.0, c, d, EasingType::Out) + b,
-            // original C++ code:
-
-            // inline static float easeOut(float t,float b , float c, float d) {
-            //     if ((t/=d) < (1/2.75f)) {
-            //         return c*(7.5625f*t*t) + b;
-            //     } else if (t < (2/2.75f)) {
-            //         float postFix = t-=(1.5f/2.75f);
-            //         return c*(7.5625f*(postFix)*t + .75f) + b;
-            //     } else if (t < (2.5/2.75)) {
-            //             float postFix = t-=(2.25f/2.75f);
-            //         return c*(7.5625f*(postFix)*t + .9375f) + b;
-            //     } else {
-            //         float postFix = t-=(2.625f/2.75f);
-            //         return c*(7.5625f*(postFix)*t + .984375f) + b;
-            //     }
-            // }
-
-            EasingType::Out => {
-                let m = t/d;
-                if m < 1.0 / 2.75 {
-                    c * (7.5625 * m * m) + b
-                } else if m < 2.0 / 2.75 {
-                    let p = m - 1.5 / 2.75;
-                    c * (7.5625 * p * p + 0.75) + b
-                } else if m < 2.5 / 2.75 {
-                    let p = m - 2.25 / 2.75;
-                    c * (7.5625 * p * p + 0.9375) + b
-                } else {
-                    let p = m - 2.625 / 2.75;
-                    c * (7.5625 * p * p + 0.984375) + b
+            fn elastic_ease_out_pow(t: $f, b: $f, c: $f, d: $f, power: $f) -> $f {
+                if t == 0.0 {
+                    return b;
                 }
+                if t == d {
+                    return b + c;
+                }
+                let p = d * 0.3;
+                let a = c;
+                let s = p / 4.0;
+                a * $f::powf(2.0, -power * t) * ((t * d - s) * (2.0 * PI) / p).sin() + c + b
             }
-            EasingType::InOut => {
-                if t < d / 2.0 {
-                    EasingBounce::ease(t * 2.0, 0.0, c, d, EasingType::In) * 0.5 + b 
+
+            fn elastic_ease_in_out_pow(t: $f, b: $f, c: $f, d: $f, power: $f) -> $f {
+                if t == 0.0 {
+                    return b;
+                }
+                if t == d {
+                    return b + c;
+                }
+                if t / d / 2.0 == 2.0 {
+                    return b + c;
+                }
+                let p = d * 0.3 * 1.5;
+                let a = c;
+                let s = p / 4.0;
+                if t < 1.0 {
+                    let post_fix = a * $f::powf(2.0, power * (t - 1.0));
+                    -0.5 * (post_fix * ((t * d - s) * (2.0 * PI) / p).sin()) + b
                 } else {
-                    EasingBounce::ease(t * 2.0 - d, 0.0, c, d, EasingType::Out) * 0.5 + c * 0.5 + b
+                    let post_fix = a * $f::powf(2.0, -power * (t - 1.0));
+                    post_fix * ((t * d - s) * (2.0 * PI) / p).sin() * 0.5 + c + b
                 }
             }
-        }
-    }
-}
 
-pub struct EasingElastic;
-impl Easing for EasingElastic {
-    fn ease(t: f32, b: f32, c: f32, d: f32, easing_type: EasingType) -> f32 {
-        match easing_type {
-            EasingType::In => EasingElastic::ease_in_pow(t, b, c, d, 10.0),
-            EasingType::Out => EasingElastic::ease_out_pow(t, b, c, d, 10.0),
-            EasingType::InOut => EasingElastic::ease_in_out_pow(t, b, c, d, 10.0),
+            impl EasingFloat for $f {
+                fn easing(time: $f, beginning_value: $f, changing_value: $f, duration: $f, easing_function: EasingFunction, easing_type: EasingType) -> $f {
+                    let t = time;
+                    let b = beginning_value;
+                    let c = changing_value;
+                    let d = duration;
+                    match easing_function {
+                        EasingFunction::Linear => EasingLinear::ease(t, b, c, d, easing_type),
+                        EasingFunction::Sine => EasingSine::ease(t, b, c, d, easing_type),
+                        EasingFunction::Circular => EasingCircular::ease(t, b, c, d, easing_type),
+                        EasingFunction::Quadratic => EasingQuadratic::ease(t, b, c, d, easing_type),
+                        EasingFunction::Cubic => EasingCubic::ease(t, b, c, d, easing_type),
+                        EasingFunction::Quartic => EasingQuartic::ease(t, b, c, d, easing_type),
+                        EasingFunction::Quintic => EasingQuintic::ease(t, b, c, d, easing_type),
+                        EasingFunction::Exponential => EasingExponential::ease(t, b, c, d, easing_type),
+                        EasingFunction::Back => EasingBack::ease(t, b, c, d, easing_type),
+                        EasingFunction::Bounce => EasingBounce::ease(t, b, c, d, easing_type),
+                        EasingFunction::Elastic => EasingElastic::ease(t, b, c, d, easing_type),
+                    }
+                }
+            }
         }
-    }
+    };
 }
 
-impl EasingElastic {
-    fn ease_in_pow(t: f32, b: f32, c: f32, d: f32, power: f32) -> f32 {
-        if t == 0.0 {
-            return b;
-        }
-        if t == d {
-            return b + c;
-        }
-        let p = d * 0.3;
-        let a = c;
-        let s = p / 4.0;
-        let post_fix = a * 2.0f32.powf(power * (t - 1.0));
-        -(post_fix * ((t * d - s) * (2.0 * PI) / p).sin()) + b
-    }
-
-    fn ease_out_pow(t: f32, b: f32, c: f32, d: f32, power: f32) -> f32 {
-        if t == 0.0 {
-            return b;
-        }
-        if t == d {
-            return b + c;
-        }
-        let p = d * 0.3;
-        let a = c;
-        let s = p / 4.0;
-        a * 2.0f32.powf(-power * t) * ((t * d - s) * (2.0 * PI) / p).sin() + c + b
-    }
-
-    fn ease_in_out_pow(t: f32, b: f32, c: f32, d: f32, power: f32) -> f32 {
-        if t == 0.0 {
-            return b;
-        }
-        if t == d {
-            return b + c;
-        }
-        if t / d / 2.0 == 2.0 {
-            return b + c;
-        }
-        let p = d * 0.3 * 1.5;
-        let a = c;
-        let s = p / 4.0;
-        if t < 1.0 {
-            let post_fix = a * 2.0f32.powf(power * (t - 1.0));
-            -0.5 * (post_fix * ((t * d - s) * (2.0 * PI) / p).sin()) + b
-        } else {
-            let post_fix = a * 2.0f32.powf(-power * (t - 1.0));
-            post_fix * ((t * d - s) * (2.0 * PI) / p).sin() * 0.5 + c + b
-        }
-    }
-}
+impl_easing!(f32, easing_f32);
+impl_easing!(f64, easing_f64);
 
 /// Calculate the easing value
 /// change_value = end_value - start_value, of the output value
 /// duration = total time of the easing
-pub fn easing(time: f32, beginning_value: f32, changing_value: f32, duration: f32, easing_function: EasingFunction, easing_type: EasingType) -> f32 {
-    let t = time;
-    let b = beginning_value;
-    let c = changing_value;
-    let d = duration;
-    match easing_function {
-        EasingFunction::Linear => EasingLinear::ease(t, b, c, d, easing_type),
-        EasingFunction::Sine => EasingSine::ease(t, b, c, d, easing_type),
-        EasingFunction::Circular => EasingCircular::ease(t, b, c, d, easing_type),
-        EasingFunction::Quadratic => EasingQuadratic::ease(t, b, c, d, easing_type),
-        EasingFunction::Cubic => EasingCubic::ease(t, b, c, d, easing_type),
-        EasingFunction::Quartic => EasingQuartic::ease(t, b, c, d, easing_type),
-        EasingFunction::Quintic => EasingQuintic::ease(t, b, c, d, easing_type),
-        EasingFunction::Exponential => EasingExponential::ease(t, b, c, d, easing_type),
-        EasingFunction::Back => EasingBack::ease(t, b, c, d, easing_type),
-        EasingFunction::Bounce => EasingBounce::ease(t, b, c, d, easing_type),
-        EasingFunction::Elastic => EasingElastic::ease(t, b, c, d, easing_type),
-    }
+pub fn easing<F: EasingFloat>(time: F, beginning_value: F, changing_value: F, duration: F, easing_function: EasingFunction, easing_type: EasingType) -> F {
+    F::easing(time, beginning_value, changing_value, duration, easing_function, easing_type)
 }
 
 #[cfg(test)]
+#[allow(non_upper_case_globals)]
 mod tests {
     use assert_float_eq::assert_float_relative_eq;
     use assert_float_eq::afe_is_relative_eq;
@@ -473,6 +498,33 @@ mod tests {
         assert_eq!(v, 100.0);
     }
 
+    #[test]
+    fn easing_f64_test() {
+        let list = [
+            EasingFunction::Linear,
+            EasingFunction::Sine,
+            EasingFunction::Cubic,
+            EasingFunction::Exponential,
+            EasingFunction::Back,
+            EasingFunction::Bounce,
+            EasingFunction::Elastic,
+        ];
+        for easing_func in list {
+            for easing_type in [EasingType::In, EasingType::Out, EasingType::InOut] {
+                for t in [0.0, 0.25, 0.5, 0.75, 1.0] {
+                    let v32 = easing(t as f32, 10.0, 100.0, 1.0, easing_func, easing_type);
+                    let v64 = easing(t, 10.0, 100.0, 1.0, easing_func, easing_type);
+                    assert_float_relative_eq!(v64 as f32, v32, 0.0001);
+                }
+            }
+        }
+
+        // 2^53 is out of the f32 range of exact integers
+        let big = 9_007_199_254_740_992.0;
+        assert_eq!(easing(0.5, big, 2.0, 1.0, EasingFunction::Linear, EasingType::In), big + 1.0);
+        assert_eq!(map(0.5f64, 0.0, 1.0, 0.0, 100.0, EasingFunction::Linear, EasingType::In), 50.0);
+    }
+
     #[test]
     fn easing_linear_test() {
         let f = move |t: f32| -> f32 {
@@ -546,7 +598,8 @@ mod tests {
 
         assert_eq!(f(0.0), 0.0);
         assert_eq!(f(0.25), 38.268345);
-        assert_eq!(f(0.5), 70.710686);
+        // 70.71068 with a correctly rounded sinf (glibc), 70.710686 with the macOS libm
+        assert!(f(0.5) == 70.710686 || f(0.5) == 70.71068);
         assert_eq!(f(0.75), 92.38795);
         assert_eq!(f(1.0), 100.0);
 
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::loader::{read_to_string, duration_to_timecode, escape_xml, one_or_many, timecode_to_duration, xml_text};
+use crate::{xml_to_json, TimelineError};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EnumKeyframe {
+    pub time: Duration,
+    pub code: i32,
+}
+
+impl EnumKeyframe {
+    pub fn new(time: Duration, code: i32) -> EnumKeyframe {
+        EnumKeyframe { time, code }
+    }
+}
+
+/// Track of discrete states (scene names, modes), stored as integer codes with step interpolation
+///
+/// Sampled through `Timeline` as `TrackValue::Int`. Before the first keyframe the first code is held.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EnumTrack {
+    keyframes: Vec<EnumKeyframe>,
+    pub names: HashMap<i32, String>,
+}
+
+impl EnumTrack {
+    pub fn new() -> EnumTrack {
+        EnumTrack { keyframes: vec![], names: HashMap::new() }
+    }
+
+    pub fn set_name(&mut self, code: i32, name: &str) -> &mut Self {
+        self.names.insert(code, name.to_string());
+        self
+    }
+
+    pub fn get_name(&self, code: i32) -> Option<&str> {
+        self.names.get(&code).map(String::as_str)
+    }
+
+    pub fn get_code(&self, name: &str) -> Option<i32> {
+        self.names.iter().find(|(_, n)| n.as_str() == name).map(|(code, _)| *code)
+    }
+
+    /// keyframes at the same time keep their insertion order
+    pub fn add_keyframe(&mut self, keyframe: EnumKeyframe) -> &mut Self {
+        let index = self.keyframes.partition_point(|k| k.time <= keyframe.time);
+        self.keyframes.insert(index, keyframe);
+        self
+    }
+
+    pub fn get_keyframes(&self) -> &[EnumKeyframe] {
+        &self.keyframes
+    }
+
+    pub fn get_duration(&self) -> Duration {
+        self.keyframes.last().map(|k| k.time).unwrap_or_default()
+    }
+
+    /// moves every keyframe to `snap(time)` (`FrameRate::snap`, `TempoMap::snap`)
+    pub fn snap_times(&mut self, snap: impl Fn(Duration) -> Duration) -> &mut Self {
+        for keyframe in &mut self.keyframes {
+            keyframe.time = snap(keyframe.time);
+        }
+        self
+    }
+
+    /// panics if the track has no keyframes (see `try_get_value`)
+    pub fn get_value(&self, time: Duration) -> i32 {
+        self.try_get_value(time).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_value(&self, time: Duration) -> Result<i32, TimelineError> {
+        let index = self.keyframes.partition_point(|k| k.time <= time);
+        self.keyframes[..index].last()
+            .or(self.keyframes.first())
+            .map(|k| k.code)
+            .ok_or(TimelineError::EmptyTrack)
+    }
+
+    /// name of the code at `time`, `None` if the code has no name
+    pub fn try_get_value_name(&self, time: Duration) -> Result<Option<&str>, TimelineError> {
+        Ok(self.get_name(self.try_get_value(time)?))
+    }
+
+    pub fn load_xml(xml_path: impl AsRef<Path>) -> Result<EnumTrack, TimelineError> {
+        EnumTrack::load_xml_reader(File::open(xml_path)?)
+    }
+
+    pub fn load_xml_reader(reader: impl Read) -> Result<EnumTrack, TimelineError> {
+        EnumTrack::load_xml_str(&read_to_string(reader)?)
+    }
+
+    /// `<names>` maps codes to names, key values can be a code or a name
+    pub fn load_xml_str(xml: &str) -> Result<EnumTrack, TimelineError> {
+        EnumTrack::from_json_value(xml_to_json::xml_str_to_json_with_texts(xml, &["/keyframes/names/name/label"])?)
+    }
+
+    pub fn load_json(json_path: impl AsRef<Path>) -> Result<EnumTrack, TimelineError> {
+        EnumTrack::load_json_reader(File::open(json_path)?)
+    }
+
+    pub fn load_json_reader(reader: impl Read) -> Result<EnumTrack, TimelineError> {
+        EnumTrack::from_json_value(serde_json::from_reader(reader)?)
+    }
+
+    /// same layout as the XML:
+    /// `{"keyframes": {"names": {"name": [{"code": 0, "label": "idle"}]}, "key": [{"time": "00:00:01:000", "value": "idle"}]}}`
+    pub fn load_json_str(json: &str) -> Result<EnumTrack, TimelineError> {
+        EnumTrack::from_json_value(serde_json::from_str(json)?)
+    }
+
+    pub fn save_xml(&self, xml_path: impl AsRef<Path>) -> Result<(), TimelineError> {
+        std::fs::write(xml_path, self.to_xml_string())?;
+        Ok(())
+    }
+
+    /// same layout as `load_xml_str`, names sorted by code. key values are written as codes
+    pub fn to_xml_string(&self) -> String {
+        let mut names: Vec<_> = self.names.iter().collect();
+        names.sort();
+
+        let mut xml = String::from("<keyframes>\n");
+        if !names.is_empty() {
+            xml += "    <names>\n";
+            for (code, name) in names {
+                xml += &format!("        <name><code>{}</code><label>{}</label></name>\n", code, escape_xml(name));
+            }
+            xml += "    </names>\n";
+        }
+        for keyframe in &self.keyframes {
+            xml += "    <key>\n";
+            xml += &format!("        <time>{}</time>\n", duration_to_timecode(keyframe.time));
+            xml += &format!("        <value>{}</value>\n", keyframe.code);
+            xml += "    </key>\n";
+        }
+        xml += "</keyframes>\n";
+        xml
+    }
+
+    fn from_json_value(json: serde_json::Value) -> Result<EnumTrack, TimelineError> {
+        let entity: EnumKeyframesEntity = serde_json::from_value(json)?;
+        let mut track = EnumTrack::new();
+        for name in entity.keyframes.names.map(|n| n.name).unwrap_or_default() {
+            track.set_name(name.code, &xml_text(name.label));
+        }
+        for key in entity.keyframes.key {
+            let code = match key.value {
+                serde_json::Value::Number(n) => n.as_i64().and_then(|n| i32::try_from(n).ok()),
+                v => track.get_code(&xml_text(v)),
+            };
+            let code = code.ok_or_else(|| TimelineError::InvalidData(format!("unknown enum value at {}", key.time)))?;
+            track.add_keyframe(EnumKeyframe::new(timecode_to_duration(&key.time)?, code));
+        }
+        Ok(track)
+    }
+}
+
+#[derive(Deserialize)]
+struct EnumKeyframesEntity {
+    keyframes: EnumKeyframesBodyEntity,
+}
+
+#[derive(Deserialize)]
+struct EnumKeyframesBodyEntity {
+    #[serde(default)]
+    names: Option<EnumNamesEntity>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    key: Vec<EnumKeyframeEntity>,
+}
+
+#[derive(Deserialize)]
+struct EnumNamesEntity {
+    #[serde(default, deserialize_with = "one_or_many")]
+    name: Vec<EnumNameEntity>,
+}
+
+#[derive(Deserialize)]
+struct EnumNameEntity {
+    code: i32,
+    label: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct EnumKeyframeEntity {
+    time: String,
+    value: serde_json::Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Timeline, TrackGetter, TrackValue};
+    use crate::test_util::{fixture, ms};
+
+    use super::*;
+
+    #[test]
+    fn step_test() {
+        let mut t = EnumTrack::new();
+        t.set_name(0, "idle").set_name(1, "run");
+        t.add_keyframe(EnumKeyframe::new(ms(500), 0))
+            .add_keyframe(EnumKeyframe::new(ms(1000), 1))
+            .add_keyframe(EnumKeyframe::new(ms(2000), 7));
+        assert_eq!(t.get_value(ms(0)), 0);
+        assert_eq!(t.get_value(ms(1500)), 1);
+        assert_eq!(t.try_get_value_name(ms(1500)), Ok(Some("run")));
+        assert_eq!(t.try_get_value_name(ms(2000)), Ok(None));
+        assert_eq!(EnumTrack::new().try_get_value(ms(0)), Err(TimelineError::EmptyTrack));
+    }
+
+    #[test]
+    fn xml_load_test() {
+        let xml = r#"
+<keyframes>
+    <names>
+        <name><code>0</code><label>intro</label></name>
+        <name><code>1</code><label>main</label></name>
+    </names>
+    <key>
+        <time>00:00:00:000</time>
+        <value>intro</value>
+    </key>
+    <key>
+        <time>00:00:10:000</time>
+        <value>1</value>
+    </key>
+</keyframes>"#;
+
+        let t = EnumTrack::load_xml_str(xml).unwrap();
+        assert_eq!(t.get_keyframes(), &[EnumKeyframe::new(ms(0), 0), EnumKeyframe::new(ms(10000), 1)]);
+        assert_eq!(t.try_get_value_name(ms(12000)), Ok(Some("main")));
+
+        let unknown = r#"<keyframes><key><time>00:00:00:000</time><value>outro</value></key></keyframes>"#;
+        assert!(EnumTrack::load_xml_str(unknown).is_err());
+    }
+
+    #[test]
+    fn xml_round_trip_test() {
+        let mut t = EnumTrack::new();
+        t.set_name(1, "on & off").set_name(0, "idle").set_name(2, "007").set_name(3, "1.50");
+        t.add_keyframe(EnumKeyframe::new(ms(0), 1))
+            .add_keyframe(EnumKeyframe::new(ms(250), -3));
+        assert_eq!(EnumTrack::load_xml_str(&t.to_xml_string()).unwrap(), t);
+    }
+
+    #[test]
+    fn json_load_test() {
+        let json = r#"{"keyframes": {
+            "names": {"name": {"code": 3, "label": "credits"}},
+            "key": {"time": "00:00:01:000", "value": "credits"}
+        }}"#;
+
+        let mut tl = Timeline::new();
+        tl.add("scene", EnumTrack::load_json_str(json).unwrap());
+        assert_eq!(tl.get_value("scene", ms(1000)), TrackValue::Int(3));
+        assert_eq!(tl.try_get("scene").unwrap().as_enum_track().get_name(3), Some("credits"));
+    }
+
+    #[test]
+    fn xml_load_file_test() {
+        let path = fixture("enum.xml");
+        let t = EnumTrack::load_xml(&path).unwrap();
+        assert_eq!(t.try_get_value_name(ms(10000)), Ok(Some("main")));
+        assert_eq!(EnumTrack::load_xml_reader(File::open(path).unwrap()).unwrap(), t);
+    }
+}
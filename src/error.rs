@@ -0,0 +1,101 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Errors returned by the fallible (`try_*`) API of the timeline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimelineError {
+    /// no track registered with the given name
+    TrackNotFound(String),
+    /// the track (or value) is not of the requested type
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// the track has no keyframes, so no value can be sampled
+    EmptyTrack,
+    /// the loaded data has no `key` entries
+    MissingKeyframes,
+    /// the timecode string could not be parsed
+    InvalidTimecode(String),
+    /// a field of the timecode is out of range (`00:61:00:000`, frame 25 at 25 fps)
+    TimecodeOutOfRange {
+        timecode: String,
+        field: &'static str,
+    },
+    /// a frame-based method was called on a timeline without frame rate (`Timeline::set_frame_rate`)
+    MissingFrameRate,
+    /// no keyframe at the given index
+    KeyframeIndexOutOfRange(usize),
+    /// a keyframe is already at the time, with `DuplicateTimePolicy::Error`
+    DuplicateKeyframeTime(Duration),
+    /// a beat-based method was called on a timeline without tempo map (`Timeline::set_tempo_map`)
+    MissingTempoMap,
+    /// the color string is neither `#rrggbb[aa]` nor `r, g, b[, a]`
+    InvalidColor(String),
+    /// the document was written by a newer version of the native format
+    UnsupportedDocumentVersion(u32),
+    /// a file could not be read or written
+    Io(String),
+    /// the XML, JSON or MessagePack data could not be parsed or written
+    Format(String),
+    /// the data was parsed, but a value is invalid (`"maybe"` for a bool, a vector with missing components)
+    InvalidData(String),
+}
+
+impl fmt::Display for TimelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimelineError::TrackNotFound(name) => write!(f, "track not found: {}", name),
+            TimelineError::TypeMismatch { expected, found } => {
+                write!(f, "invalid conversion: expected {}, found {}", expected, found)
+            }
+            TimelineError::EmptyTrack => write!(f, "no keyframes"),
+            TimelineError::MissingKeyframes => write!(f, "no keyframes found in data"),
+            TimelineError::InvalidTimecode(timecode) => write!(f, "invalid timecode: {}", timecode),
+            TimelineError::TimecodeOutOfRange { timecode, field } => {
+                write!(f, "invalid timecode: {} ({} out of range)", timecode, field)
+            }
+            TimelineError::MissingFrameRate => write!(f, "no frame rate set"),
+            TimelineError::KeyframeIndexOutOfRange(index) => write!(f, "keyframe index out of range: {}", index),
+            TimelineError::DuplicateKeyframeTime(time) => write!(f, "keyframe already at {:?}", time),
+            TimelineError::MissingTempoMap => write!(f, "no tempo map set"),
+            TimelineError::InvalidColor(color) => write!(f, "invalid color: {}", color),
+            TimelineError::UnsupportedDocumentVersion(version) => write!(f, "unsupported document version: {}", version),
+            TimelineError::Io(message) | TimelineError::Format(message) | TimelineError::InvalidData(message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TimelineError {}
+
+impl From<std::io::Error> for TimelineError {
+    fn from(error: std::io::Error) -> Self {
+        TimelineError::Io(error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for TimelineError {
+    fn from(error: serde_json::Error) -> Self {
+        TimelineError::Format(error.to_string())
+    }
+}
+
+impl From<minidom::error::Error> for TimelineError {
+    fn from(error: minidom::error::Error) -> Self {
+        TimelineError::Format(error.to_string())
+    }
+}
+
+impl From<rmp_serde::encode::Error> for TimelineError {
+    fn from(error: rmp_serde::encode::Error) -> Self {
+        TimelineError::Format(error.to_string())
+    }
+}
+
+impl From<rmp_serde::decode::Error> for TimelineError {
+    fn from(error: rmp_serde::decode::Error) -> Self {
+        TimelineError::Format(error.to_string())
+    }
+}
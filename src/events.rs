@@ -0,0 +1,242 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::loader::{read_to_string, duration_to_timecode, escape_xml, one_or_many, timecode_to_duration, xml_text};
+use crate::{xml_to_json, TimelineError};
+
+/// An instantaneous event (ofxTLBangs bang, or ofxTLFlags flag with a label)
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TimelineEvent {
+    pub time: Duration,
+    /// empty for bangs
+    pub label: String,
+}
+
+impl TimelineEvent {
+    pub fn new(time: Duration) -> TimelineEvent {
+        TimelineEvent { time, label: String::new() }
+    }
+
+    pub fn labeled(time: Duration, label: &str) -> TimelineEvent {
+        TimelineEvent { time, label: label.to_string() }
+    }
+}
+
+/// Track of timestamped events, sorted by time
+///
+/// Events have no value in between, use `events_between` (or `TimelinePlayer::get_events`)
+/// to find the events crossed by the playhead.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EventTrack {
+    events: Vec<TimelineEvent>,
+}
+
+impl EventTrack {
+    pub fn new() -> EventTrack {
+        EventTrack { events: vec![] }
+    }
+
+    /// events at the same time keep their insertion order
+    pub fn add_event(&mut self, event: TimelineEvent) -> &mut Self {
+        let index = self.events.partition_point(|e| e.time <= event.time);
+        self.events.insert(index, event);
+        self
+    }
+
+    pub fn remove_event(&mut self, index: usize) -> Option<TimelineEvent> {
+        (index < self.events.len()).then(|| self.events.remove(index))
+    }
+
+    pub fn get_events(&self) -> &[TimelineEvent] {
+        &self.events
+    }
+
+    /// time of the last event
+    pub fn get_duration(&self) -> Duration {
+        self.events.last().map(|e| e.time).unwrap_or_default()
+    }
+
+    /// moves every event to `snap(time)` (`FrameRate::snap`, `TempoMap::snap`)
+    pub fn snap_times(&mut self, snap: impl Fn(Duration) -> Duration) -> &mut Self {
+        for event in &mut self.events {
+            event.time = snap(event.time);
+        }
+        self
+    }
+
+    /// events at exactly `time`
+    pub fn events_at(&self, time: Duration) -> &[TimelineEvent] {
+        let first = self.events.partition_point(|e| e.time < time);
+        let last = self.events.partition_point(|e| e.time <= time);
+        &self.events[first..last]
+    }
+
+    /// events crossed when moving the playhead from `t0` to `t1`, in crossing order.
+    ///
+    /// forward this is `t0 <= time < t1`, in reverse (`t1 < t0`) `t1 < time <= t0`,
+    /// so consecutive calls with the previous `t1` as `t0` return every event exactly once
+    pub fn events_between(&self, t0: Duration, t1: Duration) -> Vec<&TimelineEvent> {
+        if t0 <= t1 {
+            let first = self.events.partition_point(|e| e.time < t0);
+            let last = self.events.partition_point(|e| e.time < t1);
+            self.events[first..last].iter().collect()
+        } else {
+            let first = self.events.partition_point(|e| e.time <= t1);
+            let last = self.events.partition_point(|e| e.time <= t0);
+            self.events[first..last].iter().rev().collect()
+        }
+    }
+
+    pub fn load_xml(xml_path: impl AsRef<Path>) -> Result<EventTrack, TimelineError> {
+        EventTrack::load_xml_reader(File::open(xml_path)?)
+    }
+
+    pub fn load_xml_reader(reader: impl Read) -> Result<EventTrack, TimelineError> {
+        EventTrack::load_xml_str(&read_to_string(reader)?)
+    }
+
+    /// ofxTLBangs / ofxTLFlags format
+    pub fn load_xml_str(xml: &str) -> Result<EventTrack, TimelineError> {
+        let json = xml_to_json::xml_str_to_json_with_texts(xml, &["/keyframes/key/flag"])?;
+        let entity: EventsEntity = serde_json::from_value(json)?;
+
+        let mut track = EventTrack::new();
+        for key in entity.keyframes.map(|k| k.key).unwrap_or_default() {
+            track.add_event(TimelineEvent { time: timecode_to_duration(&key.time)?, label: xml_text(key.flag) });
+        }
+        Ok(track)
+    }
+
+    pub fn save_xml(&self, xml_path: impl AsRef<Path>) -> Result<(), TimelineError> {
+        std::fs::write(xml_path, self.to_xml_string())?;
+        Ok(())
+    }
+
+    /// ofxTLFlags format (also readable as ofxTLBangs)
+    pub fn to_xml_string(&self) -> String {
+        let mut xml = String::from("<keyframes>\n");
+        for event in &self.events {
+            xml += "    <key>\n";
+            xml += &format!("        <time>{}</time>\n", duration_to_timecode(event.time));
+            xml += "        <value>0.000000000</value>\n";
+            xml += &format!("        <flag>{}</flag>\n", escape_xml(&event.label));
+            xml += "    </key>\n";
+        }
+        xml += "</keyframes>\n";
+        xml
+    }
+}
+
+#[derive(Deserialize)]
+struct EventsEntity {
+    #[serde(default)]
+    keyframes: Option<EventsBodyEntity>,
+}
+
+#[derive(Deserialize)]
+struct EventsBodyEntity {
+    #[serde(default, deserialize_with = "one_or_many")]
+    key: Vec<EventEntity>,
+}
+
+#[derive(Deserialize)]
+struct EventEntity {
+    time: String,
+    #[serde(default)]
+    flag: serde_json::Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{fixture, ms};
+
+    use super::*;
+
+    fn labels(events: Vec<&TimelineEvent>) -> Vec<&str> {
+        events.iter().map(|e| e.label.as_str()).collect()
+    }
+
+    fn create_track() -> EventTrack {
+        let mut t = EventTrack::new();
+        t.add_event(TimelineEvent::labeled(ms(500), "b"))
+            .add_event(TimelineEvent::labeled(ms(0), "a"))
+            .add_event(TimelineEvent::labeled(ms(500), "c"))
+            .add_event(TimelineEvent::labeled(ms(1000), "d"));
+        t
+    }
+
+    #[test]
+    fn events_between_test() {
+        let t = create_track();
+        assert_eq!(labels(t.events_between(ms(0), ms(500))), vec!["a"]);
+        assert_eq!(labels(t.events_between(ms(500), ms(1000))), vec!["b", "c"]);
+        assert_eq!(labels(t.events_between(ms(600), ms(900))), Vec::<&str>::new());
+        assert_eq!(labels(t.events_between(ms(500), ms(500))), Vec::<&str>::new());
+
+        // reverse playback
+        assert_eq!(labels(t.events_between(ms(1000), ms(0))), vec!["d", "c", "b"]);
+        assert_eq!(labels(t.events_between(ms(400), ms(0))), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn events_once_per_frame_test() {
+        let t = create_track();
+        let mut crossed = vec![];
+        for frame in 0..100 {
+            crossed.extend(labels(t.events_between(ms(frame * 16), ms((frame + 1) * 16))));
+        }
+        assert_eq!(crossed, vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn events_at_test() {
+        let mut t = create_track();
+        assert_eq!(t.events_at(ms(500)).len(), 2);
+        assert!(t.events_at(ms(501)).is_empty());
+        assert_eq!(t.remove_event(1).unwrap().label, "b");
+        assert!(t.remove_event(10).is_none());
+        assert_eq!(t.get_duration(), ms(1000));
+    }
+
+    #[test]
+    fn xml_load_test() {
+        let bangs = r#"
+<keyframes>
+    <key>
+        <time>00:00:01:000</time>
+        <value>0.500000000</value>
+    </key>
+    <key>
+        <time>00:00:00:250</time>
+        <value>0.500000000</value>
+    </key>
+</keyframes>"#;
+        let t = EventTrack::load_xml_str(bangs).unwrap();
+        assert_eq!(t.get_events(), &[TimelineEvent::new(ms(250)), TimelineEvent::new(ms(1000))]);
+
+        let flags = r#"<keyframes><key><time>00:00:02:000</time><value>0</value><flag>scene 2</flag></key></keyframes>"#;
+        let t = EventTrack::load_xml_str(flags).unwrap();
+        assert_eq!(t.get_events(), &[TimelineEvent::labeled(ms(2000), "scene 2")]);
+    }
+
+    #[test]
+    fn xml_round_trip_test() {
+        let mut t = create_track();
+        t.add_event(TimelineEvent::new(ms(2000)))
+            .add_event(TimelineEvent::labeled(ms(2500), "007"))
+            .add_event(TimelineEvent::labeled(ms(3000), " 1.50"));
+        assert_eq!(EventTrack::load_xml_str(&t.to_xml_string()).unwrap(), t);
+    }
+
+    #[test]
+    fn xml_load_file_test() {
+        let path = fixture("flags.xml");
+        let t = EventTrack::load_xml(&path).unwrap();
+        assert_eq!(t.get_events(), &[TimelineEvent::labeled(ms(500), "start"), TimelineEvent::labeled(ms(3000), "end")]);
+        assert_eq!(EventTrack::load_xml_reader(File::open(path).unwrap()).unwrap(), t);
+    }
+}
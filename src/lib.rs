@@ -1,17 +1,43 @@
+pub mod color;
+pub mod cursor;
+pub mod custom;
+pub mod document;
 pub mod easing;
+pub mod enums;
+pub mod error;
+pub mod events;
 pub mod loader;
+pub mod player;
+pub mod project;
+pub mod quat;
+pub mod range;
+pub mod switches;
+pub mod tempo;
+pub mod text;
+pub mod timecode;
 mod xml_to_json;
+#[cfg(test)]
+mod test_util;
 
 #[cfg(feature="bevy")]
 use bevy::math::{Vec2, Vec3};
 #[cfg(feature="bevy")]
-use bevy::render::color::Color;
-#[cfg(feature="bevy")]
 use bevy::render::render_graph::DynEq;
 use easing::{EasingFunction, EasingType};
+pub use color::{Color, ColorSpace, ColorTrack};
+pub use custom::CustomTrack;
+pub use enums::{EnumKeyframe, EnumTrack};
+pub use error::TimelineError;
+pub use events::{EventTrack, TimelineEvent};
+pub use project::{ProjectSettings, TimelinePage};
+pub use quat::Quat;
+pub use range::ValueRange;
+pub use switches::{SwitchRange, SwitchTrack};
+pub use tempo::{MusicalTime, TempoChange, TempoMap, TimeSignature};
+pub use text::{TextInterpolation, TextKeyframe, TextTrack};
+pub use timecode::{FrameRate, Timecode, TimecodeFormat};
 use serde::de::DeserializeOwned;
 
-use std::any::Any;
 use std::collections::HashMap;
 use std::time::Duration;
 
@@ -19,7 +45,8 @@ type MyVec2 = (f32, f32);
 type MyVec3 = (f32, f32, f32);
 type MyVec4 = (f32, f32, f32, f32);
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TrackVariant {
     BoolTrack(Track<bool>),
     IntTrack(Track<i32>),
@@ -29,6 +56,15 @@ pub enum TrackVariant {
     Vec2Track(Track<(f32, f32)>),
     Vec3Track(Track<(f32, f32, f32)>),
     Vec4Track(Track<(f32, f32, f32, f32)>),
+    QuatTrack(Track<Quat>),
+    ColorTrack(ColorTrack),
+    SwitchTrack(SwitchTrack),
+    EventTrack(EventTrack),
+    TextTrack(TextTrack),
+    EnumTrack(EnumTrack),
+    /// a track of a user type, see `CustomTrack`. not serializable
+    #[serde(skip)]
+    Custom(CustomTrack),
 }
 
 macro_rules! impl_from_track_variant {
@@ -49,7 +85,121 @@ impl_from_track_variant!(i64, LongTrack);
 impl_from_track_variant!(MyVec2, Vec2Track);
 impl_from_track_variant!(MyVec3, Vec3Track);
 impl_from_track_variant!(MyVec4, Vec4Track);
+impl_from_track_variant!(Quat, QuatTrack);
+
+impl From<ColorTrack> for TrackVariant {
+    fn from(track: ColorTrack) -> Self {
+        TrackVariant::ColorTrack(track)
+    }
+}
+
+/// wrapped in a `ColorTrack` with the default `ColorSpace`
+impl From<Track<Color>> for TrackVariant {
+    fn from(track: Track<Color>) -> Self {
+        TrackVariant::ColorTrack(track.into())
+    }
+}
+
+impl From<SwitchTrack> for TrackVariant {
+    fn from(track: SwitchTrack) -> Self {
+        TrackVariant::SwitchTrack(track)
+    }
+}
+
+impl From<EventTrack> for TrackVariant {
+    fn from(track: EventTrack) -> Self {
+        TrackVariant::EventTrack(track)
+    }
+}
+
+impl From<TextTrack> for TrackVariant {
+    fn from(track: TextTrack) -> Self {
+        TrackVariant::TextTrack(track)
+    }
+}
+
+impl From<EnumTrack> for TrackVariant {
+    fn from(track: EnumTrack) -> Self {
+        TrackVariant::EnumTrack(track)
+    }
+}
+
+impl From<CustomTrack> for TrackVariant {
+    fn from(track: CustomTrack) -> Self {
+        TrackVariant::Custom(track)
+    }
+}
+
+impl TrackVariant {
+    /// name of the value type held by this track (used in error messages)
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            TrackVariant::BoolTrack(_) => "bool",
+            TrackVariant::IntTrack(_) => "int",
+            TrackVariant::FloatTrack(_) => "float",
+            TrackVariant::DoubleTrack(_) => "double",
+            TrackVariant::LongTrack(_) => "long",
+            TrackVariant::Vec2Track(_) => "vec2",
+            TrackVariant::Vec3Track(_) => "vec3",
+            TrackVariant::Vec4Track(_) => "vec4",
+            TrackVariant::QuatTrack(_) => "quat",
+            TrackVariant::ColorTrack(_) => "color",
+            TrackVariant::SwitchTrack(_) => "switch",
+            TrackVariant::EventTrack(_) => "event",
+            TrackVariant::TextTrack(_) => "text",
+            TrackVariant::EnumTrack(_) => "enum",
+            TrackVariant::Custom(track) => track.type_name(),
+        }
+    }
+
+    /// panics if keyframes meet with `DuplicateTimePolicy::Error` (see `try_snap_times`)
+    pub fn snap_times(&mut self, snap: impl Fn(Duration) -> Duration) -> &mut Self {
+        self.try_snap_times(snap).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// moves keyframes (events, switch ranges) to `snap(time)`. custom tracks are left unchanged.
+    /// curve tracks go through their `DuplicateTimePolicy`, see `Track::try_snap_times`
+    pub fn try_snap_times(&mut self, snap: impl Fn(Duration) -> Duration) -> Result<&mut Self, TimelineError> {
+        match self {
+            TrackVariant::BoolTrack(track) => { track.try_snap_times(&snap)?; }
+            TrackVariant::IntTrack(track) => { track.try_snap_times(&snap)?; }
+            TrackVariant::FloatTrack(track) => { track.try_snap_times(&snap)?; }
+            TrackVariant::DoubleTrack(track) => { track.try_snap_times(&snap)?; }
+            TrackVariant::LongTrack(track) => { track.try_snap_times(&snap)?; }
+            TrackVariant::Vec2Track(track) => { track.try_snap_times(&snap)?; }
+            TrackVariant::Vec3Track(track) => { track.try_snap_times(&snap)?; }
+            TrackVariant::Vec4Track(track) => { track.try_snap_times(&snap)?; }
+            TrackVariant::QuatTrack(track) => { track.try_snap_times(&snap)?; }
+            TrackVariant::ColorTrack(track) => { track.track.try_snap_times(&snap)?; }
+            TrackVariant::SwitchTrack(track) => { track.snap_times(&snap); }
+            TrackVariant::EventTrack(track) => { track.snap_times(&snap); }
+            TrackVariant::TextTrack(track) => { track.snap_times(&snap); }
+            TrackVariant::EnumTrack(track) => { track.snap_times(&snap); }
+            TrackVariant::Custom(_) => {}
+        }
+        Ok(self)
+    }
+
+    /// moves keyframes to the nearest frame boundary, see `snap_times`
+    pub fn snap_to_frames(&mut self, rate: FrameRate) -> &mut Self {
+        self.snap_times(|time| rate.snap(time))
+    }
+
+    pub fn try_snap_to_frames(&mut self, rate: FrameRate) -> Result<&mut Self, TimelineError> {
+        self.try_snap_times(|time| rate.snap(time))
+    }
+
+    /// moves keyframes to the nearest `1 / subdivision` beat, see `snap_times`
+    pub fn snap_to_beats(&mut self, tempo_map: &TempoMap, subdivision: u32) -> &mut Self {
+        self.snap_times(|time| tempo_map.snap(time, subdivision))
+    }
+
+    pub fn try_snap_to_beats(&mut self, tempo_map: &TempoMap, subdivision: u32) -> Result<&mut Self, TimelineError> {
+        self.try_snap_times(|time| tempo_map.snap(time, subdivision))
+    }
+}
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TrackValue {
     Bool(bool),
     Int(i32),
@@ -59,54 +209,101 @@ pub enum TrackValue {
     Vec2(MyVec2),
     Vec3(MyVec3),
     Vec4(MyVec4),
+    Quat(Quat),
+    Color(Color),
+}
+
+impl TrackValue {
+    /// name of the value type (used in error messages)
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            TrackValue::Bool(_) => "bool",
+            TrackValue::Int(_) => "int",
+            TrackValue::Float(_) => "float",
+            TrackValue::Double(_) => "double",
+            TrackValue::Long(_) => "long",
+            TrackValue::Vec2(_) => "vec2",
+            TrackValue::Vec3(_) => "vec3",
+            TrackValue::Vec4(_) => "vec4",
+            TrackValue::Quat(_) => "quat",
+            TrackValue::Color(_) => "color",
+        }
+    }
 }
 
 macro_rules! impl_from_track_value {
-    ($tp:ident, $name:ident) => {
+    ($tp:ident, $name:ident, $try_as:ident, $type_name:expr) => {
         impl From<$tp> for TrackValue {
             fn from(value: $tp) -> Self {
                 TrackValue::$name(value)
             }
         }
 
+        /// panics if the value is not of this type (see `TrackValue::try_as_*`)
         impl From<TrackValue> for $tp {
             fn from(value: TrackValue) -> Self {
-                match value {
-                    TrackValue::$name(v) => v,
-                    _ => panic!("Invalid conversion"),
+                value.$try_as().unwrap_or_else(|e| panic!("{}", e))
+            }
+        }
+
+        impl TrackValue {
+            pub fn $try_as(&self) -> Result<$tp, TimelineError> {
+                match self {
+                    TrackValue::$name(v) => Ok(*v),
+                    _ => Err(TimelineError::TypeMismatch {
+                        expected: $type_name,
+                        found: self.type_name(),
+                    }),
                 }
             }
         }
     };
 }
 
-impl_from_track_value!(bool, Bool);
-impl_from_track_value!(i32, Int);
-impl_from_track_value!(f32, Float);
-impl_from_track_value!(f64, Double);
-impl_from_track_value!(i64, Long);
-impl_from_track_value!(MyVec2, Vec2);
-impl_from_track_value!(MyVec3, Vec3);
-impl_from_track_value!(MyVec4, Vec4);
+impl_from_track_value!(bool, Bool, try_as_bool, "bool");
+impl_from_track_value!(i32, Int, try_as_int, "int");
+impl_from_track_value!(f32, Float, try_as_float, "float");
+impl_from_track_value!(f64, Double, try_as_double, "double");
+impl_from_track_value!(i64, Long, try_as_long, "long");
+impl_from_track_value!(MyVec2, Vec2, try_as_vec2, "vec2");
+impl_from_track_value!(MyVec3, Vec3, try_as_vec3, "vec3");
+impl_from_track_value!(MyVec4, Vec4, try_as_vec4, "vec4");
+impl_from_track_value!(Quat, Quat, try_as_quat, "quat");
+impl_from_track_value!(Color, Color, try_as_color, "color");
 
 pub trait TrackValueGetter {
     fn get_value(&self, time: Duration) -> TrackValue;
+    fn try_get_value(&self, time: Duration) -> Result<TrackValue, TimelineError>;
     fn get_duration(&self) -> Duration;
 }
 
 impl TrackValueGetter for TrackVariant {
     fn get_value(&self, time: Duration) -> TrackValue {
+        self.try_get_value(time).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn try_get_value(&self, time: Duration) -> Result<TrackValue, TimelineError> {
         match self {
-            TrackVariant::BoolTrack(track) => track.get_value(time).into(),
-            TrackVariant::IntTrack(track) => track.get_value(time).into(),
-            TrackVariant::FloatTrack(track) => track.get_value(time).into(),
-            TrackVariant::DoubleTrack(track) => track.get_value(time).into(),
-            TrackVariant::LongTrack(track) => track.get_value(time).into(),
-            TrackVariant::Vec2Track(track) => track.get_value(time).into(),
-            TrackVariant::Vec3Track(track) => track.get_value(time).into(),
-            TrackVariant::Vec4Track(track) => track.get_value(time).into(),
+            TrackVariant::BoolTrack(track) => track.try_get_value(time).map(Into::into),
+            TrackVariant::IntTrack(track) => track.try_get_value(time).map(Into::into),
+            TrackVariant::FloatTrack(track) => track.try_get_value(time).map(Into::into),
+            TrackVariant::DoubleTrack(track) => track.try_get_value(time).map(Into::into),
+            TrackVariant::LongTrack(track) => track.try_get_value(time).map(Into::into),
+            TrackVariant::Vec2Track(track) => track.try_get_value(time).map(Into::into),
+            TrackVariant::Vec3Track(track) => track.try_get_value(time).map(Into::into),
+            TrackVariant::Vec4Track(track) => track.try_get_value(time).map(Into::into),
+            TrackVariant::QuatTrack(track) => track.try_get_value(time).map(Into::into),
+            TrackVariant::ColorTrack(track) => track.try_get_value(time).map(Into::into),
+            TrackVariant::SwitchTrack(track) => Ok(track.is_on(time).into()),
+            TrackVariant::EnumTrack(track) => track.try_get_value(time).map(Into::into),
+            // events have no value, see `EventTrack::events_between`.
+            // strings are not `Copy`, see `Timeline::get_text`.
+            // custom values are not a `TrackValue`, see `Timeline::get_custom_value`
+            TrackVariant::EventTrack(_) | TrackVariant::TextTrack(_) | TrackVariant::Custom(_) => Err(TimelineError::TypeMismatch {
+                expected: "value",
+                found: self.type_name(),
+            }),
         }
-         
     }
 
     fn get_duration(&self) -> Duration {
@@ -119,10 +316,20 @@ impl TrackValueGetter for TrackVariant {
             TrackVariant::Vec2Track(track) => track.get_duration(),
             TrackVariant::Vec3Track(track) => track.get_duration(),
             TrackVariant::Vec4Track(track) => track.get_duration(),
+            TrackVariant::QuatTrack(track) => track.get_duration(),
+            TrackVariant::ColorTrack(track) => track.get_duration(),
+            TrackVariant::SwitchTrack(track) => track.get_duration(),
+            TrackVariant::EventTrack(track) => track.get_duration(),
+            TrackVariant::TextTrack(track) => track.get_duration(),
+            TrackVariant::EnumTrack(track) => track.get_duration(),
+            TrackVariant::Custom(track) => track.get_duration(),
         }
     }
 }
 
+/// Typed access to the track inside a `TrackVariant`.
+///
+/// `as_*_track` panics on a type mismatch, `try_as_*_track` returns `TimelineError::TypeMismatch` instead.
 pub trait TrackGetter {
     fn as_float_track(&self) -> &Track<f32>;
     fn as_int_track(&self) -> &Track<i32>;
@@ -132,6 +339,13 @@ pub trait TrackGetter {
     fn as_vec2_track(&self) -> &Track<MyVec2>;
     fn as_vec3_track(&self) -> &Track<MyVec3>;
     fn as_vec4_track(&self) -> &Track<MyVec4>;
+    fn as_quat_track(&self) -> &Track<Quat>;
+    fn as_color_track(&self) -> &ColorTrack;
+    fn as_switch_track(&self) -> &SwitchTrack;
+    fn as_event_track(&self) -> &EventTrack;
+    fn as_text_track(&self) -> &TextTrack;
+    fn as_enum_track(&self) -> &EnumTrack;
+    fn as_custom_track(&self) -> &CustomTrack;
 
     fn as_float_truck_mut(&mut self) -> &mut Track<f32>;
     fn as_int_track_mut(&mut self) -> &mut Track<i32>;
@@ -141,53 +355,135 @@ pub trait TrackGetter {
     fn as_vec2_track_mut(&mut self) -> &mut Track<MyVec2>;
     fn as_vec3_track_mut(&mut self) -> &mut Track<MyVec3>;
     fn as_vec4_track_mut(&mut self) -> &mut Track<MyVec4>;
+    fn as_quat_track_mut(&mut self) -> &mut Track<Quat>;
+    fn as_color_track_mut(&mut self) -> &mut ColorTrack;
+    fn as_switch_track_mut(&mut self) -> &mut SwitchTrack;
+    fn as_event_track_mut(&mut self) -> &mut EventTrack;
+    fn as_text_track_mut(&mut self) -> &mut TextTrack;
+    fn as_enum_track_mut(&mut self) -> &mut EnumTrack;
+    fn as_custom_track_mut(&mut self) -> &mut CustomTrack;
+
+    fn try_as_float_track(&self) -> Result<&Track<f32>, TimelineError>;
+    fn try_as_int_track(&self) -> Result<&Track<i32>, TimelineError>;
+    fn try_as_bool_track(&self) -> Result<&Track<bool>, TimelineError>;
+    fn try_as_double_track(&self) -> Result<&Track<f64>, TimelineError>;
+    fn try_as_long_track(&self) -> Result<&Track<i64>, TimelineError>;
+    fn try_as_vec2_track(&self) -> Result<&Track<MyVec2>, TimelineError>;
+    fn try_as_vec3_track(&self) -> Result<&Track<MyVec3>, TimelineError>;
+    fn try_as_vec4_track(&self) -> Result<&Track<MyVec4>, TimelineError>;
+    fn try_as_quat_track(&self) -> Result<&Track<Quat>, TimelineError>;
+    fn try_as_color_track(&self) -> Result<&ColorTrack, TimelineError>;
+    fn try_as_switch_track(&self) -> Result<&SwitchTrack, TimelineError>;
+    fn try_as_event_track(&self) -> Result<&EventTrack, TimelineError>;
+    fn try_as_text_track(&self) -> Result<&TextTrack, TimelineError>;
+    fn try_as_enum_track(&self) -> Result<&EnumTrack, TimelineError>;
+    fn try_as_custom_track(&self) -> Result<&CustomTrack, TimelineError>;
+
+    fn try_as_float_track_mut(&mut self) -> Result<&mut Track<f32>, TimelineError>;
+    fn try_as_int_track_mut(&mut self) -> Result<&mut Track<i32>, TimelineError>;
+    fn try_as_bool_track_mut(&mut self) -> Result<&mut Track<bool>, TimelineError>;
+    fn try_as_double_track_mut(&mut self) -> Result<&mut Track<f64>, TimelineError>;
+    fn try_as_long_track_mut(&mut self) -> Result<&mut Track<i64>, TimelineError>;
+    fn try_as_vec2_track_mut(&mut self) -> Result<&mut Track<MyVec2>, TimelineError>;
+    fn try_as_vec3_track_mut(&mut self) -> Result<&mut Track<MyVec3>, TimelineError>;
+    fn try_as_vec4_track_mut(&mut self) -> Result<&mut Track<MyVec4>, TimelineError>;
+    fn try_as_quat_track_mut(&mut self) -> Result<&mut Track<Quat>, TimelineError>;
+    fn try_as_color_track_mut(&mut self) -> Result<&mut ColorTrack, TimelineError>;
+    fn try_as_switch_track_mut(&mut self) -> Result<&mut SwitchTrack, TimelineError>;
+    fn try_as_event_track_mut(&mut self) -> Result<&mut EventTrack, TimelineError>;
+    fn try_as_text_track_mut(&mut self) -> Result<&mut TextTrack, TimelineError>;
+    fn try_as_enum_track_mut(&mut self) -> Result<&mut EnumTrack, TimelineError>;
+    fn try_as_custom_track_mut(&mut self) -> Result<&mut CustomTrack, TimelineError>;
 }
 
 macro_rules! track_getter_method {
-    ($id:ident, $tp:ident, $id2:ident) => {
-        fn $id(&self) -> &Track<$tp> {
+    ($id:ident, $try_id:ident, $track:ty, $id2:ident, $type_name:expr) => {
+        fn $id(&self) -> &$track {
+            self.$try_id().unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        fn $try_id(&self) -> Result<&$track, TimelineError> {
             match self {
-                TrackVariant::$id2(track) => track,
-                _ => panic!("Invalid conversion"),
+                TrackVariant::$id2(track) => Ok(track),
+                _ => Err(TimelineError::TypeMismatch {
+                    expected: $type_name,
+                    found: self.type_name(),
+                }),
             }
-        } 
+        }
     };
 }
 
 macro_rules! track_getter_mut_method {
-    ($id:ident, $tp:ident, $id2:ident) => {
-        fn $id(&mut self) -> &mut Track<$tp> {
+    ($id:ident, $try_id:ident, $track:ty, $id2:ident, $type_name:expr) => {
+        fn $id(&mut self) -> &mut $track {
+            self.$try_id().unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        fn $try_id(&mut self) -> Result<&mut $track, TimelineError> {
+            let found = self.type_name();
             match self {
-                TrackVariant::$id2(track) => track,
-                _ => panic!("Invalid conversion"),
+                TrackVariant::$id2(track) => Ok(track),
+                _ => Err(TimelineError::TypeMismatch {
+                    expected: $type_name,
+                    found,
+                }),
             }
-        } 
+        }
     };
 }
 
 impl TrackGetter for TrackVariant {
-    track_getter_method!(as_float_track, f32, FloatTrack);
-    track_getter_method!(as_int_track, i32, IntTrack);
-    track_getter_method!(as_bool_track, bool, BoolTrack);
-    track_getter_method!(as_double_track, f64, DoubleTrack);
-    track_getter_method!(as_long_track, i64, LongTrack);
-    track_getter_method!(as_vec2_track, MyVec2, Vec2Track);
-    track_getter_method!(as_vec3_track, MyVec3, Vec3Track);
-    track_getter_method!(as_vec4_track, MyVec4, Vec4Track);
-
-    track_getter_mut_method!(as_float_truck_mut, f32, FloatTrack);
-    track_getter_mut_method!(as_int_track_mut, i32, IntTrack);
-    track_getter_mut_method!(as_bool_track_mut, bool, BoolTrack);
-    track_getter_mut_method!(as_double_track_mut, f64, DoubleTrack);
-    track_getter_mut_method!(as_long_track_mut, i64, LongTrack);
-    track_getter_mut_method!(as_vec2_track_mut, MyVec2, Vec2Track);
-    track_getter_mut_method!(as_vec3_track_mut, MyVec3, Vec3Track);
-    track_getter_mut_method!(as_vec4_track_mut, MyVec4, Vec4Track);
-}
-
-#[derive(Debug, Default)]
+    track_getter_method!(as_float_track, try_as_float_track, Track<f32>, FloatTrack, "float");
+    track_getter_method!(as_int_track, try_as_int_track, Track<i32>, IntTrack, "int");
+    track_getter_method!(as_bool_track, try_as_bool_track, Track<bool>, BoolTrack, "bool");
+    track_getter_method!(as_double_track, try_as_double_track, Track<f64>, DoubleTrack, "double");
+    track_getter_method!(as_long_track, try_as_long_track, Track<i64>, LongTrack, "long");
+    track_getter_method!(as_vec2_track, try_as_vec2_track, Track<MyVec2>, Vec2Track, "vec2");
+    track_getter_method!(as_vec3_track, try_as_vec3_track, Track<MyVec3>, Vec3Track, "vec3");
+    track_getter_method!(as_vec4_track, try_as_vec4_track, Track<MyVec4>, Vec4Track, "vec4");
+    track_getter_method!(as_quat_track, try_as_quat_track, Track<Quat>, QuatTrack, "quat");
+    track_getter_method!(as_color_track, try_as_color_track, ColorTrack, ColorTrack, "color");
+    track_getter_method!(as_switch_track, try_as_switch_track, SwitchTrack, SwitchTrack, "switch");
+    track_getter_method!(as_event_track, try_as_event_track, EventTrack, EventTrack, "event");
+    track_getter_method!(as_text_track, try_as_text_track, TextTrack, TextTrack, "text");
+    track_getter_method!(as_enum_track, try_as_enum_track, EnumTrack, EnumTrack, "enum");
+    track_getter_method!(as_custom_track, try_as_custom_track, CustomTrack, Custom, "custom");
+
+    track_getter_mut_method!(as_float_truck_mut, try_as_float_track_mut, Track<f32>, FloatTrack, "float");
+    track_getter_mut_method!(as_int_track_mut, try_as_int_track_mut, Track<i32>, IntTrack, "int");
+    track_getter_mut_method!(as_bool_track_mut, try_as_bool_track_mut, Track<bool>, BoolTrack, "bool");
+    track_getter_mut_method!(as_double_track_mut, try_as_double_track_mut, Track<f64>, DoubleTrack, "double");
+    track_getter_mut_method!(as_long_track_mut, try_as_long_track_mut, Track<i64>, LongTrack, "long");
+    track_getter_mut_method!(as_vec2_track_mut, try_as_vec2_track_mut, Track<MyVec2>, Vec2Track, "vec2");
+    track_getter_mut_method!(as_vec3_track_mut, try_as_vec3_track_mut, Track<MyVec3>, Vec3Track, "vec3");
+    track_getter_mut_method!(as_vec4_track_mut, try_as_vec4_track_mut, Track<MyVec4>, Vec4Track, "vec4");
+    track_getter_mut_method!(as_quat_track_mut, try_as_quat_track_mut, Track<Quat>, QuatTrack, "quat");
+    track_getter_mut_method!(as_color_track_mut, try_as_color_track_mut, ColorTrack, ColorTrack, "color");
+    track_getter_mut_method!(as_switch_track_mut, try_as_switch_track_mut, SwitchTrack, SwitchTrack, "switch");
+    track_getter_mut_method!(as_event_track_mut, try_as_event_track_mut, EventTrack, EventTrack, "event");
+    track_getter_mut_method!(as_text_track_mut, try_as_text_track_mut, TextTrack, TextTrack, "text");
+    track_getter_mut_method!(as_enum_track_mut, try_as_enum_track_mut, EnumTrack, EnumTrack, "enum");
+    track_getter_mut_method!(as_custom_track_mut, try_as_custom_track_mut, CustomTrack, Custom, "custom");
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Timeline {
     pub tracks: HashMap<String, TrackVariant>,
+    /// track layout of an ofxTimeline project, see `load_project`
+    #[serde(default)]
+    pub pages: Vec<TimelinePage>,
+    #[serde(default)]
+    pub settings: ProjectSettings,
+    /// frame rate of the frame-based methods (`get_value_at_frame`, `snap_to_frames`)
+    #[serde(default)]
+    pub frame_rate: Option<FrameRate>,
+    /// sample at the start of the frame containing the time (needs `frame_rate`)
+    #[serde(default)]
+    pub quantize_to_frames: bool,
+    /// bars and beats of the timeline (`get_value_at_beat`, `snap_to_beats`)
+    #[serde(default)]
+    pub tempo_map: Option<TempoMap>,
 }
 
 impl Timeline
@@ -195,6 +491,11 @@ impl Timeline
     pub fn new() -> Timeline {
         Timeline {
             tracks: HashMap::new(),
+            pages: vec![],
+            settings: ProjectSettings::default(),
+            frame_rate: None,
+            quantize_to_frames: false,
+            tempo_map: None,
         }
     }
 
@@ -222,8 +523,152 @@ impl Timeline
         self.tracks.get_mut(name).map(|track| track.into())
     }
 
+    /// same as `get`, but returns `TimelineError::TrackNotFound` instead of `None`
+    pub fn try_get(&self, name: &str) -> Result<&TrackVariant, TimelineError> {
+        self.tracks.get(name).ok_or_else(|| TimelineError::TrackNotFound(name.to_string()))
+    }
+
+    /// same as `get_mut`, but returns `TimelineError::TrackNotFound` instead of `None`
+    pub fn try_get_mut(&mut self, name: &str) -> Result<&mut TrackVariant, TimelineError> {
+        self.tracks.get_mut(name).ok_or_else(|| TimelineError::TrackNotFound(name.to_string()))
+    }
+
+    /// panics if the track is missing or empty (see `try_get_value`)
     pub fn get_value(&self, name: &str, time: Duration) -> TrackValue {
-        self.get(name).unwrap().get_value(time)
+        self.try_get_value(name, time).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_value(&self, name: &str, time: Duration) -> Result<TrackValue, TimelineError> {
+        self.try_get(name)?.try_get_value(self.get_sample_time(time))
+    }
+
+    /// sample a text track. panics if the track is missing, not a text track or empty (see `try_get_text`)
+    pub fn get_text(&self, name: &str, time: Duration) -> &str {
+        self.try_get_text(name, time).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_text(&self, name: &str, time: Duration) -> Result<&str, TimelineError> {
+        self.try_get(name)?.try_as_text_track()?.try_get_value(self.get_sample_time(time))
+    }
+
+    /// sample a float or double track mapped to its range, see `ValueRange`
+    pub fn try_get_mapped_value(&self, name: &str, time: Duration) -> Result<f64, TimelineError> {
+        let time = self.get_sample_time(time);
+        match self.try_get(name)? {
+            TrackVariant::FloatTrack(track) => Ok(track.try_get_mapped_value(time)? as f64),
+            TrackVariant::DoubleTrack(track) => track.try_get_mapped_value(time),
+            track => Err(TimelineError::TypeMismatch { expected: "float", found: track.type_name() }),
+        }
+    }
+
+    /// value range of a float or double track
+    pub fn try_get_range(&self, name: &str) -> Result<Option<&ValueRange>, TimelineError> {
+        match self.try_get(name)? {
+            TrackVariant::FloatTrack(track) => Ok(track.range.as_ref()),
+            TrackVariant::DoubleTrack(track) => Ok(track.range.as_ref()),
+            track => Err(TimelineError::TypeMismatch { expected: "float", found: track.type_name() }),
+        }
+    }
+
+    /// sample a `CustomTrack` wrapping a `Track<T>`. panics on errors (see `try_get_custom_value`)
+    pub fn get_custom_value<T>(&self, name: &str, time: Duration) -> T
+    where T: Interpolate + DeserializeOwned + 'static
+    {
+        self.try_get_custom_value(name, time).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_custom_value<T>(&self, name: &str, time: Duration) -> Result<T, TimelineError>
+    where T: Interpolate + DeserializeOwned + 'static
+    {
+        self.try_get(name)?.try_as_custom_track()?.try_get_value(self.get_sample_time(time))
+    }
+
+    pub fn set_frame_rate(&mut self, frame_rate: FrameRate) -> &mut Self {
+        self.frame_rate = Some(frame_rate);
+        self
+    }
+
+    /// see `quantize_to_frames`
+    pub fn set_quantize_to_frames(&mut self, quantize: bool) -> &mut Self {
+        self.quantize_to_frames = quantize;
+        self
+    }
+
+    /// `TimelineError::MissingFrameRate` if no frame rate is set
+    pub fn try_get_frame_rate(&self) -> Result<FrameRate, TimelineError> {
+        self.frame_rate.ok_or(TimelineError::MissingFrameRate)
+    }
+
+    /// start time of a frame
+    pub fn try_get_frame_time(&self, frame: u64) -> Result<Duration, TimelineError> {
+        Ok(self.try_get_frame_rate()?.frame_to_duration(frame))
+    }
+
+    /// frame containing `time`
+    pub fn try_get_frame(&self, time: Duration) -> Result<u64, TimelineError> {
+        Ok(self.try_get_frame_rate()?.duration_to_frame(time))
+    }
+
+    /// panics if the frame rate is not set, or the track is missing or empty (see `try_get_value_at_frame`)
+    pub fn get_value_at_frame(&self, name: &str, frame: u64) -> TrackValue {
+        self.try_get_value_at_frame(name, frame).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// sample a track at the start of a frame
+    pub fn try_get_value_at_frame(&self, name: &str, frame: u64) -> Result<TrackValue, TimelineError> {
+        self.try_get_value(name, self.try_get_frame_time(frame)?)
+    }
+
+    /// moves the keyframes of all tracks to the nearest frame boundary, see `TrackVariant::snap_to_frames`.
+    /// the tracks are left unchanged on an error
+    pub fn snap_to_frames(&mut self) -> Result<&mut Self, TimelineError> {
+        let rate = self.try_get_frame_rate()?;
+        let mut tracks = self.tracks.clone();
+        for track in tracks.values_mut() {
+            track.try_snap_to_frames(rate)?;
+        }
+        self.tracks = tracks;
+        Ok(self)
+    }
+
+    pub fn set_tempo_map(&mut self, tempo_map: TempoMap) -> &mut Self {
+        self.tempo_map = Some(tempo_map);
+        self
+    }
+
+    /// `TimelineError::MissingTempoMap` if no tempo map is set
+    pub fn try_get_tempo_map(&self) -> Result<&TempoMap, TimelineError> {
+        self.tempo_map.as_ref().ok_or(TimelineError::MissingTempoMap)
+    }
+
+    /// panics if the tempo map is not set, or the track is missing or empty (see `try_get_value_at_beat`)
+    pub fn get_value_at_beat(&self, name: &str, beat: f64) -> TrackValue {
+        self.try_get_value_at_beat(name, beat).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// sample a track at a beat position, see `TempoMap::get_beat`
+    pub fn try_get_value_at_beat(&self, name: &str, beat: f64) -> Result<TrackValue, TimelineError> {
+        self.try_get_value(name, self.try_get_tempo_map()?.try_get_time(beat)?)
+    }
+
+    /// moves the keyframes of all tracks to the nearest `1 / subdivision` beat, see `TrackVariant::snap_to_beats`.
+    /// the tracks are left unchanged on an error
+    pub fn snap_to_beats(&mut self, subdivision: u32) -> Result<&mut Self, TimelineError> {
+        let tempo_map = self.try_get_tempo_map()?;
+        let mut tracks = self.tracks.clone();
+        for track in tracks.values_mut() {
+            track.try_snap_to_beats(tempo_map, subdivision)?;
+        }
+        self.tracks = tracks;
+        Ok(self)
+    }
+
+    /// `time` quantized to frames if `quantize_to_frames` is set
+    fn get_sample_time(&self, time: Duration) -> Duration {
+        match self.frame_rate {
+            Some(rate) if self.quantize_to_frames => rate.quantize(time),
+            _ => time,
+        }
     }
 
     // pub fn get_value(&self, name: &str, time: Duration) -> T {
@@ -233,7 +678,7 @@ impl Timeline
     /// returns max duration of all tracks
     pub fn get_max_duration(&self) -> Duration {
         let mut max_duration = Duration::from_secs(0);
-        for (_, track) in &self.tracks {
+        for track in self.tracks.values() {
             let duration = track.get_duration();
             if duration > max_duration {
                 max_duration = duration;
@@ -243,11 +688,68 @@ impl Timeline
     }
 }
 
-#[derive(Debug)]
+/// How a track is evaluated before its first or after its last keyframe
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Extrapolation {
+    /// hold the first / last keyframe value
+    #[default]
+    Constant,
+    /// continue the slope of the first / last segment
+    Linear,
+    /// repeat the keyed range
+    Cycle,
+    /// repeat the keyed range, shifted by (last value - first value) on each repetition
+    CycleWithOffset,
+    /// repeat the keyed range, alternating forward and backward
+    PingPong,
+}
+
+/// How a whole track is interpolated between keyframes
+///
+/// The spline modes look at the neighbouring keyframes, so motion stays smooth through each keyframe.
+/// The first and last keyframes get reflected phantom neighbours.
+#[derive(Default, Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackInterpolation {
+    /// each keyframe decides (`Keyframe::interpolation`)
+    #[default]
+    PerKeyframe,
+    /// centripetal Catmull-Rom through the keyframe values
+    CatmullRom,
+    /// Kochanek-Bartels (TCB) Hermite spline. all zero is a Catmull-Rom spline in time
+    KochanekBartels {
+        tension: f32,
+        continuity: f32,
+        bias: f32,
+    },
+    /// uniform cubic B-spline. smoothest, but only passes through the first and last keyframes
+    BSpline,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(bound(serialize = "T: serde::Serialize", deserialize = "T: DeserializeOwned"))]
 pub struct Track<T>
 where T : Copy + DeserializeOwned
 {
    pub keyframes: Vec<Keyframe<T>>,
+   /// evaluation before the first keyframe
+   #[serde(default)]
+   pub pre_extrapolation: Extrapolation,
+   /// evaluation after the last keyframe
+   #[serde(default)]
+   pub post_extrapolation: Extrapolation,
+   #[serde(default)]
+   pub interpolation: TrackInterpolation,
+   /// how interpolated values are converted back to integers (integer tracks only)
+   #[serde(default)]
+   pub rounding: Rounding,
+   /// range the normalized values are mapped to (float and double tracks only), see `ValueRange`
+   #[serde(default)]
+   pub range: Option<ValueRange>,
+   /// see `DuplicateTimePolicy`
+   #[serde(default)]
+   pub duplicate_policy: DuplicateTimePolicy,
 }
 
 impl<T> Default for Track<T>
@@ -256,110 +758,293 @@ where T : Copy + DeserializeOwned
     fn default() -> Self {
         Track {
             keyframes: vec![],
+            pre_extrapolation: Extrapolation::default(),
+            post_extrapolation: Extrapolation::default(),
+            interpolation: TrackInterpolation::default(),
+            rounding: Rounding::default(),
+            range: None,
+            duplicate_policy: DuplicateTimePolicy::default(),
+        }
+    }
+}
+
+impl<T> Track<T>
+where T : Copy + DeserializeOwned
+{
+    /// panics if keyframes meet with `DuplicateTimePolicy::Error` (see `try_snap_times`)
+    pub fn snap_times(&mut self, snap: impl Fn(Duration) -> Duration) -> &mut Self {
+        self.try_snap_times(snap).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// moves every keyframe to `snap(time)` (`FrameRate::snap`, `TempoMap::snap`). `snap` must not change the order.
+    /// keyframes snapped to the same time go through `duplicate_policy`: `Replace` keeps the last one,
+    /// `Error` returns `TimelineError::DuplicateKeyframeTime` and leaves the track unchanged
+    pub fn try_snap_times(&mut self, snap: impl Fn(Duration) -> Duration) -> Result<&mut Self, TimelineError> {
+        let mut snapped: Vec<Keyframe<T>> = Vec::with_capacity(self.keyframes.len());
+        let mut previous_time = None;
+        for keyframe in &self.keyframes {
+            let time = snap(keyframe.time);
+            // keyframes already at the same time before snapping are kept
+            let met = previous_time != Some(keyframe.time) && snapped.last().is_some_and(|k| k.time == time);
+            previous_time = Some(keyframe.time);
+            if met {
+                match self.duplicate_policy {
+                    DuplicateTimePolicy::KeepBoth => {}
+                    DuplicateTimePolicy::Replace => {
+                        let first = snapped.partition_point(|k| k.time < time);
+                        snapped.truncate(first);
+                    }
+                    DuplicateTimePolicy::Error => return Err(TimelineError::DuplicateKeyframeTime(time)),
+                }
+            }
+            snapped.push(Keyframe { time, ..*keyframe });
         }
+        self.keyframes = snapped;
+        Ok(self)
+    }
+
+    pub fn set_duplicate_policy(&mut self, policy: DuplicateTimePolicy) -> &mut Self {
+        self.duplicate_policy = policy;
+        self
     }
 }
 
-trait InteroperableValue<T> {
+/// A value type that can be animated by a `Track`
+///
+/// Only `get_linear_combination` is required, which is enough for vector-like values.
+/// Override the other methods for values that are not a vector space (rotations, discrete states),
+/// see the `Quat` and `bool` implementations.
+///
+/// `Track<T>` of any `T: Interpolate + DeserializeOwned` is a `TimelineTrack<T>`;
+/// wrap it in a `CustomTrack` to store it in a `Timeline`.
+pub trait Interpolate: Copy {
+    /// sum of value * weight. used by the bezier and spline curves, weights can be negative
+    fn get_linear_combination(terms: &[(Self, f64)]) -> Self;
+
+    /// distance between two values, used for the centripetal Catmull-Rom knots.
+    /// the default is a constant, which gives a uniform Catmull-Rom spline
+    fn get_distance(_a: Self, _b: Self) -> f32 {
+        1.0
+    }
+
+    /// `a` at `t = 0`, `b` at `t = 1`
+    fn lerp(a: Self, b: Self, t: f64) -> Self {
+        Self::get_linear_combination(&[(a, 1.0 - t), (b, t)])
+    }
+
+    /// value `time` seconds into a segment of `duration` seconds. the default is `lerp` with the eased progress
     fn get_easing_value(
-        time: f32, start_value: T, next_value: T,
-        duration: f32, easing_function: EasingFunction, easing_type: EasingType) -> T;
-    fn get_self(&self) -> T;
+        time: f64, start_value: Self, next_value: Self,
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType) -> Self
+    {
+        let t = easing::easing(time, 0.0, 1.0, duration, easing_function, easing_type);
+        Self::lerp(start_value, next_value, t)
+    }
+
+    /// value + (to_value - from_value) * count, used by `Extrapolation::CycleWithOffset`
+    fn get_offset_value(value: Self, from_value: Self, to_value: Self, count: i64) -> Self {
+        Self::get_linear_combination(&[(value, 1.0), (to_value, count as f64), (from_value, -(count as f64))])
+    }
+
+    /// `get_easing_value` with the `Rounding` of the track. only integer types round, the default ignores it
+    fn get_rounded_easing_value(
+        time: f64, start_value: Self, next_value: Self,
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType, _rounding: Rounding) -> Self
+    {
+        Self::get_easing_value(time, start_value, next_value, duration, easing_function, easing_type)
+    }
+
+    /// `get_linear_combination` with the `Rounding` of the track, see `get_rounded_easing_value`
+    fn get_rounded_linear_combination(terms: &[(Self, f64)], _rounding: Rounding) -> Self {
+        Self::get_linear_combination(terms)
+    }
 }
 
-impl InteroperableValue<f32> for f32 {
+impl Interpolate for f32 {
     fn get_easing_value(
-        time: f32, start_value: f32, next_value: f32,
-        duration: f32, easing_function: EasingFunction, easing_type: EasingType) -> f32
+        time: f64, start_value: f32, next_value: f32,
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType) -> f32
     {
+        let (time, duration) = (time as f32, duration as f32);
         easing::easing(time, start_value, next_value - start_value, duration, easing_function, easing_type)
     }
     
-    fn get_self(&self) -> f32 {
-        *self
+    fn get_offset_value(value: f32, from_value: f32, to_value: f32, count: i64) -> f32 {
+        value + (to_value - from_value) * count as f32
+    }
+
+    fn get_linear_combination(terms: &[(f32, f64)]) -> f32 {
+        terms.iter().map(|(v, w)| *v as f64 * w).sum::<f64>() as f32
+    }
+
+    fn get_distance(a: f32, b: f32) -> f32 {
+        (a - b).abs()
     }
 }
 
-impl InteroperableValue<f64> for f64 {
+impl Interpolate for f64 {
     fn get_easing_value(
-        time: f32, start_value: f64, next_value: f64,
-        duration: f32, easing_function: EasingFunction, easing_type: EasingType) -> f64
+        time: f64, start_value: f64, next_value: f64,
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType) -> f64
     {
-        easing::easing(time, start_value as f32, (next_value - start_value) as f32, duration, easing_function, easing_type) as f64
+        easing::easing(time, start_value, next_value - start_value, duration, easing_function, easing_type)
+    }
+
+    fn get_offset_value(value: f64, from_value: f64, to_value: f64, count: i64) -> f64 {
+        value + (to_value - from_value) * count as f64
+    }
+
+    fn get_linear_combination(terms: &[(f64, f64)]) -> f64 {
+        terms.iter().map(|(v, w)| v * w).sum()
     }
 
-    fn get_self(&self) -> f64 {
-        *self
+    fn get_distance(a: f64, b: f64) -> f32 {
+        (a - b).abs() as f32
     }
 }
 
-impl InteroperableValue<i32> for i32 {
+impl Interpolate for i32 {
     fn get_easing_value(
-        time: f32, start_value: i32, next_value: i32,
-        duration: f32, easing_function: EasingFunction, easing_type: EasingType) -> i32
+        time: f64, start_value: i32, next_value: i32,
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType) -> i32
+    {
+        i32::get_rounded_easing_value(time, start_value, next_value, duration, easing_function, easing_type, Rounding::default())
+    }
+
+    fn get_rounded_easing_value(
+        time: f64, start_value: i32, next_value: i32,
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType, rounding: Rounding) -> i32
     {
-        let changing_value = next_value - start_value;
-        easing::easing(
-            time, start_value as f32, changing_value as f32, duration, easing_function, easing_type
-        ) as i32
+        if rounding == Rounding::Step {
+            return if time < duration { start_value } else { next_value };
+        }
+        let changing_value = next_value as f64 - start_value as f64;
+        rounding.round(easing::easing(
+            time, start_value as f64, changing_value, duration, easing_function, easing_type
+        )) as i32
+    }
+
+    /// computed in i64, saturating at the i32 range
+    fn get_offset_value(value: i32, from_value: i32, to_value: i32, count: i64) -> i32 {
+        let offset = (to_value as i64 - from_value as i64).saturating_mul(count);
+        (value as i64).saturating_add(offset).clamp(i32::MIN as i64, i32::MAX as i64) as i32
+    }
+
+    fn get_linear_combination(terms: &[(i32, f64)]) -> i32 {
+        i32::get_rounded_linear_combination(terms, Rounding::default())
+    }
+
+    fn get_rounded_linear_combination(terms: &[(i32, f64)], rounding: Rounding) -> i32 {
+        rounding.round(terms.iter().map(|(v, w)| *v as f64 * w).sum::<f64>()) as i32
     }
 
-    fn get_self(&self) -> i32 {
-        *self
+    fn get_distance(a: i32, b: i32) -> f32 {
+        (a as f32 - b as f32).abs()
     }
 }
 
-impl InteroperableValue<i64> for i64 {
+impl Interpolate for i64 {
     fn get_easing_value(
-        time: f32, start_value: i64, next_value: i64,
-        duration: f32, easing_function: EasingFunction, easing_type: EasingType) -> i64
+        time: f64, start_value: i64, next_value: i64,
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType) -> i64
+    {
+        i64::get_rounded_easing_value(time, start_value, next_value, duration, easing_function, easing_type, Rounding::default())
+    }
+
+    fn get_rounded_easing_value(
+        time: f64, start_value: i64, next_value: i64,
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType, rounding: Rounding) -> i64
     {
-        let changing_value = next_value - start_value;
-        easing::easing(
-            time, start_value as f32, changing_value as f32, duration, easing_function, easing_type
-        ) as i64
+        if rounding == Rounding::Step {
+            return if time < duration { start_value } else { next_value };
+        }
+        let changing_value = next_value as f64 - start_value as f64;
+        rounding.round(easing::easing(
+            time, start_value as f64, changing_value, duration, easing_function, easing_type
+        )) as i64
+    }
+
+    fn get_offset_value(value: i64, from_value: i64, to_value: i64, count: i64) -> i64 {
+        value.saturating_add(to_value.saturating_sub(from_value).saturating_mul(count))
+    }
+
+    fn get_linear_combination(terms: &[(i64, f64)]) -> i64 {
+        i64::get_rounded_linear_combination(terms, Rounding::default())
+    }
+
+    fn get_rounded_linear_combination(terms: &[(i64, f64)], rounding: Rounding) -> i64 {
+        rounding.round(terms.iter().map(|(v, w)| *v as f64 * w).sum::<f64>()) as i64
     }
 
-    fn get_self(&self) -> i64 {
-        *self
+    fn get_distance(a: i64, b: i64) -> f32 {
+        (a as f64 - b as f64).abs() as f32
     }
 }
 
-impl InteroperableValue<bool> for bool {
+impl Interpolate for bool {
     fn get_easing_value(
-        time: f32, start_value: bool, next_value: bool,
-        duration: f32, easing_function: EasingFunction, easing_type: EasingType) -> bool
+        _time: f64, start_value: bool, _next_value: bool,
+        _duration: f64, _easing_function: EasingFunction, _easing_type: EasingType) -> bool
     {
         // WORKAROUND:
         start_value
     }
 
-    fn get_self(&self) -> bool {
-        *self
+    fn get_offset_value(value: bool, _from_value: bool, _to_value: bool, _count: i64) -> bool {
+        // WORKAROUND: bool has no offset
+        value
+    }
+
+    /// the value with the larger total weight (ties keep the first term's value),
+    /// so splines and bezier curves switch near the middle of a segment instead of blending
+    fn get_linear_combination(terms: &[(bool, f64)]) -> bool {
+        let weight = |value: bool| terms.iter().filter(|(v, _)| *v == value).map(|(_, w)| w).sum::<f64>();
+        let first = terms[0].0;
+        if weight(!first) > weight(first) { !first } else { first }
+    }
+
+    fn get_distance(a: bool, b: bool) -> f32 {
+        if a == b { 0.0 } else { 1.0 }
     }
 }
 
-impl InteroperableValue<(f32, f32)> for (f32, f32) {
+impl Interpolate for (f32, f32) {
     fn get_easing_value(
-        time: f32, start_value: (f32, f32), next_value: (f32, f32),
-        duration: f32, easing_function: EasingFunction, easing_type: EasingType) -> (f32, f32)
+        time: f64, start_value: (f32, f32), next_value: (f32, f32),
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType) -> (f32, f32)
     {
+        let (time, duration) = (time as f32, duration as f32);
         (
             easing::easing(time, start_value.0, next_value.0 - start_value.0, duration, easing_function, easing_type),
             easing::easing(time, start_value.1, next_value.1 - start_value.1, duration, easing_function, easing_type),
         )
     }
 
-    fn get_self(&self) -> (f32, f32) {
-        *self
+    fn get_offset_value(value: (f32, f32), from_value: (f32, f32), to_value: (f32, f32), count: i64) -> (f32, f32) {
+        (
+            value.0 + (to_value.0 - from_value.0) * count as f32,
+            value.1 + (to_value.1 - from_value.1) * count as f32,
+        )
+    }
+
+    fn get_linear_combination(terms: &[((f32, f32), f64)]) -> (f32, f32) {
+        (
+            terms.iter().map(|(v, w)| v.0 as f64 * w).sum::<f64>() as f32,
+            terms.iter().map(|(v, w)| v.1 as f64 * w).sum::<f64>() as f32,
+        )
+    }
+
+    fn get_distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
     }
 }
 
-impl InteroperableValue<(f32, f32, f32)> for (f32, f32, f32) {
+impl Interpolate for (f32, f32, f32) {
     fn get_easing_value(
-        time: f32, start_value: (f32, f32, f32), next_value: (f32, f32, f32),
-        duration: f32, easing_function: EasingFunction, easing_type: EasingType) -> (f32, f32, f32)
+        time: f64, start_value: (f32, f32, f32), next_value: (f32, f32, f32),
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType) -> (f32, f32, f32)
     {
+        let (time, duration) = (time as f32, duration as f32);
         (
             easing::easing(time, start_value.0, next_value.0 - start_value.0, duration, easing_function, easing_type),
             easing::easing(time, start_value.1, next_value.1 - start_value.1, duration, easing_function, easing_type),
@@ -367,16 +1052,33 @@ impl InteroperableValue<(f32, f32, f32)> for (f32, f32, f32) {
         )
     }
 
-    fn get_self(&self) -> (f32, f32, f32) {
-        *self
+    fn get_offset_value(value: (f32, f32, f32), from_value: (f32, f32, f32), to_value: (f32, f32, f32), count: i64) -> (f32, f32, f32) {
+        (
+            value.0 + (to_value.0 - from_value.0) * count as f32,
+            value.1 + (to_value.1 - from_value.1) * count as f32,
+            value.2 + (to_value.2 - from_value.2) * count as f32,
+        )
+    }
+
+    fn get_linear_combination(terms: &[((f32, f32, f32), f64)]) -> (f32, f32, f32) {
+        (
+            terms.iter().map(|(v, w)| v.0 as f64 * w).sum::<f64>() as f32,
+            terms.iter().map(|(v, w)| v.1 as f64 * w).sum::<f64>() as f32,
+            terms.iter().map(|(v, w)| v.2 as f64 * w).sum::<f64>() as f32,
+        )
+    }
+
+    fn get_distance(a: (f32, f32, f32), b: (f32, f32, f32)) -> f32 {
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
     }
 }
 
-impl InteroperableValue<(f32, f32, f32, f32)> for (f32, f32, f32, f32) {
+impl Interpolate for (f32, f32, f32, f32) {
     fn get_easing_value(
-        time: f32, start_value: (f32, f32, f32, f32), next_value: (f32, f32, f32, f32),
-        duration: f32, easing_function: EasingFunction, easing_type: EasingType) -> (f32, f32, f32, f32)
+        time: f64, start_value: (f32, f32, f32, f32), next_value: (f32, f32, f32, f32),
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType) -> (f32, f32, f32, f32)
     {
+        let (time, duration) = (time as f32, duration as f32);
         (
             easing::easing(time, start_value.0, next_value.0 - start_value.0, duration, easing_function, easing_type),
             easing::easing(time, start_value.1, next_value.1 - start_value.1, duration, easing_function, easing_type),
@@ -385,37 +1087,132 @@ impl InteroperableValue<(f32, f32, f32, f32)> for (f32, f32, f32, f32) {
         )
     }
 
-    fn get_self(&self) -> (f32, f32, f32, f32) {
-        *self
+    fn get_offset_value(value: (f32, f32, f32, f32), from_value: (f32, f32, f32, f32), to_value: (f32, f32, f32, f32), count: i64) -> (f32, f32, f32, f32) {
+        (
+            value.0 + (to_value.0 - from_value.0) * count as f32,
+            value.1 + (to_value.1 - from_value.1) * count as f32,
+            value.2 + (to_value.2 - from_value.2) * count as f32,
+            value.3 + (to_value.3 - from_value.3) * count as f32,
+        )
+    }
+
+    fn get_linear_combination(terms: &[((f32, f32, f32, f32), f64)]) -> (f32, f32, f32, f32) {
+        (
+            terms.iter().map(|(v, w)| v.0 as f64 * w).sum::<f64>() as f32,
+            terms.iter().map(|(v, w)| v.1 as f64 * w).sum::<f64>() as f32,
+            terms.iter().map(|(v, w)| v.2 as f64 * w).sum::<f64>() as f32,
+            terms.iter().map(|(v, w)| v.3 as f64 * w).sum::<f64>() as f32,
+        )
+    }
+
+    fn get_distance(a: (f32, f32, f32, f32), b: (f32, f32, f32, f32)) -> f32 {
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2) + (a.3 - b.3).powi(2)).sqrt()
+    }
+}
+
+impl Interpolate for Quat {
+    /// slerp along the shortest arc
+    fn lerp(a: Quat, b: Quat, t: f64) -> Quat {
+        a.slerp(b, t as f32)
+    }
+
+    /// slerp along the shortest arc, with the eased progress
+    fn get_easing_value(
+        time: f64, start_value: Quat, next_value: Quat,
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType) -> Quat
+    {
+        let t = easing::easing(time, 0.0, 1.0, duration, easing_function, easing_type) as f32;
+        start_value.slerp(next_value, t)
+    }
+
+    /// the rotation from `from_value` to `to_value` applied `count` more times
+    fn get_offset_value(value: Quat, from_value: Quat, to_value: Quat, count: i64) -> Quat {
+        ((to_value * from_value.conjugate()).powf(count as f32) * value).normalize()
+    }
+
+    /// normalized weighted sum, each term flipped into the hemisphere of the first one.
+    /// only an approximation of a spherical blend, good enough for bezier/spline curves between close rotations
+    fn get_linear_combination(terms: &[(Quat, f64)]) -> Quat {
+        let reference = terms.first().map(|(q, _)| *q).unwrap_or_default();
+        let mut sum = Quat::new(0.0, 0.0, 0.0, 0.0);
+        for (q, w) in terms {
+            let q = if q.dot(reference) < 0.0 { -*q } else { *q };
+            let w = *w as f32;
+            sum = Quat::new(sum.x + q.x * w, sum.y + q.y * w, sum.z + q.z * w, sum.w + q.w * w);
+        }
+        sum.normalize()
+    }
+
+    /// rotation angle in radians
+    fn get_distance(a: Quat, b: Quat) -> f32 {
+        a.angle_between(b)
     }
 }
 
 pub fn get_easing_value<T>(
-    time: f32, start_value: T, next_value: T,
-    duration: f32, easing_function: EasingFunction, easing_type: EasingType) -> T
-where T: InteroperableValue<T>
+    time: f64, start_value: T, next_value: T,
+    duration: f64, easing_function: EasingFunction, easing_type: EasingType) -> T
+where T: Interpolate
 {
-    T::get_easing_value(time, start_value.get_self(), next_value.get_self(), duration, easing_function, easing_type)
+    T::get_easing_value(time, start_value, next_value, duration, easing_function, easing_type)
 }
 
 pub trait TimelineTrackImpl<T>  {
     fn get_easing_value_wrap(
         &self,
-        time: f32, start_value: T, next_value: T,
-        duration: f32, easing_function: EasingFunction, easing_type: EasingType) -> T;
+        time: f64, start_value: T, next_value: T,
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType) -> T;
+    fn get_offset_value_wrap(&self, value: T, from_value: T, to_value: T, count: i64) -> T;
+    fn get_linear_combination_wrap(&self, terms: &[(T, f64)]) -> T;
+    fn get_distance_wrap(&self, a: T, b: T) -> f32;
 
     fn get_keyframes(&self) -> &Vec<Keyframe<T>>;
     fn get_keyframes_mut(&mut self) -> &mut Vec<Keyframe<T>>;
+    fn get_pre_extrapolation(&self) -> Extrapolation;
+    fn get_post_extrapolation(&self) -> Extrapolation;
+    fn get_interpolation(&self) -> TrackInterpolation;
+    fn get_rounding(&self) -> Rounding;
+    fn get_duplicate_policy(&self) -> DuplicateTimePolicy {
+        DuplicateTimePolicy::default()
+    }
 }
 
+/// Keyframe access and editing
+///
+/// The editing methods expect the keyframes sorted by time (call `sort_keyframes` after changing them directly).
 pub trait TimelineTrack<T> {
+    /// inserts the keyframe at its sorted position, see `DuplicateTimePolicy` for keyframes at the same time.
+    /// panics with `DuplicateTimePolicy::Error` (see `try_add_keyframe`)
     fn add_keyframe(&mut self, keyframe: Keyframe<T>) -> &mut Self;
+    /// same as `add_keyframe`, returns the index of the inserted keyframe
+    fn try_add_keyframe(&mut self, keyframe: Keyframe<T>) -> Result<usize, TimelineError>;
     fn add_keyframes(&mut self, keyframes: Vec<Keyframe<T>>) -> &mut Self;
     fn sort_keyframes(&mut self) -> &mut Self;
     fn get_keyframe(&self, index: usize) -> Option<&Keyframe<T>>;
     fn get_keyframe_mut(&mut self, index: usize) -> Option<&mut Keyframe<T>>;
+    /// `None` if the index is out of range
+    fn remove_keyframe(&mut self, index: usize) -> Option<Keyframe<T>>;
+    /// removes the keyframes at exactly `time`
+    fn remove_keyframes_at(&mut self, time: Duration) -> Vec<Keyframe<T>>;
+    /// removes the keyframes in `start <= time < end`
+    fn remove_keyframes_in_range(&mut self, start: Duration, end: Duration) -> Vec<Keyframe<T>>;
+    /// moves a keyframe to `time` (following the `DuplicateTimePolicy`) and returns its new index.
+    /// the track is unchanged on errors
+    fn move_keyframe(&mut self, index: usize, time: Duration) -> Result<usize, TimelineError>;
+    fn set_keyframe_value(&mut self, index: usize, value: T) -> Result<&mut Self, TimelineError>;
+    /// keyframes in `start <= time < end`
+    fn keyframes_in_range(&self, start: Duration, end: Duration) -> &[Keyframe<T>];
+    /// index of the keyframe nearest to `time` if it is within `tolerance` (the earlier one on a tie)
+    fn get_nearest_keyframe_index(&self, time: Duration, tolerance: Duration) -> Option<usize>;
+    /// sets the in handle of a keyframe. auto handles become `HandleType::Aligned`,
+    /// and with `HandleType::Aligned` the out handle is moved to the same slope
+    fn set_in_handle(&mut self, index: usize, handle: BezierHandle<T>) -> &mut Self;
+    /// sets the out handle of a keyframe, see `set_in_handle`
+    fn set_out_handle(&mut self, index: usize, handle: BezierHandle<T>) -> &mut Self;
     fn get_duration(&self) -> Duration;
+    /// panics if the track has no keyframes (see `try_get_value`)
     fn get_value(&self, time: Duration) -> T;
+    fn try_get_value(&self, time: Duration) -> Result<T, TimelineError>;
 }
 
 impl<U, T> TimelineTrack<T> for U
@@ -424,11 +1221,29 @@ where
     T: Copy
 {
     fn add_keyframe(&mut self, keyframe: Keyframe<T>) -> &mut Self {
-        self.get_keyframes_mut().push(keyframe);
-        self.sort_keyframes();
+        self.try_add_keyframe(keyframe).unwrap_or_else(|e| panic!("{}", e));
         self
     }
 
+    fn try_add_keyframe(&mut self, keyframe: Keyframe<T>) -> Result<usize, TimelineError> {
+        let policy = self.get_duplicate_policy();
+        let keyframes = self.get_keyframes_mut();
+        let first = keyframe_index(keyframes, keyframe.time);
+        let mut last = keyframes.partition_point(|k| k.time <= keyframe.time);
+        if first < last {
+            match policy {
+                DuplicateTimePolicy::KeepBoth => {}
+                DuplicateTimePolicy::Replace => {
+                    keyframes.drain(first..last);
+                    last = first;
+                }
+                DuplicateTimePolicy::Error => return Err(TimelineError::DuplicateKeyframeTime(keyframe.time)),
+            }
+        }
+        keyframes.insert(last, keyframe);
+        Ok(last)
+    }
+
     fn add_keyframes(&mut self, keyframes: Vec<Keyframe<T>>) -> &mut Self {
         for keyframe in keyframes {
             self.add_keyframe(keyframe);
@@ -438,7 +1253,7 @@ where
     }
 
     fn sort_keyframes(&mut self) -> &mut Self {
-        self.get_keyframes_mut().sort_by(|a, b| a.time.cmp(&b.time));
+        self.get_keyframes_mut().sort_by_key(|keyframe| keyframe.time);
         self
     }
 
@@ -450,154 +1265,640 @@ where
         self.get_keyframes_mut().get_mut(index)
     }
 
-    fn get_duration(&self) -> Duration {
-        if self.get_keyframes().len() > 0 {
-            self.get_keyframes()[self.get_keyframes().len() - 1].time
-        } else {
-            Duration::from_secs(0)
-        }
+    fn remove_keyframe(&mut self, index: usize) -> Option<Keyframe<T>> {
+        let keyframes = self.get_keyframes_mut();
+        (index < keyframes.len()).then(|| keyframes.remove(index))
     }
 
-    fn get_value(&self, time: Duration) -> T {
-        let mut value = None;
-        let mut prev_keyframe = None;
-        let mut next_keyframe = None;
-        let n = self.get_keyframes().len();
-
-        if n == 0 {
-            panic!("No keyframes");
-        } else if n == 1 {
-            return self.get_keyframes()[0].value;
-        } else {
-            // if before first keyframe time
-            if time < self.get_keyframes()[0].time {
-                // WORKAROUND: return first keyframe value
-                return self.get_keyframes()[0].value;
-            }
-        }
+    fn remove_keyframes_at(&mut self, time: Duration) -> Vec<Keyframe<T>> {
+        let keyframes = self.get_keyframes_mut();
+        let first = keyframe_index(keyframes, time);
+        let last = keyframes.partition_point(|k| k.time <= time);
+        keyframes.drain(first..last).collect()
+    }
 
-        for i in 0..n {
-            let keyframe = &self.get_keyframes()[i];
-            if keyframe.time == time {
-                value = Some(keyframe.value);
-                break;
-            }
-            if keyframe.time < time {
-                prev_keyframe = Some(keyframe);
-                // if has next
-                if i + 1 < n {
-                    // and in range
-                    if self.get_keyframes()[i + 1].time > time {
-                        next_keyframe = Some(&self.get_keyframes()[i + 1]);
-                        break;
-                    }
-                } else {
-                    next_keyframe = None;
-                    break;
-                }
-            }
-        }
-        if value.is_none() {
-
-            // if exceed last keyframe time
-            if next_keyframe.is_none() {
-                // WORKAROUND: return last keyframe value
-                value = Some(prev_keyframe.unwrap().value);
-
-            // if before first keyframe time
-            } else if prev_keyframe.is_none() {
-                unreachable!();
-            
-            // if normal
-            } else if let Some(prev_keyframe) = prev_keyframe {
-                if let Some(next_keyframe) = next_keyframe {
-                    let duration = (next_keyframe.time - prev_keyframe.time).as_secs_f32();
-                    let dt = (time - prev_keyframe.time).as_secs_f32();
-                    // value = Some(prev_keyframe.value + (next_keyframe.value - prev_keyframe.value) * t);
-                    // use easing
-                    let v1 = prev_keyframe.value;
-                    let v2 = next_keyframe.value;
-                    // println!("dt, duration: {}, {}", dt, duration);
-                    // println!("t: {}", time.as_secs_f32());
-                    // println!("t0, t1: {}, {}", prev_keyframe.time.as_secs_f32(), next_keyframe.time.as_secs_f32());
-                    // println!("v1, v2: {}, {}", v1, v2);
-                    // println!("easing({}, {}, {}, {}, {:?}, {:?})", dt, v1, v2 - v1, duration, prev_keyframe.easing_function, prev_keyframe.easing_type);
-                    // value = Some(easing::easing(
-                    //     dt,
-                    //     v1,
-                    //     v2 - v1,
-                    //     duration,
-                    //     prev_keyframe.easing_function,
-                    //     prev_keyframe.easing_type,
-                    // ).into());
-
-                    value = Some(self.get_easing_value_wrap(
-                        dt,
-                        v1,
-                        v2,
-                        duration,
-                        prev_keyframe.easing_function,
-                        prev_keyframe.easing_type,
-                    ));
-                    // let v: f32 = value.unwrap().into();
-                    // println!("value: {}", v);
-                }else{
-                    unreachable!();
-                }
-            }
-        }
-        value.unwrap()
+    fn remove_keyframes_in_range(&mut self, start: Duration, end: Duration) -> Vec<Keyframe<T>> {
+        let keyframes = self.get_keyframes_mut();
+        let first = keyframe_index(keyframes, start);
+        let last = keyframe_index(keyframes, end).max(first);
+        keyframes.drain(first..last).collect()
     }
-}
 
-macro_rules! timeline_track_impl {
-    ($tp:ident) => {
-        impl TimelineTrackImpl<$tp> for Track<$tp> {
-            fn get_easing_value_wrap(
-                &self,
-                time: f32, start_value: $tp, next_value: $tp,
-                duration: f32, easing_function: EasingFunction, easing_type: EasingType) -> $tp
-            {
-                <$tp>::get_easing_value(time, start_value, next_value, duration, easing_function, easing_type)
-            }
+    fn move_keyframe(&mut self, index: usize, time: Duration) -> Result<usize, TimelineError> {
+        let keyframe = self.remove_keyframe(index).ok_or(TimelineError::KeyframeIndexOutOfRange(index))?;
+        self.try_add_keyframe(Keyframe { time, ..keyframe }).inspect_err(|_| {
+            self.get_keyframes_mut().insert(index, keyframe);
+        })
+    }
 
-            fn get_keyframes(&self) -> &Vec<Keyframe<$tp>> {
-                &self.keyframes
-            }
+    fn set_keyframe_value(&mut self, index: usize, value: T) -> Result<&mut Self, TimelineError> {
+        let keyframe = self.get_keyframe_mut(index).ok_or(TimelineError::KeyframeIndexOutOfRange(index))?;
+        keyframe.value = value;
+        Ok(self)
+    }
 
-            fn get_keyframes_mut(&mut self) -> &mut Vec<Keyframe<$tp>> {
-                &mut self.keyframes
-            }
-        }
-    };
-}
+    fn keyframes_in_range(&self, start: Duration, end: Duration) -> &[Keyframe<T>] {
+        let keyframes = self.get_keyframes();
+        let first = keyframe_index(keyframes, start);
+        let last = keyframe_index(keyframes, end).max(first);
+        &keyframes[first..last]
+    }
 
-timeline_track_impl!(f32);
-timeline_track_impl!(f64);
-timeline_track_impl!(i32);
-timeline_track_impl!(i64);
-timeline_track_impl!(bool);
-timeline_track_impl!(MyVec2);
-timeline_track_impl!(MyVec3);
-timeline_track_impl!(MyVec4);
+    fn get_nearest_keyframe_index(&self, time: Duration, tolerance: Duration) -> Option<usize> {
+        let keyframes = self.get_keyframes();
+        let index = keyframe_index(keyframes, time);
+        // the last keyframe before `time`, or the first one at or after it
+        let before = index.checked_sub(1).map(|i| (i, time - keyframes[i].time));
+        let after = keyframes.get(index).map(|k| (index, k.time - time));
+        let (nearest, distance) = match (before, after) {
+            (Some(before), Some(after)) => if after.1 < before.1 { after } else { before },
+            (Some(nearest), None) | (None, Some(nearest)) => nearest,
+            (None, None) => return None,
+        };
+        (distance <= tolerance).then_some(nearest)
+    }
 
-#[derive(Debug, Default)]
-pub struct Keyframe<T> {
-    pub time: Duration,
-    pub value: T,
-    pub easing_function: EasingFunction,
+    fn set_in_handle(&mut self, index: usize, handle: BezierHandle<T>) -> &mut Self {
+        set_handle(self, index, handle, false);
+        self
+    }
+
+    fn set_out_handle(&mut self, index: usize, handle: BezierHandle<T>) -> &mut Self {
+        set_handle(self, index, handle, true);
+        self
+    }
+
+    fn get_duration(&self) -> Duration {
+        match self.get_keyframes().last() {
+            Some(keyframe) => keyframe.time,
+            None => Duration::from_secs(0),
+        }
+    }
+
+    fn get_value(&self, time: Duration) -> T {
+        self.try_get_value(time).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn try_get_value(&self, time: Duration) -> Result<T, TimelineError> {
+        let index = keyframe_index(self.get_keyframes(), time);
+        get_value_at_index(self, index, time)
+    }
+}
+
+/// index of the first keyframe whose time is not before `time` (binary search)
+///
+/// keyframes must be sorted by time
+pub(crate) fn keyframe_index<T>(keyframes: &[Keyframe<T>], time: Duration) -> usize {
+    keyframes.partition_point(|keyframe| keyframe.time < time)
+}
+
+/// evaluates the track at `time`, where `index` is the result of `keyframe_index` for the same time
+pub(crate) fn get_value_at_index<U, T>(track: &U, index: usize, time: Duration) -> Result<T, TimelineError>
+where
+    U: TimelineTrackImpl<T> + ?Sized,
+    T: Copy
+{
+    let keyframes = track.get_keyframes();
+    let n = keyframes.len();
+
+    if n == 0 {
+        return Err(TimelineError::EmptyTrack);
+    }
+
+    // if before first keyframe time
+    if index == 0 && time < keyframes[0].time {
+        return extrapolate(track, track.get_pre_extrapolation(), time);
+    }
+
+    // if exceed last keyframe time
+    if index >= n {
+        return extrapolate(track, track.get_post_extrapolation(), time);
+    }
+
+    let interpolation = track.get_interpolation();
+    let next_keyframe = &keyframes[index];
+    if next_keyframe.time == time {
+        // B-spline does not pass through the inner keyframes
+        if interpolation == TrackInterpolation::BSpline && index + 1 < n {
+            return Ok(get_spline_value(track, interpolation, index, 0.0));
+        }
+        return Ok(next_keyframe.value);
+    }
+
+    let prev_keyframe = &keyframes[index - 1];
+    let duration = (next_keyframe.time - prev_keyframe.time).as_secs_f64();
+    let dt = (time - prev_keyframe.time).as_secs_f64();
+
+    if track.get_rounding() == Rounding::Step {
+        return Ok(prev_keyframe.value);
+    }
+
+    if interpolation != TrackInterpolation::PerKeyframe {
+        return Ok(get_spline_value(track, interpolation, index - 1, dt));
+    }
+
+    match prev_keyframe.interpolation {
+        Interpolation::Constant => Ok(prev_keyframe.value),
+        Interpolation::StepEnd => Ok(next_keyframe.value),
+        Interpolation::Linear => Ok(track.get_easing_value_wrap(
+            dt,
+            prev_keyframe.value,
+            next_keyframe.value,
+            duration,
+            EasingFunction::Linear,
+            EasingType::In,
+        )),
+        Interpolation::Eased => Ok(track.get_easing_value_wrap(
+            dt,
+            prev_keyframe.value,
+            next_keyframe.value,
+            duration,
+            prev_keyframe.easing_function,
+            prev_keyframe.easing_type,
+        )),
+        Interpolation::Bezier => Ok(get_bezier_value(track, index - 1, dt, duration)),
+    }
+}
+
+/// evaluates the spline segment from keyframe `index` to `index + 1`, `dt` seconds after keyframe `index`
+fn get_spline_value<U, T>(track: &U, interpolation: TrackInterpolation, index: usize, dt: f64) -> T
+where
+    U: TimelineTrackImpl<T> + ?Sized,
+    T: Copy
+{
+    let keyframes = track.get_keyframes();
+    let n = keyframes.len();
+    let i = index;
+    let seconds = |a: usize, b: usize| (keyframes[b].time - keyframes[a].time).as_secs_f64();
+
+    // the four control points as weighted keyframe indices, reflected at both ends
+    let p0 = if i > 0 { [(i - 1, 1.0), (i - 1, 0.0)] } else { [(i, 2.0), (i + 1, -1.0)] };
+    let p1 = [(i, 1.0), (i, 0.0)];
+    let p2 = [(i + 1, 1.0), (i + 1, 0.0)];
+    let p3 = if i + 2 < n { [(i + 2, 1.0), (i + 2, 0.0)] } else { [(i + 1, 2.0), (i, -1.0)] };
+
+    let d1 = seconds(i, i + 1);
+    let d0 = if i > 0 { seconds(i - 1, i) } else { d1 };
+    let d2 = if i + 2 < n { seconds(i + 1, i + 2) } else { d1 };
+    if d1 == 0.0 {
+        return keyframes[i].value;
+    }
+    let u = dt / d1;
+
+    let weights = match interpolation {
+        TrackInterpolation::PerKeyframe => unreachable!(),
+        TrackInterpolation::CatmullRom => {
+            // a reflected neighbour is as far away as the segment itself
+            let distance = |a: usize, b: usize| track.get_distance_wrap(keyframes[a].value, keyframes[b].value) as f64;
+            let d12 = distance(i, i + 1);
+            let d01 = if i > 0 { distance(i - 1, i) } else { d12 };
+            let d23 = if i + 2 < n { distance(i + 1, i + 2) } else { d12 };
+            let (k0, k1, k2) = (centripetal_knot(d01), centripetal_knot(d12), centripetal_knot(d23));
+            catmull_rom_weights(warp_time(u, d0, d1, d2, k0, k1, k2), k0, k1, k2)
+        }
+        TrackInterpolation::KochanekBartels { tension, continuity, bias } => {
+            kochanek_bartels_weights(u, tension as f64, continuity as f64, bias as f64, d0, d1, d2)
+        }
+        TrackInterpolation::BSpline => b_spline_weights(warp_time(u, d0, d1, d2, 1.0, 1.0, 1.0)),
+    };
+
+    let mut terms = [(keyframes[i].value, 0.0); 8];
+    for (k, point) in [p0, p1, p2, p3].iter().enumerate() {
+        for (j, (keyframe_index, factor)) in point.iter().enumerate() {
+            terms[k * 2 + j] = (keyframes[*keyframe_index].value, weights[k] * factor);
+        }
+    }
+    track.get_linear_combination_wrap(&terms)
+}
+
+/// maps the segment time `s` (0..1) to the spline parameter `u` (0..1), so that the spline parameter
+/// is C1 in time across keyframes. `d0`..`d2` are the durations and `l0`..`l2` the parameter lengths
+/// of the previous, current and next segment
+fn warp_time(s: f64, d0: f64, d1: f64, d2: f64, l0: f64, l1: f64, l2: f64) -> f64 {
+    // parameter speed at each keyframe is the harmonic mean of the neighbouring segments,
+    // which keeps the mapping monotonic (Fritsch-Butland)
+    let harmonic = |a: f64, b: f64| if a + b > 0.0 { 2.0 * a * b / (a + b) } else { 0.0 };
+    let speed = |l: f64, d: f64| if d > 0.0 { l / d } else { 0.0 };
+    let (v0, v1, v2) = (speed(l0, d0), speed(l1, d1), speed(l2, d2));
+    let m1 = harmonic(v0, v1) / v1;
+    let m2 = harmonic(v1, v2) / v1;
+
+    let h10 = s * s * s - 2.0 * s * s + s;
+    let h01 = -2.0 * s * s * s + 3.0 * s * s;
+    let h11 = s * s * s - s * s;
+    h10 * m1 + h01 + h11 * m2
+}
+
+/// centripetal knot interval for a distance between control points
+fn centripetal_knot(distance: f64) -> f64 {
+    distance.sqrt().max(1e-4)
+}
+
+/// Catmull-Rom weights (Barry-Goldman pyramid) with knot intervals `k0`..`k2`
+fn catmull_rom_weights(u: f64, k0: f64, k1: f64, k2: f64) -> [f64; 4] {
+    let t0 = 0.0;
+    let t1 = t0 + k0;
+    let t2 = t1 + k1;
+    let t3 = t2 + k2;
+    let t = t1 + u * (t2 - t1);
+
+    let blend = |a: [f64; 4], b: [f64; 4], ta: f64, tb: f64| -> [f64; 4] {
+        let wa = (tb - t) / (tb - ta);
+        let wb = (t - ta) / (tb - ta);
+        [
+            a[0] * wa + b[0] * wb,
+            a[1] * wa + b[1] * wb,
+            a[2] * wa + b[2] * wb,
+            a[3] * wa + b[3] * wb,
+        ]
+    };
+
+    let p0 = [1.0, 0.0, 0.0, 0.0];
+    let p1 = [0.0, 1.0, 0.0, 0.0];
+    let p2 = [0.0, 0.0, 1.0, 0.0];
+    let p3 = [0.0, 0.0, 0.0, 1.0];
+
+    let a1 = blend(p0, p1, t0, t1);
+    let a2 = blend(p1, p2, t1, t2);
+    let a3 = blend(p2, p3, t2, t3);
+    let b1 = blend(a1, a2, t0, t2);
+    let b2 = blend(a2, a3, t1, t3);
+    blend(b1, b2, t1, t2)
+}
+
+/// Kochanek-Bartels Hermite weights, tangents scaled by the neighbouring segment durations `d0`, `d1`, `d2`
+fn kochanek_bartels_weights(u: f64, tension: f64, continuity: f64, bias: f64, d0: f64, d1: f64, d2: f64) -> [f64; 4] {
+    let (t, c, b) = (tension, continuity, bias);
+
+    // outgoing tangent at p1 = a * (p1 - p0) + b * (p2 - p1)
+    let out_scale = if d0 + d1 > 0.0 { 2.0 * d1 / (d0 + d1) } else { 1.0 };
+    let oa = (1.0 - t) * (1.0 + b) * (1.0 + c) / 2.0 * out_scale;
+    let ob = (1.0 - t) * (1.0 - b) * (1.0 - c) / 2.0 * out_scale;
+
+    // incoming tangent at p2 = a * (p2 - p1) + b * (p3 - p2)
+    let in_scale = if d1 + d2 > 0.0 { 2.0 * d1 / (d1 + d2) } else { 1.0 };
+    let ia = (1.0 - t) * (1.0 + b) * (1.0 - c) / 2.0 * in_scale;
+    let ib = (1.0 - t) * (1.0 - b) * (1.0 + c) / 2.0 * in_scale;
+
+    let h00 = 2.0 * u * u * u - 3.0 * u * u + 1.0;
+    let h10 = u * u * u - 2.0 * u * u + u;
+    let h01 = -2.0 * u * u * u + 3.0 * u * u;
+    let h11 = u * u * u - u * u;
+
+    [
+        -h10 * oa,
+        h00 + h10 * (oa - ob) - h11 * ia,
+        h01 + h10 * ob + h11 * (ia - ib),
+        h11 * ib,
+    ]
+}
+
+/// uniform cubic B-spline basis
+fn b_spline_weights(u: f64) -> [f64; 4] {
+    let r = 1.0 - u;
+    [
+        r * r * r / 6.0,
+        (3.0 * u * u * u - 6.0 * u * u + 4.0) / 6.0,
+        (-3.0 * u * u * u + 3.0 * u * u + 3.0 * u + 1.0) / 6.0,
+        u * u * u / 6.0,
+    ]
+}
+
+/// time offset and value offset of a handle. the value is kept as weighted keyframe values,
+/// so auto handles of integer tracks are not rounded before evaluation
+type HandleTerms<T> = (f64, [(T, f64); 2]);
+
+fn get_handle<T: Copy>(keyframes: &[Keyframe<T>], index: usize, out: bool) -> HandleTerms<T> {
+    let keyframe = &keyframes[index];
+    if let Some(handles) = keyframe.handles.filter(|h| h.handle_type != HandleType::Auto) {
+        let handle = if out { handles.out_handle } else { handles.in_handle };
+        return (handle.time, [(handle.value, 1.0), (handle.value, 0.0)]);
+    }
+
+    // parallel to the line between the neighbours, a third of the adjacent segment long
+    let prev = &keyframes[index.saturating_sub(1)];
+    let next = &keyframes[(index + 1).min(keyframes.len() - 1)];
+    let span = (next.time - prev.time).as_secs_f64();
+    let time = if out {
+        (next.time - keyframe.time).as_secs_f64() / 3.0
+    } else {
+        -(keyframe.time - prev.time).as_secs_f64() / 3.0
+    };
+    if span == 0.0 {
+        return (time, [(keyframe.value, 0.0), (keyframe.value, 0.0)]);
+    }
+    let slope = time / span;
+    (time, [(next.value, slope), (prev.value, -slope)])
+}
+
+/// evaluates the Bezier segment from keyframe `index` to `index + 1`
+fn get_bezier_value<U, T>(track: &U, index: usize, dt: f64, duration: f64) -> T
+where
+    U: TimelineTrackImpl<T> + ?Sized,
+    T: Copy
+{
+    let keyframes = track.get_keyframes();
+    let (out_time, out_terms) = get_handle(keyframes, index, true);
+    let (in_time, in_terms) = get_handle(keyframes, index + 1, false);
+
+    // keep the handles inside the segment, so time is monotonic
+    let x1 = out_time.clamp(0.0, duration);
+    let x2 = duration + in_time.clamp(-duration, 0.0);
+    let s = solve_bezier_parameter(dt, x1, x2, duration);
+
+    let r = 1.0 - s;
+    let b0 = r * r * r;
+    let b1 = 3.0 * r * r * s;
+    let b2 = 3.0 * r * s * s;
+    let b3 = s * s * s;
+
+    track.get_linear_combination_wrap(&[
+        (keyframes[index].value, b0 + b1),
+        (keyframes[index + 1].value, b2 + b3),
+        (out_terms[0].0, out_terms[0].1 * b1),
+        (out_terms[1].0, out_terms[1].1 * b1),
+        (in_terms[0].0, in_terms[0].1 * b2),
+        (in_terms[1].0, in_terms[1].1 * b2),
+    ])
+}
+
+/// finds the curve parameter whose time is `x`, for a Bezier with time control points 0, x1, x2, d
+fn solve_bezier_parameter(x: f64, x1: f64, x2: f64, d: f64) -> f64 {
+    let bezier = |s: f64| {
+        let r = 1.0 - s;
+        3.0 * r * r * s * x1 + 3.0 * r * s * s * x2 + s * s * s * d
+    };
+
+    let mut low = 0.0;
+    let mut high = 1.0;
+    for _ in 0..52 {
+        let mid = (low + high) * 0.5;
+        if bezier(mid) < x {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) * 0.5
+}
+
+fn set_handle<U, T>(track: &mut U, index: usize, handle: BezierHandle<T>, out: bool)
+where
+    U: TimelineTrackImpl<T> + ?Sized,
+    T: Copy
+{
+    let Some(keyframe) = track.get_keyframes().get(index) else {
+        return;
+    };
+    let mut handles = match keyframe.handles {
+        Some(handles) if handles.handle_type != HandleType::Auto => handles,
+        _ => {
+            // start from a mirrored handle
+            let mirrored = BezierHandle::new(-handle.time, track.get_linear_combination_wrap(&[(handle.value, -1.0)]));
+            BezierHandles { in_handle: mirrored, out_handle: mirrored, handle_type: HandleType::Aligned }
+        }
+    };
+    let (handle_mut, other) = if out {
+        (&mut handles.out_handle, &mut handles.in_handle)
+    } else {
+        (&mut handles.in_handle, &mut handles.out_handle)
+    };
+    *handle_mut = handle;
+
+    if handles.handle_type == HandleType::Aligned && handle.time != 0.0 {
+        if other.time == 0.0 {
+            other.time = -handle.time;
+        }
+        other.value = track.get_linear_combination_wrap(&[(handle.value, other.time / handle.time)]);
+    }
+
+    track.get_keyframes_mut()[index].handles = Some(handles);
+}
+
+/// evaluates the track outside of its keyed range
+fn extrapolate<U, T>(track: &U, extrapolation: Extrapolation, time: Duration) -> Result<T, TimelineError>
+where
+    U: TimelineTrackImpl<T> + ?Sized,
+    T: Copy
+{
+    let keyframes = track.get_keyframes();
+    let n = keyframes.len();
+    let first = &keyframes[0];
+    let last = &keyframes[n - 1];
+    let before = time < first.time;
+    let constant = if before { first.value } else { last.value };
+
+    let span = last.time - first.time;
+    if span.is_zero() {
+        return Ok(constant);
+    }
+
+    match extrapolation {
+        Extrapolation::Constant => Ok(constant),
+        Extrapolation::Linear => {
+            let (a, b) = if before {
+                (&keyframes[0], &keyframes[1])
+            } else {
+                (&keyframes[n - 2], &keyframes[n - 1])
+            };
+            let duration = (b.time - a.time).as_secs_f64();
+            if duration == 0.0 {
+                return Ok(constant);
+            }
+            let dt = time.as_secs_f64() - a.time.as_secs_f64();
+            Ok(track.get_easing_value_wrap(dt, a.value, b.value, duration, EasingFunction::Linear, EasingType::In))
+        }
+        Extrapolation::Cycle | Extrapolation::CycleWithOffset | Extrapolation::PingPong => {
+            let span = span.as_nanos() as i128;
+            let offset = time.as_nanos() as i128 - first.time.as_nanos() as i128;
+            let cycles = offset.div_euclid(span);
+            let mut local = offset.rem_euclid(span);
+            if extrapolation == Extrapolation::PingPong && cycles % 2 != 0 {
+                local = span - local;
+            }
+
+            let local_time = first.time + Duration::from_nanos(local as u64);
+            let value = get_value_at_index(track, keyframe_index(keyframes, local_time), local_time)?;
+            if extrapolation == Extrapolation::CycleWithOffset {
+                Ok(track.get_offset_value_wrap(value, first.value, last.value, cycles as i64))
+            } else {
+                Ok(value)
+            }
+        }
+    }
+}
+
+impl<T> TimelineTrackImpl<T> for Track<T>
+where T: Interpolate + DeserializeOwned
+{
+    fn get_easing_value_wrap(
+        &self,
+        time: f64, start_value: T, next_value: T,
+        duration: f64, easing_function: EasingFunction, easing_type: EasingType) -> T
+    {
+        T::get_rounded_easing_value(time, start_value, next_value, duration, easing_function, easing_type, self.rounding)
+    }
+
+    fn get_offset_value_wrap(&self, value: T, from_value: T, to_value: T, count: i64) -> T {
+        T::get_offset_value(value, from_value, to_value, count)
+    }
+
+    fn get_linear_combination_wrap(&self, terms: &[(T, f64)]) -> T {
+        T::get_rounded_linear_combination(terms, self.rounding)
+    }
+
+    fn get_distance_wrap(&self, a: T, b: T) -> f32 {
+        T::get_distance(a, b)
+    }
+
+    fn get_keyframes(&self) -> &Vec<Keyframe<T>> {
+        &self.keyframes
+    }
+
+    fn get_keyframes_mut(&mut self) -> &mut Vec<Keyframe<T>> {
+        &mut self.keyframes
+    }
+
+    fn get_pre_extrapolation(&self) -> Extrapolation {
+        self.pre_extrapolation
+    }
+
+    fn get_post_extrapolation(&self) -> Extrapolation {
+        self.post_extrapolation
+    }
+
+    fn get_interpolation(&self) -> TrackInterpolation {
+        self.interpolation
+    }
+
+    fn get_rounding(&self) -> Rounding {
+        self.rounding
+    }
+
+    fn get_duplicate_policy(&self) -> DuplicateTimePolicy {
+        self.duplicate_policy
+    }
+}
+
+/// What `TimelineTrack::add_keyframe` (and `move_keyframe`) does with a keyframe at the time of existing ones
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateTimePolicy {
+    /// insert after the existing keyframes, for a jump at that time
+    #[default]
+    KeepBoth,
+    /// remove the existing keyframes
+    Replace,
+    /// `TimelineError::DuplicateKeyframeTime`
+    Error,
+}
+
+/// How an integer track converts interpolated values back to integers
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    /// toward zero, as `as i32` does
+    #[default]
+    Truncate,
+    /// to the nearest integer, halfway cases away from zero
+    Nearest,
+    Floor,
+    Ceil,
+    /// no in-between values, hold each keyframe value until the next keyframe
+    Step,
+}
+
+impl Rounding {
+    /// `Step` rounds like `Floor`, tracks handle it before any value is interpolated
+    pub fn round(self, value: f64) -> f64 {
+        match self {
+            Rounding::Truncate => value.trunc(),
+            Rounding::Nearest => value.round(),
+            Rounding::Floor | Rounding::Step => value.floor(),
+            Rounding::Ceil => value.ceil(),
+        }
+    }
+}
+
+/// How the segment from a keyframe to the next one is interpolated
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    /// `easing_function` / `easing_type`, as in ofxTimeline
+    #[default]
+    Eased,
+    /// straight line to the next keyframe, ignores `easing_function` / `easing_type`
+    Linear,
+    /// hold this keyframe value until the next keyframe
+    #[serde(alias = "step", alias = "hold")]
+    Constant,
+    /// jump to the next keyframe value right after this keyframe
+    StepEnd,
+    /// cubic Bezier through the out handle of this keyframe and the in handle of the next one
+    Bezier,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleType {
+    /// handles are computed from the neighbouring keyframes, stored handles are ignored
+    #[default]
+    Auto,
+    /// in and out handles keep the same slope, moving one moves the other
+    Aligned,
+    /// in and out handles are independent
+    Broken,
+}
+
+/// Bezier handle, relative to its keyframe
+#[derive(Default, Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BezierHandle<T> {
+    /// time offset in seconds (negative for in handles)
+    pub time: f64,
+    /// value offset
+    pub value: T,
+}
+
+impl<T> BezierHandle<T> {
+    pub fn new(time: f64, value: T) -> BezierHandle<T> {
+        BezierHandle { time, value }
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BezierHandles<T> {
+    pub in_handle: BezierHandle<T>,
+    pub out_handle: BezierHandle<T>,
+    pub handle_type: HandleType,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Keyframe<T> {
+    pub time: Duration,
+    pub value: T,
+    pub easing_function: EasingFunction,
     pub easing_type: EasingType,
+    pub interpolation: Interpolation,
+    /// used when `interpolation` is `Interpolation::Bezier`. `None` for auto handles
+    pub handles: Option<BezierHandles<T>>,
 }
 
 impl<T> Keyframe<T> {
     pub fn new(time: Duration, value: T) -> Keyframe<T> {
         Keyframe {
-            time: time,
-            value: value,
+            time,
+            value,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            interpolation: Interpolation::Eased,
+            handles: None,
         }
     }
+
+    /// `HandleType::Auto` without stored handles
+    pub fn get_handle_type(&self) -> HandleType {
+        self.handles.as_ref().map_or(HandleType::Auto, |h| h.handle_type)
+    }
 }
 
 #[cfg(test)]
@@ -606,6 +1907,10 @@ mod tests {
     use assert_float_eq::afe_is_relative_eq;
     use assert_float_eq::afe_relative_error_msg;
     use assert_float_eq::afe_abs;
+    use assert_float_eq::assert_float_absolute_eq;
+    use assert_float_eq::afe_is_absolute_eq;
+    use assert_float_eq::afe_absolute_error_msg;
+    use crate::test_util::ms;
 
     use super::*;
 
@@ -620,7 +1925,7 @@ mod tests {
         let mut tl = Timeline::new();
         tl.add("test", Track::<f32>::default());
         assert_eq!(tl.tracks.len(), 1);
-        assert_eq!(tl.tracks.contains_key("test"), true);
+        assert!(tl.tracks.contains_key("test"));
     }
 
     #[test]
@@ -631,6 +1936,7 @@ mod tests {
             value: 0.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         assert_eq!(t.keyframes.len(), 1);
         assert_eq!(t.keyframes[0].time, Duration::from_secs(1));
@@ -645,12 +1951,14 @@ mod tests {
             value: 0.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         t.add_keyframe(Keyframe {
             time: Duration::from_secs(2),
             value: 1.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         assert_eq!(t.keyframes.len(), 2);
         assert_eq!(t.keyframes[0].time, Duration::from_secs(1));
@@ -667,12 +1975,14 @@ mod tests {
             value: 1.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         t.add_keyframe(Keyframe {
             time: Duration::from_secs(1),
             value: 0.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         assert_eq!(t.keyframes.len(), 2);
         assert_eq!(t.keyframes[0].time, Duration::from_secs(1));
@@ -689,12 +1999,14 @@ mod tests {
             value: 0.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         t.add_keyframe(Keyframe {
             time: Duration::from_secs(2),
             value: 1.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         assert_eq!(t.get_value(Duration::from_secs(1)), 0.0);
         assert_eq!(t.get_value(Duration::from_secs(2)), 1.0);
@@ -710,12 +2022,14 @@ mod tests {
             value: 0.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         t.add_keyframe(Keyframe {
             time: Duration::from_secs(2),
             value: 1.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         tl.add("test", t);
         let v: f32 = tl.get_value("test", Duration::from_secs(1)).into();
@@ -734,12 +2048,14 @@ mod tests {
             value: 0.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         t.add_keyframe(Keyframe {
             time: Duration::from_secs(2),
             value: 1.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         assert_eq!(t.get_duration(), Duration::from_secs(2));
     }
@@ -752,12 +2068,14 @@ mod tests {
             value: 0.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         t.add_keyframe(Keyframe {
             time: Duration::from_secs(2),
             value: 1.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         assert_eq!(t.get_value(Duration::from_secs(3)), 1.0);
         assert_eq!(t.get_value(Duration::from_secs(4)), 1.0);
@@ -771,12 +2089,14 @@ mod tests {
             value: 0.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         t.add_keyframe(Keyframe {
             time: Duration::from_secs(2),
             value: 1.0,
             easing_function: EasingFunction::Linear,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         assert_eq!(t.get_value(Duration::from_secs(0)), 0.0);
     }
@@ -789,15 +2109,596 @@ mod tests {
             value: 0.0,
             easing_function: EasingFunction::Sine,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         t.add_keyframe(Keyframe {
             time: Duration::from_secs(2),
             value: 1.0,
             easing_function: EasingFunction::Sine,
             easing_type: EasingType::In,
+            ..Default::default()
         });
         assert_eq!(t.get_value(Duration::from_secs(1)), 0.0);
         assert_eq!(t.get_value(Duration::from_secs(2)), 1.0);
         assert_float_relative_eq!(t.get_value(Duration::from_secs(1) + Duration::from_millis(500)), 0.29289323, 0.0001);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn try_get_value_empty_track_test() {
+        let t = Track::<f32>::default();
+        assert_eq!(t.try_get_value(Duration::from_secs(0)), Err(TimelineError::EmptyTrack));
+    }
+
+    #[test]
+    fn try_get_value_from_timeline_test() {
+        let mut tl = Timeline::new();
+        let mut t = Track::<f32>::default();
+        t.add_keyframe(Keyframe::new(Duration::from_secs(1), 0.5));
+        tl.add("test", t);
+        tl.add("empty", Track::<i32>::default());
+
+        assert_eq!(tl.try_get_value("test", Duration::from_secs(1)), Ok(TrackValue::Float(0.5)));
+        assert_eq!(
+            tl.try_get_value("missing", Duration::from_secs(1)),
+            Err(TimelineError::TrackNotFound("missing".to_string()))
+        );
+        assert_eq!(tl.try_get_value("empty", Duration::from_secs(1)), Err(TimelineError::EmptyTrack));
+    }
+
+    #[test]
+    fn try_as_track_test() {
+        let mut tl = Timeline::new();
+        tl.add("test", Track::<f32>::default());
+
+        assert!(tl.try_get("test").unwrap().try_as_float_track().is_ok());
+        assert_eq!(
+            tl.try_get("test").unwrap().try_as_int_track().unwrap_err(),
+            TimelineError::TypeMismatch { expected: "int", found: "float" }
+        );
+        assert!(tl.try_get_mut("test").unwrap().try_as_vec2_track_mut().is_err());
+    }
+
+    #[test]
+    fn track_value_try_as_test() {
+        let v = TrackValue::Vec2((1.0, 2.0));
+        assert_eq!(v.try_as_vec2(), Ok((1.0, 2.0)));
+        assert_eq!(v.try_as_float(), Err(TimelineError::TypeMismatch { expected: "float", found: "vec2" }));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid conversion")]
+    fn track_value_into_mismatch_panics_test() {
+        let _: f32 = TrackValue::Int(1).into();
+    }
+
+    #[test]
+    fn get_value_duplicate_time_test() {
+        let mut t = Track::<f32>::default();
+        t.add_keyframe(Keyframe::new(Duration::from_secs(1), 0.0));
+        t.add_keyframe(Keyframe::new(Duration::from_secs(2), 1.0));
+        t.add_keyframe(Keyframe::new(Duration::from_secs(2), 2.0));
+        t.add_keyframe(Keyframe::new(Duration::from_secs(3), 3.0));
+        assert_eq!(t.get_value(Duration::from_millis(1500)), 0.5);
+        assert_eq!(t.get_value(Duration::from_secs(2)), 1.0);
+        assert_eq!(t.get_value(Duration::from_millis(2500)), 2.5);
+    }
+    fn create_extrapolation_track(pre: Extrapolation, post: Extrapolation) -> Track<f32> {
+        let mut t = Track::<f32> {
+            pre_extrapolation: pre,
+            post_extrapolation: post,
+            ..Default::default()
+        };
+        t.add_keyframe(Keyframe::new(Duration::from_secs(1), 0.0));
+        t.add_keyframe(Keyframe::new(Duration::from_secs(2), 1.0));
+        t.add_keyframe(Keyframe::new(Duration::from_secs(3), 3.0));
+        t
+    }
+
+    #[test]
+    fn extrapolation_constant_test() {
+        let t = create_extrapolation_track(Extrapolation::Constant, Extrapolation::Constant);
+        assert_eq!(t.get_value(Duration::from_millis(500)), 0.0);
+        assert_eq!(t.get_value(Duration::from_secs(5)), 3.0);
+    }
+
+    #[test]
+    fn extrapolation_linear_test() {
+        let t = create_extrapolation_track(Extrapolation::Linear, Extrapolation::Linear);
+        assert_eq!(t.get_value(Duration::from_millis(500)), -0.5);
+        assert_eq!(t.get_value(Duration::from_secs(0)), -1.0);
+        assert_eq!(t.get_value(Duration::from_secs(4)), 5.0);
+    }
+
+    #[test]
+    fn extrapolation_cycle_test() {
+        let t = create_extrapolation_track(Extrapolation::Cycle, Extrapolation::Cycle);
+        assert_eq!(t.get_value(Duration::from_millis(3500)), 0.5);
+        assert_eq!(t.get_value(Duration::from_millis(4500)), 2.0);
+        assert_eq!(t.get_value(Duration::from_millis(7500)), 0.5);
+        assert_eq!(t.get_value(Duration::from_millis(500)), 2.0);
+    }
+
+    #[test]
+    fn extrapolation_cycle_with_offset_test() {
+        let t = create_extrapolation_track(Extrapolation::CycleWithOffset, Extrapolation::CycleWithOffset);
+        assert_eq!(t.get_value(Duration::from_millis(3500)), 3.5);
+        assert_eq!(t.get_value(Duration::from_secs(5)), 6.0);
+        assert_eq!(t.get_value(Duration::from_millis(500)), -1.0);
+    }
+
+    #[test]
+    fn extrapolation_ping_pong_test() {
+        let t = create_extrapolation_track(Extrapolation::PingPong, Extrapolation::PingPong);
+        assert_eq!(t.get_value(Duration::from_millis(3500)), 2.0);
+        assert_eq!(t.get_value(Duration::from_secs(5)), 0.0);
+        assert_eq!(t.get_value(Duration::from_millis(5500)), 0.5);
+        assert_eq!(t.get_value(Duration::from_millis(500)), 0.5);
+    }
+
+    #[test]
+    fn get_value_double_precision_test() {
+        // values above 2^24 are not exact in f32
+        let mut t = Track::<i64>::default();
+        t.add_keyframe(Keyframe::new(Duration::from_secs(0), 100_000_000));
+        t.add_keyframe(Keyframe::new(Duration::from_secs(100), 100_000_100));
+        assert_eq!(t.get_value(Duration::from_secs(1)), 100_000_001);
+        assert_eq!(t.get_value(Duration::from_secs(99)), 100_000_099);
+
+        let mut t = Track::<f64>::default();
+        t.add_keyframe(Keyframe::new(Duration::from_secs(0), 1.0e9));
+        t.add_keyframe(Keyframe::new(Duration::from_secs(10), 1.0e9 + 1.0));
+        assert_float_relative_eq!(t.get_value(Duration::from_millis(2500)), 1.0e9 + 0.25, 1e-15);
+    }
+
+    #[test]
+    fn track_serde_defaults_test() {
+        let track: Track<f32> = serde_json::from_str(r#"{"keyframes": []}"#).unwrap();
+        assert_eq!(track, Track::<f32>::default());
+        let track: Track<i32> = serde_json::from_str(r#"{"keyframes": [], "rounding": "floor"}"#).unwrap();
+        assert_eq!(track.rounding, Rounding::Floor);
+        assert_eq!(track.post_extrapolation, Extrapolation::Constant);
+    }
+
+    #[test]
+    fn bool_spline_test() {
+        let mut t = Track::<bool>::default();
+        t.add_keyframe(Keyframe::new(Duration::from_millis(0), true))
+            .add_keyframe(Keyframe::new(Duration::from_millis(1000), false))
+            .add_keyframe(Keyframe::new(Duration::from_millis(2000), false));
+        for interpolation in [
+            TrackInterpolation::CatmullRom,
+            TrackInterpolation::KochanekBartels { tension: 0.0, continuity: 0.0, bias: 0.0 },
+            TrackInterpolation::BSpline,
+        ] {
+            t.interpolation = interpolation;
+            assert!(t.get_value(Duration::from_millis(250)), "{:?}", interpolation);
+            assert!(!t.get_value(Duration::from_millis(1500)), "{:?}", interpolation);
+            assert!(!t.get_value(Duration::from_millis(2000)), "{:?}", interpolation);
+        }
+    }
+
+    #[test]
+    fn spline_double_precision_test() {
+        // collinear keyframes, so every curve is the straight line through them
+        let mut t = Track::<f64>::default();
+        for i in 0..3 {
+            t.add_keyframe(Keyframe {
+                interpolation: Interpolation::Bezier,
+                ..Keyframe::new(Duration::from_secs(i * 100_000), 1.0e9 + i as f64 * 100_000.0)
+            });
+        }
+        let time = Duration::from_millis(123_456_789);
+        for interpolation in [TrackInterpolation::PerKeyframe, TrackInterpolation::CatmullRom] {
+            t.interpolation = interpolation;
+            assert_float_absolute_eq!(t.get_value(time), 1.0e9 + 123_456.789, 1e-4);
+        }
+    }
+
+    fn create_rounding_track(rounding: Rounding, to: i32) -> Track<i32> {
+        let mut t = Track::<i32> { rounding, ..Default::default() };
+        t.add_keyframe(Keyframe::new(Duration::from_secs(0), 0));
+        t.add_keyframe(Keyframe::new(Duration::from_secs(1), to));
+        t.add_keyframe(Keyframe::new(Duration::from_secs(2), 0));
+        t
+    }
+
+    #[test]
+    fn rounding_test() {
+        let at = |rounding, to, ms| create_rounding_track(rounding, to).get_value(Duration::from_millis(ms));
+        assert_eq!(at(Rounding::Truncate, 10, 960), 9);
+        assert_eq!(at(Rounding::Truncate, -10, 960), -9);
+        assert_eq!(at(Rounding::Nearest, 10, 960), 10);
+        assert_eq!(at(Rounding::Nearest, -10, 960), -10);
+        assert_eq!(at(Rounding::Nearest, 10, 940), 9);
+        assert_eq!(at(Rounding::Floor, -10, 940), -10);
+        assert_eq!(at(Rounding::Floor, 10, 990), 9);
+        assert_eq!(at(Rounding::Ceil, 10, 910), 10);
+        assert_eq!(at(Rounding::Step, 10, 990), 0);
+        assert_eq!(at(Rounding::Step, 10, 1000), 10);
+        assert_eq!(at(Rounding::Step, 10, 1990), 10);
+    }
+
+    #[test]
+    fn rounding_spline_test() {
+        let mut t = create_rounding_track(Rounding::Nearest, 10);
+        t.interpolation = TrackInterpolation::CatmullRom;
+        let values: Vec<i32> = (0..=20).map(|i| t.get_value(Duration::from_millis(i * 100))).collect();
+        assert!(values.contains(&10));
+
+        t.rounding = Rounding::Step;
+        assert_eq!(t.get_value(Duration::from_millis(500)), 0);
+
+        let mut t = Track::<i64> { rounding: Rounding::Ceil, ..Default::default() };
+        t.add_keyframe(Keyframe::new(Duration::from_secs(0), 0));
+        t.add_keyframe(Keyframe::new(Duration::from_secs(3), 1));
+        assert_eq!(t.get_value(Duration::from_millis(1)), 1);
+    }
+
+    #[test]
+    fn extrapolation_int_cycle_with_offset_test() {
+        let mut t = Track::<i32> {
+            post_extrapolation: Extrapolation::CycleWithOffset,
+            ..Default::default()
+        };
+        t.add_keyframe(Keyframe::new(Duration::from_secs(0), 0));
+        t.add_keyframe(Keyframe::new(Duration::from_secs(1), 10));
+        assert_eq!(t.get_value(Duration::from_secs(3)), 30);
+        assert_eq!(t.get_value(Duration::from_secs(1 << 30)), i32::MAX);
+
+        t.keyframes[1].value = -10;
+        assert_eq!(t.get_value(Duration::from_secs(1 << 30)), i32::MIN);
+    }
+
+    fn create_interpolation_track(interpolation: Interpolation) -> Track<f32> {
+        let mut t = Track::<f32>::default();
+        for (time, value) in [(0, 0.0), (1, 1.0), (2, 3.0)] {
+            let mut keyframe = Keyframe::new(Duration::from_secs(time), value);
+            keyframe.easing_function = EasingFunction::Cubic;
+            keyframe.interpolation = interpolation;
+            t.add_keyframe(keyframe);
+        }
+        t
+    }
+
+    #[test]
+    fn interpolation_constant_test() {
+        let t = create_interpolation_track(Interpolation::Constant);
+        assert_eq!(t.get_value(Duration::from_millis(999)), 0.0);
+        assert_eq!(t.get_value(Duration::from_secs(1)), 1.0);
+        assert_eq!(t.get_value(Duration::from_millis(1500)), 1.0);
+    }
+
+    #[test]
+    fn interpolation_step_end_test() {
+        let t = create_interpolation_track(Interpolation::StepEnd);
+        assert_eq!(t.get_value(Duration::from_secs(0)), 0.0);
+        assert_eq!(t.get_value(Duration::from_millis(1)), 1.0);
+        assert_eq!(t.get_value(Duration::from_millis(1500)), 3.0);
+    }
+
+    #[test]
+    fn interpolation_linear_test() {
+        // easing_function is ignored
+        let t = create_interpolation_track(Interpolation::Linear);
+        assert_float_relative_eq!(t.get_value(Duration::from_millis(250)), 0.25, 0.0001);
+        assert_float_relative_eq!(t.get_value(Duration::from_millis(1500)), 2.0, 0.0001);
+
+        let mut t = t;
+        t.keyframes[0].interpolation = Interpolation::Eased;
+        assert!(t.get_value(Duration::from_millis(250)) < 0.25);
+    }
+
+    #[test]
+    fn interpolation_int_constant_test() {
+        let mut t = Track::<i32>::default();
+        t.add_keyframe(Keyframe { interpolation: Interpolation::Constant, ..Keyframe::new(Duration::from_secs(0), 0) });
+        t.add_keyframe(Keyframe::new(Duration::from_secs(1), 10));
+        assert_eq!(t.get_value(Duration::from_millis(990)), 0);
+    }
+
+    fn create_bezier_track(handle_type: HandleType) -> Track<f32> {
+        let mut t = Track::<f32>::default();
+        for (time, value) in [(0, 0.0), (1, 1.0)] {
+            let mut keyframe = Keyframe::new(Duration::from_secs(time), value);
+            keyframe.interpolation = Interpolation::Bezier;
+            if handle_type != HandleType::Auto {
+                keyframe.handles = Some(BezierHandles { handle_type, ..Default::default() });
+            }
+            t.add_keyframe(keyframe);
+        }
+        t
+    }
+
+    #[test]
+    fn bezier_auto_handles_test() {
+        // auto handles of two keyframes lie on the line between them
+        let t = create_bezier_track(HandleType::Auto);
+        assert_float_relative_eq!(t.get_value(Duration::from_millis(250)), 0.25, 0.0001);
+        assert_float_relative_eq!(t.get_value(Duration::from_millis(500)), 0.5, 0.0001);
+        assert_eq!(t.get_value(Duration::from_secs(1)), 1.0);
+    }
+
+    #[test]
+    fn bezier_flat_handles_test() {
+        let mut t = create_bezier_track(HandleType::Broken);
+        t.set_out_handle(0, BezierHandle::new(0.5, 0.0));
+        t.set_in_handle(1, BezierHandle::new(-0.5, 0.0));
+        assert_float_relative_eq!(t.get_value(Duration::from_millis(500)), 0.5, 0.0001);
+        assert!(t.get_value(Duration::from_millis(250)) < 0.25);
+        assert!(t.get_value(Duration::from_millis(750)) > 0.75);
+    }
+
+    #[test]
+    fn bezier_overshoot_handles_test() {
+        let mut t = create_bezier_track(HandleType::Broken);
+        t.set_out_handle(0, BezierHandle::new(0.3, 2.0));
+        t.set_in_handle(1, BezierHandle::new(-0.3, 0.0));
+        let max = (0..100).map(|ms| t.get_value(Duration::from_millis(ms * 10))).fold(0.0, f32::max);
+        assert!(max > 1.0);
+    }
+
+    #[test]
+    fn bezier_aligned_handles_test() {
+        let mut t = create_bezier_track(HandleType::Auto);
+        t.set_out_handle(1, BezierHandle::new(0.5, 1.0));
+        assert_eq!(t.keyframes[0].get_handle_type(), HandleType::Auto);
+        let handles = t.keyframes[1].handles.unwrap();
+        assert_eq!(handles.handle_type, HandleType::Aligned);
+        assert_eq!(handles.in_handle, BezierHandle::new(-0.5, -1.0));
+
+        t.set_in_handle(1, BezierHandle::new(-0.25, -1.0));
+        assert_eq!(t.keyframes[1].handles.unwrap().out_handle, BezierHandle::new(0.5, 2.0));
+    }
+
+    #[test]
+    fn bezier_vec2_test() {
+        let mut t = Track::<(f32, f32)>::default();
+        for (time, value) in [(0, (0.0, 10.0)), (1, (1.0, 0.0)), (2, (3.0, 0.0))] {
+            let mut keyframe = Keyframe::new(Duration::from_secs(time), value);
+            keyframe.interpolation = Interpolation::Bezier;
+            t.add_keyframe(keyframe);
+        }
+        assert_eq!(t.get_value(Duration::from_secs(1)), (1.0, 0.0));
+        let (x, y) = t.get_value(Duration::from_millis(1500));
+        assert!(x > 1.0 && x < 3.0);
+        // the auto tangent at the middle keyframe still points downward
+        assert!(y < 0.0);
+    }
+    fn create_spline_track(interpolation: TrackInterpolation) -> Track<f32> {
+        let mut t = Track::<f32> {
+            interpolation,
+            ..Default::default()
+        };
+        for (ms, value) in [(0, 0.0), (1000, 1.0), (1500, 0.5), (3000, 2.0)] {
+            t.add_keyframe(Keyframe::new(Duration::from_millis(ms), value));
+        }
+        t
+    }
+
+    /// left and right slope at `ms`
+    fn slopes(t: &Track<f32>, ms: u64) -> (f32, f32) {
+        let h = Duration::from_micros(100);
+        let at = Duration::from_millis(ms);
+        let v = t.get_value(at);
+        ((v - t.get_value(at - h)) / h.as_secs_f32(), (t.get_value(at + h) - v) / h.as_secs_f32())
+    }
+
+    #[test]
+    fn spline_catmull_rom_test() {
+        let t = create_spline_track(TrackInterpolation::CatmullRom);
+        assert_eq!(t.get_value(Duration::from_millis(1000)), 1.0);
+        assert_eq!(t.get_value(Duration::from_millis(1500)), 0.5);
+        assert!(t.get_value(Duration::from_millis(1)).abs() < 0.01);
+        assert_float_relative_eq!(t.get_value(Duration::from_millis(2999)), 2.0, 0.01);
+        for ms in [1000, 1500] {
+            let (left, right) = slopes(&t, ms);
+            assert!((left - right).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn spline_kochanek_bartels_is_c1_test() {
+        let t = create_spline_track(TrackInterpolation::KochanekBartels { tension: 0.0, continuity: 0.0, bias: 0.0 });
+        assert_eq!(t.get_value(Duration::from_millis(1500)), 0.5);
+        for ms in [1000, 1500] {
+            let (left, right) = slopes(&t, ms);
+            assert_float_relative_eq!(left, right, 0.02);
+        }
+
+        // full tension gives zero tangents
+        let t = create_spline_track(TrackInterpolation::KochanekBartels { tension: 1.0, continuity: 0.0, bias: 0.0 });
+        let (left, right) = slopes(&t, 1000);
+        assert!(left.abs() < 0.01 && right.abs() < 0.01);
+    }
+
+    #[test]
+    fn spline_b_spline_test() {
+        let t = create_spline_track(TrackInterpolation::BSpline);
+        assert_float_relative_eq!(t.get_value(Duration::from_millis(0)), 0.0, 0.0001);
+        assert_eq!(t.get_value(Duration::from_millis(3000)), 2.0);
+        // approximating: inner keyframes are not hit, but the curve is continuous there
+        let v = t.get_value(Duration::from_millis(1000));
+        assert!(v < 1.0);
+        assert_float_relative_eq!(t.get_value(Duration::from_millis(999)), v, 0.01);
+        let (left, right) = slopes(&t, 1000);
+        assert_float_relative_eq!(left, right, 0.02);
+    }
+
+    #[test]
+    fn spline_vec2_catmull_rom_test() {
+        let mut t = Track::<(f32, f32)> {
+            interpolation: TrackInterpolation::CatmullRom,
+            ..Default::default()
+        };
+        for (ms, value) in [(0, (0.0, 0.0)), (1000, (1.0, 1.0)), (2000, (2.0, 0.0)), (3000, (3.0, 1.0))] {
+            t.add_keyframe(Keyframe::new(Duration::from_millis(ms), value));
+        }
+        assert_eq!(t.get_value(Duration::from_millis(2000)), (2.0, 0.0));
+        let (x, y) = t.get_value(Duration::from_millis(1500));
+        assert_float_relative_eq!(x, 1.5, 0.01);
+        assert_float_relative_eq!(y, 0.5, 0.01);
+        // the tangent at (1, 1) is horizontal, so the path bulges above the straight line
+        let (_, y) = t.get_value(Duration::from_millis(1250));
+        assert!(y > 0.75);
+    }
+
+    #[test]
+    fn frame_value_test() {
+        let mut tl = Timeline::new();
+        let mut t = Track::<f32>::default();
+        t.add_keyframe(Keyframe::new(Duration::from_millis(0), 0.0))
+            .add_keyframe(Keyframe::new(Duration::from_millis(1000), 1.0));
+        tl.add("t", t);
+        assert_eq!(tl.try_get_value_at_frame("t", 12), Err(TimelineError::MissingFrameRate));
+
+        tl.set_frame_rate(FrameRate::Fps24);
+        assert_eq!(tl.get_value_at_frame("t", 12), TrackValue::Float(0.5));
+        assert_eq!(tl.try_get_frame_time(36), Ok(Duration::from_millis(1500)));
+        assert_eq!(tl.try_get_frame(Duration::from_millis(520)), Ok(12));
+
+        // 520ms is in frame 12, which starts at 500ms
+        assert_eq!(tl.get_value("t", Duration::from_millis(520)), TrackValue::Float(0.52));
+        tl.set_quantize_to_frames(true);
+        assert_eq!(tl.get_value("t", Duration::from_millis(520)), TrackValue::Float(0.5));
+
+        // animation on twos
+        tl.set_frame_rate(FrameRate::Custom { num: 12, den: 1 });
+        assert_eq!(tl.get_value_at_frame("t", 3), TrackValue::Float(0.25));
+        assert_eq!(tl.get_value("t", Duration::from_millis(520)), TrackValue::Float(0.5));
+    }
+
+    #[test]
+    fn snap_to_frames_test() {
+        let mut tl = Timeline::new();
+        let mut t = Track::<f32>::default();
+        t.add_keyframe(Keyframe::new(Duration::from_millis(0), 0.0))
+            .add_keyframe(Keyframe::new(Duration::from_millis(510), 1.0))
+            .add_keyframe(Keyframe::new(Duration::from_millis(1030), 0.0));
+        tl.add("t", t);
+        assert!(tl.snap_to_frames().is_err());
+
+        tl.set_frame_rate(FrameRate::Fps25).snap_to_frames().unwrap();
+        let times: Vec<Duration> = tl.tracks["t"].as_float_track().keyframes.iter().map(|k| k.time).collect();
+        assert_eq!(times, [0, 520, 1040].map(Duration::from_millis));
+    }
+
+    #[test]
+    fn snap_duplicate_policy_test() {
+        let mut t = Track::<f32>::default();
+        t.add_keyframe(Keyframe::new(ms(0), 0.0))
+            .add_keyframe(Keyframe::new(ms(1000), 1.0))
+            .add_keyframe(Keyframe::new(ms(1000), 2.0))
+            .add_keyframe(Keyframe::new(ms(1010), 3.0));
+        let values = |t: &Track<f32>| t.keyframes.iter().map(|k| (k.time, k.value)).collect::<Vec<_>>();
+        let snap = |time: Duration| FrameRate::Fps25.snap(time);
+
+        let mut keep = t.clone();
+        keep.snap_times(snap);
+        assert_eq!(values(&keep), [(ms(0), 0.0), (ms(1000), 1.0), (ms(1000), 2.0), (ms(1000), 3.0)]);
+
+        // the keyframe snapped to 1000ms replaces the ones there, like `try_add_keyframe`
+        let mut replace = t.clone();
+        replace.set_duplicate_policy(DuplicateTimePolicy::Replace).snap_times(snap);
+        assert_eq!(values(&replace), [(ms(0), 0.0), (ms(1000), 3.0)]);
+
+        let mut error = t.clone();
+        error.set_duplicate_policy(DuplicateTimePolicy::Error);
+        assert_eq!(error.try_snap_times(snap).err(), Some(TimelineError::DuplicateKeyframeTime(ms(1000))));
+        assert_eq!(error.keyframes, t.keyframes);
+        // an existing jump alone is not an error
+        error.keyframes.pop();
+        assert!(error.try_snap_times(snap).is_ok());
+
+        let mut tl = Timeline::new();
+        tl.add("keep", t.clone());
+        tl.add("error", { let mut e = t.clone(); e.set_duplicate_policy(DuplicateTimePolicy::Error); e });
+        assert!(tl.set_frame_rate(FrameRate::Fps25).snap_to_frames().is_err());
+        assert_eq!(tl.tracks["keep"].as_float_track(), &t);
+    }
+
+    #[test]
+    fn beat_value_test() {
+        let mut tl = Timeline::new();
+        let mut t = Track::<f32>::default();
+        t.add_keyframe(Keyframe::new(Duration::from_millis(0), 0.0))
+            .add_keyframe(Keyframe::new(Duration::from_millis(2000), 1.0))
+            .add_keyframe(Keyframe::new(Duration::from_millis(2600), 0.0));
+        tl.add("t", t);
+        assert_eq!(tl.try_get_value_at_beat("t", 1.0), Err(TimelineError::MissingTempoMap));
+
+        tl.set_tempo_map(TempoMap::default());
+        assert_eq!(tl.get_value_at_beat("t", 1.0), TrackValue::Float(0.25));
+        assert!(matches!(tl.try_get_value_at_beat("t", 1e300), Err(TimelineError::InvalidData(_))));
+
+        tl.snap_to_beats(1).unwrap();
+        let times: Vec<Duration> = tl.tracks["t"].as_float_track().keyframes.iter().map(|k| k.time).collect();
+        assert_eq!(times, [0, 2000, 2500].map(Duration::from_millis));
+    }
+
+    fn create_edit_track() -> Track<f32> {
+        let mut t = Track::<f32>::default();
+        for (time, value) in [(0, 0.0), (1000, 1.0), (2000, 2.0), (3000, 3.0)] {
+            t.add_keyframe(Keyframe::new(ms(time), value));
+        }
+        t
+    }
+
+    #[test]
+    fn duplicate_time_policy_test() {
+        let mut t = create_edit_track();
+        assert_eq!(t.try_add_keyframe(Keyframe::new(ms(1000), 5.0)), Ok(2));
+        assert_eq!(t.keyframes.iter().map(|k| k.value).collect::<Vec<_>>(), [0.0, 1.0, 5.0, 2.0, 3.0]);
+
+        t.set_duplicate_policy(DuplicateTimePolicy::Replace);
+        assert_eq!(t.try_add_keyframe(Keyframe::new(ms(1000), 6.0)), Ok(1));
+        assert_eq!(t.keyframes.iter().map(|k| k.value).collect::<Vec<_>>(), [0.0, 6.0, 2.0, 3.0]);
+
+        t.set_duplicate_policy(DuplicateTimePolicy::Error);
+        assert_eq!(t.try_add_keyframe(Keyframe::new(ms(2000), 7.0)), Err(TimelineError::DuplicateKeyframeTime(ms(2000))));
+        assert_eq!(t.try_add_keyframe(Keyframe::new(ms(2500), 7.0)), Ok(3));
+        assert_eq!(t.keyframes.len(), 5);
+    }
+
+    #[test]
+    fn remove_keyframe_test() {
+        let mut t = create_edit_track();
+        assert_eq!(t.remove_keyframe(1).map(|k| k.value), Some(1.0));
+        assert!(t.remove_keyframe(10).is_none());
+        assert_eq!(t.remove_keyframes_at(ms(2000)).len(), 1);
+        assert!(t.remove_keyframes_at(ms(2500)).is_empty());
+
+        let mut t = create_edit_track();
+        assert_eq!(t.keyframes_in_range(ms(500), ms(3000)).len(), 2);
+        assert!(t.keyframes_in_range(ms(3000), ms(500)).is_empty());
+        assert_eq!(t.remove_keyframes_in_range(ms(1000), ms(3000)).len(), 2);
+        assert_eq!(t.keyframes.iter().map(|k| k.value).collect::<Vec<_>>(), [0.0, 3.0]);
+    }
+
+    #[test]
+    fn move_keyframe_test() {
+        let mut t = create_edit_track();
+        assert_eq!(t.move_keyframe(0, ms(2500)), Ok(2));
+        assert_eq!(t.keyframes.iter().map(|k| k.value).collect::<Vec<_>>(), [1.0, 2.0, 0.0, 3.0]);
+        assert_eq!(t.move_keyframe(4, ms(0)), Err(TimelineError::KeyframeIndexOutOfRange(4)));
+
+        t.set_duplicate_policy(DuplicateTimePolicy::Error);
+        let before = t.clone();
+        assert_eq!(t.move_keyframe(0, ms(2000)), Err(TimelineError::DuplicateKeyframeTime(ms(2000))));
+        assert_eq!(t, before);
+
+        t.set_keyframe_value(0, 10.0).unwrap();
+        assert_eq!(t.get_value(ms(1000)), 10.0);
+        assert!(t.set_keyframe_value(4, 0.0).is_err());
+    }
+
+    #[test]
+    fn nearest_keyframe_test() {
+        let t = create_edit_track();
+        assert_eq!(t.get_nearest_keyframe_index(ms(1400), ms(500)), Some(1));
+        assert_eq!(t.get_nearest_keyframe_index(ms(1600), ms(500)), Some(2));
+        assert_eq!(t.get_nearest_keyframe_index(ms(1500), ms(500)), Some(1));
+        assert_eq!(t.get_nearest_keyframe_index(ms(1500), ms(499)), None);
+        assert_eq!(t.get_nearest_keyframe_index(ms(9000), ms(6000)), Some(3));
+        assert_eq!(Track::<f32>::default().get_nearest_keyframe_index(ms(0), ms(1000)), None);
+    }
+}
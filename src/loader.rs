@@ -1,29 +1,45 @@
-use std::collections::HashMap;
 use std::fs::File;
+use std::io::Read;
+use std::path::Path;
 use std::time::Duration;
 
-use anyhow::Result;
 use serde::de::DeserializeOwned;
+use serde::Serialize;
 
-use crate::{xml_to_json, Timeline, TimelineTrack, TimelineTrackImpl, Track, TrackVariant};
+use crate::{
+    xml_to_json, BezierHandle, BezierHandles, Color, ColorSpace, ColorTrack, Extrapolation, HandleType, Interpolation, Quat, Rounding,
+    Timeline, Timecode, TimecodeFormat, TimelineError, Track, TrackInterpolation, TrackVariant, ValueRange,
+};
 use crate::Keyframe;
+use crate::easing::{EasingFunction, EasingType};
 
+/// Loads a track into a `Timeline`, from a file (`load_xml`), a reader (`load_xml_reader`) or a string (`load_xml_str`)
 pub trait TimelineXMLLoader {
-    fn load_xml<T>(&mut self, track_name: &str, xml_path: &str) -> Result<()>
+    fn load_xml<T>(&mut self, track_name: &str, xml_path: impl AsRef<Path>) -> Result<(), TimelineError>
     where
         TrackVariant: From<Track<T>>,
         T: Copy + DeserializeOwned + JsonLoaderWrapper<T>;
-    fn load_xml_str<T>(&mut self, track_name: &str, xml: &str) -> Result<()>
+    fn load_xml_reader<T>(&mut self, track_name: &str, reader: impl Read) -> Result<(), TimelineError>
+    where
+        TrackVariant: From<Track<T>>,
+        T: Copy + DeserializeOwned + JsonLoaderWrapper<T>;
+    fn load_xml_str<T>(&mut self, track_name: &str, xml: &str) -> Result<(), TimelineError>
     where
         TrackVariant: From<Track<T>>,
         T: Copy + DeserializeOwned + JsonLoaderWrapper<T>;
 }
+
+/// same as `TimelineXMLLoader`, for the JSON layout of the XML
 pub trait TimelineJsonLoader {
-    fn load_json<T>(&mut self, track_name: &str, json_path: &str) -> Result<()>
+    fn load_json<T>(&mut self, track_name: &str, json_path: impl AsRef<Path>) -> Result<(), TimelineError>
+    where
+        TrackVariant: From<Track<T>>,
+        T: Copy + DeserializeOwned + JsonLoaderWrapper<T>;
+    fn load_json_reader<T>(&mut self, track_name: &str, reader: impl Read) -> Result<(), TimelineError>
     where
         TrackVariant: From<Track<T>>,
         T: Copy + DeserializeOwned + JsonLoaderWrapper<T>;
-    fn load_json_str<T>(&mut self, track_name: &str, json: &str) -> Result<()>
+    fn load_json_str<T>(&mut self, track_name: &str, json: &str) -> Result<(), TimelineError>
     where
         TrackVariant: From<Track<T>>,
         T: Copy + DeserializeOwned + JsonLoaderWrapper<T>;
@@ -34,8 +50,9 @@ where
     TrackVariant: From<Track<T>>,
     T: Copy + DeserializeOwned + JsonLoaderWrapper<T>
 {
-    fn load_xml(xml: &str) -> Result<Track<T>>;
-    fn load_xml_str(xml: &str) -> Result<Track<T>>;
+    fn load_xml(xml_path: impl AsRef<Path>) -> Result<Track<T>, TimelineError>;
+    fn load_xml_reader(reader: impl Read) -> Result<Track<T>, TimelineError>;
+    fn load_xml_str(xml: &str) -> Result<Track<T>, TimelineError>;
 }
 
 pub trait JsonTrackLoader<T>
@@ -43,22 +60,30 @@ where
     TrackVariant: From<Track<T>>,
     T: Copy + DeserializeOwned + JsonLoaderWrapper<T>
 {
-    fn load_json(json_path: &str) -> Result<Track<T>>;
-    fn load_json_str(json: &str) -> Result<Track<T>>;
+    fn load_json(json_path: impl AsRef<Path>) -> Result<Track<T>, TimelineError>;
+    fn load_json_reader(reader: impl Read) -> Result<Track<T>, TimelineError>;
+    fn load_json_str(json: &str) -> Result<Track<T>, TimelineError>;
 }
 
 impl TimelineXMLLoader for Timeline
 {
-    fn load_xml<T>(&mut self, track_name: &str, xml_path: &str) -> Result<()>
+    fn load_xml<T>(&mut self, track_name: &str, xml_path: impl AsRef<Path>) -> Result<(), TimelineError>
+    where
+        TrackVariant: From<Track<T>>,
+        T: Copy + DeserializeOwned + JsonLoaderWrapper<T>
+    {
+        self.load_xml_reader::<T>(track_name, File::open(xml_path)?)
+    }
+
+    fn load_xml_reader<T>(&mut self, track_name: &str, reader: impl Read) -> Result<(), TimelineError>
     where
         TrackVariant: From<Track<T>>,
         T: Copy + DeserializeOwned + JsonLoaderWrapper<T>
     {
-        let json = xml_to_json::xml_str_to_json(xml_path)?;
-        self.load_json::<T>(track_name, &json.to_string())
+        self.load_xml_str::<T>(track_name, &read_to_string(reader)?)
     }
 
-    fn load_xml_str<T>(&mut self, track_name: &str, xml: &str) -> Result<()>
+    fn load_xml_str<T>(&mut self, track_name: &str, xml: &str) -> Result<(), TimelineError>
     where
         TrackVariant: From<Track<T>>,
         T: Copy + DeserializeOwned + JsonLoaderWrapper<T>
@@ -69,23 +94,29 @@ impl TimelineXMLLoader for Timeline
 }
 
 impl TimelineJsonLoader for Timeline {
-    fn load_json<T>(&mut self, track_name: &str, json_path: &str) -> Result<()>
+    fn load_json<T>(&mut self, track_name: &str, json_path: impl AsRef<Path>) -> Result<(), TimelineError>
     where
         TrackVariant: From<Track<T>>,
         T: Copy + DeserializeOwned + JsonLoaderWrapper<T>
     {
-        let track = <T as JsonLoaderWrapper<T>>::load_json(json_path)?;
-        self.add::<Track<T>>(track_name, track.into());
-        Ok(())
+        self.load_json_reader::<T>(track_name, File::open(json_path)?)
+    }
+
+    fn load_json_reader<T>(&mut self, track_name: &str, reader: impl Read) -> Result<(), TimelineError>
+    where
+        TrackVariant: From<Track<T>>,
+        T: Copy + DeserializeOwned + JsonLoaderWrapper<T>
+    {
+        self.load_json_str::<T>(track_name, &read_to_string(reader)?)
     }
 
-    fn load_json_str<T>(&mut self, track_name: &str, json: &str) -> Result<()>
+    fn load_json_str<T>(&mut self, track_name: &str, json: &str) -> Result<(), TimelineError>
     where
         TrackVariant: From<Track<T>>,
         T: Copy + DeserializeOwned + JsonLoaderWrapper<T>
     {
-        let track = <T as JsonLoaderWrapper<T>>::load_json_str(json)?;
-        self.add::<Track<T>>(track_name, track.into());
+        let track = <T as JsonLoaderWrapper<T>>::load_track_variant(json)?;
+        self.add(track_name, track);
         Ok(())
     }
 }
@@ -95,98 +126,551 @@ where
     TrackVariant: From<Track<T>>,
 T: Copy + DeserializeOwned + JsonLoaderWrapper<T>
 {
-    fn load_xml(xml_path: &str) -> Result<Track<T>>
+    fn load_xml(xml_path: impl AsRef<Path>) -> Result<Track<T>, TimelineError>
     {
-        let json = xml_to_json::xml_str_to_json(xml_path)?;
-        <T as JsonLoaderWrapper<T>>::load_json(&json.to_string())
+        Self::load_xml_reader(File::open(xml_path)?)
     }
 
-    fn load_xml_str(xml: &str) -> Result<Track<T>>
+    fn load_xml_reader(reader: impl Read) -> Result<Track<T>, TimelineError>
+    {
+        Self::load_xml_str(&read_to_string(reader)?)
+    }
+
+    fn load_xml_str(xml: &str) -> Result<Track<T>, TimelineError>
     {
         let json = xml_to_json::xml_str_to_json(xml)?;
         <T as JsonLoaderWrapper<T>>::load_json_str(&json.to_string())
     }
 }
 
-trait JsonLoaderWrapper<T>
+/// Dispatches to the `JsonTrackLoader` implementation of each supported value type
+pub trait JsonLoaderWrapper<T>
 where 
     T: Copy + DeserializeOwned
 {
-    fn load_json(json_path: &str) -> Result<Track<T>>;
-    fn load_json_str(json: &str) -> Result<Track<T>>;
+    fn load_json(json_path: impl AsRef<Path>) -> Result<Track<T>, TimelineError>;
+    fn load_json_reader(reader: impl Read) -> Result<Track<T>, TimelineError>;
+    fn load_json_str(json: &str) -> Result<Track<T>, TimelineError>;
+    /// the track as added to a `Timeline`, a color track keeps its `<color_space>`
+    fn load_track_variant(json: &str) -> Result<TrackVariant, TimelineError>;
 }
         
+/// `$t` is read from the `<value>` as `$entity` (see `ValueEntity`)
 macro_rules! impl_json_track_loader {
-    ($($t:ty),*) => {
+    ($($t:ty => $entity:ty),*) => {
         $(
             impl JsonTrackLoader<$t> for Track<$t>
                 where
                     TrackVariant: From<Track<$t>>,
             {
-                fn load_json(json: &str) -> Result<Track<$t>>
+                fn load_json(json_path: impl AsRef<Path>) -> Result<Track<$t>, TimelineError>
+                {
+                    Self::load_json_reader(File::open(json_path)?)
+                }
+
+                fn load_json_reader(reader: impl Read) -> Result<Track<$t>, TimelineError>
                 {
-                    let mut track = Track::<$t>::default();
-                    let file = File::open(json)?;
-                    let json: KeyframesEntity<$t> = serde_json::from_reader(file)?;
-                    for keyframe in json.keyframes.get("key").unwrap() {
-                        track.add_keyframe(Keyframe {
-                            time: timecode_to_duration(&keyframe.time)?,
-                            value: keyframe.value,
-                            easing_function: keyframe.easefunc.into(),
-                            easing_type: keyframe.easetype.into(),
-                        });
-                    }
-                    Ok(track)
+                    let json: KeyframesEntity<$entity> = serde_json::from_reader(reader)?;
+                    track_from_entity(json)
                 }
 
-                fn load_json_str(json: &str) -> Result<Track<$t>>
+                fn load_json_str(json: &str) -> Result<Track<$t>, TimelineError>
                 {
-                    let mut track = Track::<$t>::default();
-                    let json: KeyframesEntity<$t> = serde_json::from_str(json)?;
-                    for keyframe in json.keyframes.get("key").unwrap() {
-                        track.add_keyframe(Keyframe {
-                            time: timecode_to_duration(&keyframe.time)?,
-                            value: keyframe.value,
-                            easing_function: keyframe.easefunc.into(),
-                            easing_type: keyframe.easetype.into(),
-                        });
-                    }
-                    Ok(track)
+                    let json: KeyframesEntity<$entity> = serde_json::from_str(json)?;
+                    track_from_entity(json)
                 }
             }
 
             impl JsonLoaderWrapper<$t> for $t {
-                fn load_json(json: &str) -> Result<Track<$t>> {
-                    Track::<Self>::load_json(json)
+                fn load_json(json_path: impl AsRef<Path>) -> Result<Track<$t>, TimelineError> {
+                    Track::<Self>::load_json(json_path)
+                }
+
+                fn load_json_reader(reader: impl Read) -> Result<Track<$t>, TimelineError> {
+                    Track::<Self>::load_json_reader(reader)
                 }
 
-                fn load_json_str(json: &str) -> Result<Track<$t>> {
+                fn load_json_str(json: &str) -> Result<Track<$t>, TimelineError> {
                     Track::<Self>::load_json_str(json)
                 }
+
+                fn load_track_variant(json: &str) -> Result<TrackVariant, TimelineError> {
+                    let json: KeyframesEntity<$entity> = serde_json::from_str(json)?;
+                    track_variant_from_entity::<_, $t>(json)
+                }
             }
         )*
     };
 }
 
-impl_json_track_loader!(f32, f64, i32, i64);
+impl_json_track_loader!(
+    f32 => f32, f64 => f64, i32 => i32, i64 => i64, Quat => Quat, Color => Color, bool => BoolEntity,
+    (f32, f32) => VecEntity, (f32, f32, f32) => VecEntity, (f32, f32, f32, f32) => VecEntity
+);
+
+/// What the `<value>` of a key is deserialized as, before being converted to the track value type
+trait ValueEntity<T>: DeserializeOwned {
+    fn into_value(self) -> Result<T, TimelineError>;
+}
+
+impl<T: DeserializeOwned> ValueEntity<T> for T {
+    fn into_value(self) -> Result<T, TimelineError> {
+        Ok(self)
+    }
+}
+
+/// `true` / `false`, a number (not zero is true) or `on` / `off`, `yes` / `no`
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum BoolEntity {
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl ValueEntity<bool> for BoolEntity {
+    fn into_value(self) -> Result<bool, TimelineError> {
+        match self {
+            BoolEntity::Bool(b) => Ok(b),
+            BoolEntity::Number(n) => Ok(n != 0.0),
+            BoolEntity::String(s) => match s.trim().to_ascii_lowercase().as_str() {
+                "true" | "on" | "yes" => Ok(true),
+                "false" | "off" | "no" => Ok(false),
+                _ => Err(TimelineError::InvalidData(format!("invalid bool: {}", s))),
+            },
+        }
+    }
+}
+
+/// vector as `x`, `y`, `z`, `w` fields (child elements in XML) or as an array
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum VecEntity {
+    Array(Vec<f32>),
+    Xyzw { x: f32, y: f32, z: Option<f32>, w: Option<f32> },
+}
+
+impl VecEntity {
+    fn into_components<const N: usize>(self) -> Result<[f32; N], TimelineError> {
+        let components = match self {
+            VecEntity::Array(v) => v,
+            VecEntity::Xyzw { x, y, z, w } => [Some(x), Some(y), z, w].into_iter().map_while(|c| c).collect(),
+        };
+        let len = components.len();
+        components.try_into().map_err(|_| TimelineError::InvalidData(format!("invalid vector: expected {} components, found {}", N, len)))
+    }
+}
+
+impl ValueEntity<(f32, f32)> for VecEntity {
+    fn into_value(self) -> Result<(f32, f32), TimelineError> {
+        let [x, y] = self.into_components()?;
+        Ok((x, y))
+    }
+}
+
+impl ValueEntity<(f32, f32, f32)> for VecEntity {
+    fn into_value(self) -> Result<(f32, f32, f32), TimelineError> {
+        let [x, y, z] = self.into_components()?;
+        Ok((x, y, z))
+    }
+}
+
+impl ValueEntity<(f32, f32, f32, f32)> for VecEntity {
+    fn into_value(self) -> Result<(f32, f32, f32, f32), TimelineError> {
+        let [x, y, z, w] = self.into_components()?;
+        Ok((x, y, z, w))
+    }
+}
+
+/// Value types that can be written back to ofxTimeline XML, see `Track::to_xml_string`
+pub trait XMLKeyWriter: Copy + DeserializeOwned {
+    /// content of a `<value>` element (also used for the Bezier handles)
+    fn to_xml_value(value: Self) -> String;
+
+    /// elements of a `<key>` after its `<time>`
+    fn to_xml_key(keyframe: &Keyframe<Self>) -> String {
+        curve_xml_key(keyframe)
+    }
+}
+
+macro_rules! impl_xml_key_writer {
+    ($($t:ty, $format:literal),*) => {
+        $(
+            impl XMLKeyWriter for $t {
+                fn to_xml_value(value: $t) -> String {
+                    format!($format, value)
+                }
+            }
+        )*
+    };
+}
+
+// ofxTimeline writes curve values with 9 decimals, f64 and integers are written exactly
+impl_xml_key_writer!(f32, "{:.9}", f64, "{}", i32, "{}", i64, "{}", bool, "{}");
+
+impl XMLKeyWriter for (f32, f32) {
+    fn to_xml_value((x, y): (f32, f32)) -> String {
+        components_xml(&[x, y])
+    }
+}
+
+impl XMLKeyWriter for (f32, f32, f32) {
+    fn to_xml_value((x, y, z): (f32, f32, f32)) -> String {
+        components_xml(&[x, y, z])
+    }
+}
+
+impl XMLKeyWriter for (f32, f32, f32, f32) {
+    fn to_xml_value((x, y, z, w): (f32, f32, f32, f32)) -> String {
+        components_xml(&[x, y, z, w])
+    }
+}
+
+impl XMLKeyWriter for Quat {
+    fn to_xml_value(q: Quat) -> String {
+        components_xml(&[q.x, q.y, q.z, q.w])
+    }
+}
+
+/// ofxTLColorTrack layout: the color as an ofColor (`r, g, b, a` in 0..255), easing only if not the default.
+/// the palette position (`<colorx>`, `<colory>`) is not known and not written
+impl XMLKeyWriter for Color {
+    /// `<r>`, `<g>`, `<b>`, `<a>` child elements in 0..1, handle offsets are not clamped like the ofColor
+    fn to_xml_value(c: Color) -> String {
+        [c.r, c.g, c.b, c.a].iter().zip(["r", "g", "b", "a"]).map(|(v, tag)| format!("<{0}>{1}</{0}>", tag, v)).collect()
+    }
+
+    fn to_xml_key(keyframe: &Keyframe<Color>) -> String {
+        let c = keyframe.value;
+        let [r, g, b, a] = [c.r, c.g, c.b, c.a].map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8);
+        let mut xml = format!("        <color>{}, {}, {}, {}</color>\n", r, g, b, a);
+        if keyframe.easing_function != EasingFunction::default() || keyframe.easing_type != EasingType::default() {
+            xml += &format!("        <easefunc>{}</easefunc>\n", keyframe.easing_function as u8);
+            xml += &format!("        <easetype>{}</easetype>\n", keyframe.easing_type as u8);
+        }
+        xml + &interpolation_xml(keyframe.interpolation) + &handles_xml(keyframe)
+    }
+}
+
+/// `<range>` element of this crate, not part of the ofxTimeline format (see `ValueRange`)
+pub(crate) fn range_xml(range: &ValueRange) -> String {
+    let mut xml = format!("    <range><min>{}</min><max>{}</max>", range.min, range.max);
+    if !range.unit.is_empty() {
+        xml += &format!("<unit>{}</unit>", escape_xml(&range.unit));
+    }
+    xml + "</range>\n"
+}
+
+/// `<value>`, easing, interpolation and Bezier handles of a curve key
+fn curve_xml_key<T: XMLKeyWriter>(keyframe: &Keyframe<T>) -> String {
+    let mut xml = format!("        <value>{}</value>\n", T::to_xml_value(keyframe.value));
+    xml += &format!("        <easefunc>{}</easefunc>\n", keyframe.easing_function as u8);
+    xml += &format!("        <easetype>{}</easetype>\n", keyframe.easing_type as u8);
+    xml + &interpolation_xml(keyframe.interpolation) + &handles_xml(keyframe)
+}
+
+/// `<in_handle>`, `<out_handle>` and `<handle_type>`, only written for stored handles
+fn handles_xml<T: XMLKeyWriter>(keyframe: &Keyframe<T>) -> String {
+    let Some(handles) = &keyframe.handles else {
+        return String::new();
+    };
+    let handle_xml = |tag: &str, handle: &BezierHandle<T>| {
+        format!("        <{0}><time>{1}</time><value>{2}</value></{0}>\n", tag, handle.time, T::to_xml_value(handle.value))
+    };
+    handle_xml("in_handle", &handles.in_handle)
+        + &handle_xml("out_handle", &handles.out_handle)
+        + &format!("        <handle_type>{}</handle_type>\n", xml_name(handles.handle_type))
+}
+
+/// `<x>`, `<y>`, `<z>`, `<w>` child elements
+fn components_xml(components: &[f32]) -> String {
+    components.iter().zip(["x", "y", "z", "w"]).map(|(c, tag)| format!("<{0}>{1:.9}</{0}>", tag, c)).collect()
+}
+
+/// only written if not `Interpolation::Eased`, which ofxTimeline does not know
+fn interpolation_xml(interpolation: Interpolation) -> String {
+    if interpolation == Interpolation::Eased {
+        return String::new();
+    }
+    format!("        <interpolation>{}</interpolation>\n", xml_name(interpolation))
+}
+
+/// track-level spline mode, `<interpolation><kochanek_bartels><tension>..</tension>..</kochanek_bartels></interpolation>` for TCB
+fn track_interpolation_xml(interpolation: TrackInterpolation) -> String {
+    match interpolation {
+        TrackInterpolation::KochanekBartels { tension, continuity, bias } => format!(
+            "    <interpolation><kochanek_bartels><tension>{}</tension><continuity>{}</continuity><bias>{}</bias></kochanek_bartels></interpolation>\n",
+            tension, continuity, bias
+        ),
+        _ => format!("    <interpolation>{}</interpolation>\n", xml_name(interpolation)),
+    }
+}
+
+/// name of a unit variant as serialized by serde (`snake_case`)
+pub(crate) fn xml_name<T: Serialize>(value: T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => s,
+        _ => String::new(),
+    }
+}
+
+impl<T: XMLKeyWriter> Track<T> {
+    pub fn save_xml(&self, xml_path: impl AsRef<Path>) -> Result<(), TimelineError> {
+        std::fs::write(xml_path, self.to_xml_string())?;
+        Ok(())
+    }
+
+    /// ofxTimeline format. extrapolation, rounding, range and the spline mode are only written if not the default
+    pub fn to_xml_string(&self) -> String {
+        self.to_xml_string_with(String::new())
+    }
+
+    /// `settings` are written before the track settings
+    fn to_xml_string_with(&self, settings: String) -> String {
+        let mut xml = String::from("<keyframes>\n") + &settings;
+        if self.pre_extrapolation != Extrapolation::default() {
+            xml += &format!("    <pre_extrapolation>{}</pre_extrapolation>\n", xml_name(self.pre_extrapolation));
+        }
+        if self.post_extrapolation != Extrapolation::default() {
+            xml += &format!("    <post_extrapolation>{}</post_extrapolation>\n", xml_name(self.post_extrapolation));
+        }
+        if self.rounding != Rounding::default() {
+            xml += &format!("    <rounding>{}</rounding>\n", xml_name(self.rounding));
+        }
+        if let Some(range) = &self.range {
+            xml += &range_xml(range);
+        }
+        if self.interpolation != TrackInterpolation::default() {
+            xml += &track_interpolation_xml(self.interpolation);
+        }
+        for keyframe in &self.keyframes {
+            xml += "    <key>\n";
+            xml += &format!("        <time>{}</time>\n", duration_to_timecode(keyframe.time));
+            xml += &T::to_xml_key(keyframe);
+            xml += "    </key>\n";
+        }
+        xml += "</keyframes>\n";
+        xml
+    }
+}
+
+impl ColorTrack {
+    pub fn load_xml(xml_path: impl AsRef<Path>) -> Result<ColorTrack, TimelineError> {
+        ColorTrack::load_xml_reader(File::open(xml_path)?)
+    }
+
+    pub fn load_xml_reader(reader: impl Read) -> Result<ColorTrack, TimelineError> {
+        ColorTrack::load_xml_str(&read_to_string(reader)?)
+    }
+
+    /// `Track::<Color>::load_xml_str` with the `<color_space>`, the default space if there is none
+    pub fn load_xml_str(xml: &str) -> Result<ColorTrack, TimelineError> {
+        let json: KeyframesEntity<Color> = serde_json::from_value(xml_to_json::xml_str_to_json(xml)?)?;
+        let space = json.keyframes.color_space.unwrap_or_default();
+        Ok(ColorTrack { track: track_from_entity(json)?, space })
+    }
+
+    pub fn save_xml(&self, xml_path: impl AsRef<Path>) -> Result<(), TimelineError> {
+        std::fs::write(xml_path, self.to_xml_string())?;
+        Ok(())
+    }
+
+    /// `Track::to_xml_string` with a `<color_space>` if it is not the default
+    pub fn to_xml_string(&self) -> String {
+        let mut settings = String::new();
+        if self.space != ColorSpace::default() {
+            settings += &format!("    <color_space>{}</color_space>\n", xml_name(self.space));
+        }
+        self.track.to_xml_string_with(settings)
+    }
+}
+
+impl TrackVariant {
+    /// `TimelineError::TypeMismatch` for custom tracks
+    pub fn to_xml_string(&self) -> Result<String, TimelineError> {
+        match self {
+            TrackVariant::BoolTrack(track) => Ok(track.to_xml_string()),
+            TrackVariant::IntTrack(track) => Ok(track.to_xml_string()),
+            TrackVariant::FloatTrack(track) => Ok(track.to_xml_string()),
+            TrackVariant::DoubleTrack(track) => Ok(track.to_xml_string()),
+            TrackVariant::LongTrack(track) => Ok(track.to_xml_string()),
+            TrackVariant::Vec2Track(track) => Ok(track.to_xml_string()),
+            TrackVariant::Vec3Track(track) => Ok(track.to_xml_string()),
+            TrackVariant::Vec4Track(track) => Ok(track.to_xml_string()),
+            TrackVariant::QuatTrack(track) => Ok(track.to_xml_string()),
+            TrackVariant::ColorTrack(track) => Ok(track.to_xml_string()),
+            TrackVariant::SwitchTrack(track) => Ok(track.to_xml_string()),
+            TrackVariant::EventTrack(track) => Ok(track.to_xml_string()),
+            TrackVariant::TextTrack(track) => Ok(track.to_xml_string()),
+            TrackVariant::EnumTrack(track) => Ok(track.to_xml_string()),
+            TrackVariant::Custom(_) => Err(TimelineError::TypeMismatch { expected: "xml track", found: self.type_name() }),
+        }
+    }
+}
+
+impl Timeline {
+    /// writes one track in the format its loader reads, see `TrackVariant::to_xml_string`
+    pub fn save_xml(&self, track_name: &str, xml_path: impl AsRef<Path>) -> Result<(), TimelineError> {
+        std::fs::write(xml_path, self.try_get(track_name)?.to_xml_string()?)?;
+        Ok(())
+    }
+}
+
+/// a `ColorTrack` gets the `<color_space>`, the other tracks ignore it
+fn track_variant_from_entity<E, T>(json: KeyframesEntity<E>) -> Result<TrackVariant, TimelineError>
+where
+    E: ValueEntity<T>,
+    T: Copy + DeserializeOwned + Default,
+    TrackVariant: From<Track<T>>,
+{
+    let space = json.keyframes.color_space;
+    let mut variant = TrackVariant::from(track_from_entity(json)?);
+    if let (TrackVariant::ColorTrack(track), Some(space)) = (&mut variant, space) {
+        track.space = space;
+    }
+    Ok(variant)
+}
+
+fn track_from_entity<E, T>(json: KeyframesEntity<E>) -> Result<Track<T>, TimelineError>
+where
+    E: ValueEntity<T>,
+    T: Copy + DeserializeOwned + Default,
+{
+    let mut track = Track::<T> {
+        pre_extrapolation: json.keyframes.pre_extrapolation,
+        post_extrapolation: json.keyframes.post_extrapolation,
+        rounding: json.keyframes.rounding,
+        range: json.keyframes.range.map(ValueRange::from),
+        interpolation: json.keyframes.interpolation,
+        ..Default::default()
+    };
+    if json.keyframes.key.is_empty() {
+        return Err(TimelineError::MissingKeyframes);
+    }
+    for keyframe in json.keyframes.key {
+        track.keyframes.push(Keyframe {
+            time: timecode_to_duration(&keyframe.time)?,
+            value: keyframe.value.into_value()?,
+            easing_function: keyframe.easefunc.into(),
+            easing_type: keyframe.easetype.into(),
+            interpolation: keyframe.interpolation,
+            handles: handles_from_entity(keyframe.in_handle, keyframe.out_handle, keyframe.handle_type)?,
+        });
+    }
+    track.keyframes.sort_by_key(|keyframe| keyframe.time);
+    Ok(track)
+}
+
+/// stored handles need both `<in_handle>` and `<out_handle>`, the type defaults to `HandleType::Aligned`
+fn handles_from_entity<E: ValueEntity<T>, T>(
+    in_handle: Option<HandleEntity<E>>,
+    out_handle: Option<HandleEntity<E>>,
+    handle_type: Option<HandleType>,
+) -> Result<Option<BezierHandles<T>>, TimelineError> {
+    match (in_handle, out_handle) {
+        (Some(in_handle), Some(out_handle)) => Ok(Some(BezierHandles {
+            in_handle: BezierHandle::new(in_handle.time, in_handle.value.into_value()?),
+            out_handle: BezierHandle::new(out_handle.time, out_handle.value.into_value()?),
+            handle_type: handle_type.unwrap_or(HandleType::Aligned),
+        })),
+        (None, None) => Ok(None),
+        _ => Err(TimelineError::InvalidData("bezier handles need both <in_handle> and <out_handle>".to_string())),
+    }
+}
+
+/// `hh:mm:ss:mmm` as written by ofxTimeline
+pub(crate) fn timecode_to_duration(timecode: &str) -> Result<Duration, TimelineError> {
+    Ok(Timecode::parse(timecode, TimecodeFormat::Millis)?.duration)
+}
+
+/// inverse of `timecode_to_duration`, truncated to milliseconds
+pub(crate) fn duration_to_timecode(duration: Duration) -> String {
+    Timecode::millis(duration).to_string()
+}
+
+pub(crate) fn read_to_string(mut reader: impl Read) -> std::io::Result<String> {
+    let mut s = String::new();
+    reader.read_to_string(&mut s)?;
+    Ok(s)
+}
 
-fn timecode_to_duration(timecode: &str) -> Result<Duration> {
-    let parts: Vec<&str> = timecode.split(':').collect();
-    if parts.len() != 4 {
-        return Err(anyhow::anyhow!("Invalid timecode format"));
+/// text of an element converted by `xml_to_json`
+pub(crate) fn xml_text(value: serde_json::Value) -> String {
+    match value {
+        // empty elements are converted to an empty object
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::Object(o) if o.is_empty() => String::new(),
+        serde_json::Value::String(s) => s,
+        // texts like "1" are converted to numbers
+        v => v.to_string(),
     }
+}
+
+pub(crate) fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
 
-    let hours: f64 = parts[0].parse()?;
-    let minutes: f64 = parts[1].parse()?;
-    let seconds: f64 = parts[2].parse()?;
-    let milliseconds: f64 = parts[3].parse()?;
+/// a single <key> is converted to an object instead of an array
+pub(crate) fn one_or_many<'de, D, T>(deserializer: D) -> std::result::Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
+{
+    // `Many` first, structs can also be deserialized from a sequence
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        Many(Vec<T>),
+        One(T),
+    }
 
-    Ok(Duration::from_secs_f64(hours * 3600.0 + minutes * 60.0 + seconds) + Duration::from_millis(milliseconds as u64))
+    Ok(match serde::Deserialize::deserialize(deserializer)? {
+        OneOrMany::One(v) => vec![v],
+        OneOrMany::Many(v) => v,
+    })
 }
 
 #[derive(serde::Deserialize)]
+#[serde(bound(deserialize = "T: DeserializeOwned"))]
 struct KeyframesEntity<T> {
-    keyframes: HashMap<String, Vec<KeyframeEntity<T>>>,
+    keyframes: KeyframesBodyEntity<T>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(bound(deserialize = "T: DeserializeOwned"))]
+struct KeyframesBodyEntity<T> {
+    #[serde(default, deserialize_with = "one_or_many")]
+    key: Vec<KeyframeEntity<T>>,
+    #[serde(default)]
+    pre_extrapolation: Extrapolation,
+    #[serde(default)]
+    post_extrapolation: Extrapolation,
+    /// integer tracks only
+    #[serde(default)]
+    rounding: Rounding,
+    #[serde(default)]
+    range: Option<RangeEntity>,
+    /// track-level spline mode
+    #[serde(default)]
+    interpolation: TrackInterpolation,
+    /// color tracks only
+    #[serde(default)]
+    color_space: Option<ColorSpace>,
+}
+
+/// `<range><min>0</min><max>100</max><unit>cm</unit></range>`, written by `range_xml`
+#[derive(serde::Deserialize)]
+pub(crate) struct RangeEntity {
+    min: f64,
+    max: f64,
+    #[serde(default)]
+    unit: serde_json::Value,
+}
+
+impl From<RangeEntity> for ValueRange {
+    fn from(range: RangeEntity) -> Self {
+        ValueRange { min: range.min, max: range.max, unit: xml_text(range.unit) }
+    }
 }
 
 // #[derive(serde::Deserialize)]
@@ -195,20 +679,43 @@ struct KeyframesEntity<T> {
 // }
 
 #[derive(serde::Deserialize)]
+#[serde(bound(deserialize = "T: DeserializeOwned"))]
 struct KeyframeEntity<T> {
+    // ofxTLColorTrack keys have no easing
+    #[serde(default)]
     easefunc: u8,
+    #[serde(default)]
     easetype: u8,
+    #[serde(default)]
+    interpolation: Interpolation,
     time: String,
+    // ofxTLColorTrack stores the sampled ofColor as <color>, the palette position (<colorx>, <colory>) is ignored
+    #[serde(alias = "color")]
+    value: T,
+    #[serde(default)]
+    in_handle: Option<HandleEntity<T>>,
+    #[serde(default)]
+    out_handle: Option<HandleEntity<T>>,
+    #[serde(default)]
+    handle_type: Option<HandleType>,
+}
+
+/// `<in_handle><time>-0.1</time><value>0.5</value></in_handle>`, see `BezierHandle`
+#[derive(serde::Deserialize)]
+struct HandleEntity<T> {
+    time: f64,
     value: T,
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{easing::{EasingFunction, EasingType}, TrackGetter};
+    use crate::{easing::{EasingFunction, EasingType}, TimelineTrack, TrackGetter};
+    use crate::test_util::{fixture, temp_path};
 
     use super::*;
 
     #[test]
+    #[allow(clippy::excessive_precision)] // values are kept as written in the XML
     fn xml_load_test() {
         let xml = r#"
 <keyframes>
@@ -270,4 +777,313 @@ mod tests {
         assert_eq!(track.keyframes[3].easing_function, EasingFunction::Linear);
         assert_eq!(track.keyframes[3].easing_type, EasingType::InOut);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn xml_load_invalid_timecode_test() {
+        let xml = r#"
+<keyframes>
+    <key>
+        <easefunc>0</easefunc>
+        <easetype>0</easetype>
+        <time>00:00:643</time>
+        <value>0.5</value>
+    </key>
+    <key>
+        <easefunc>0</easefunc>
+        <easetype>0</easetype>
+        <time>00:00:01:000</time>
+        <value>1.0</value>
+    </key>
+</keyframes>"#;
+
+        let mut tl = Timeline::new();
+        let err = tl.load_xml_str::<f32>("test", xml).unwrap_err();
+        assert_eq!(err, TimelineError::InvalidTimecode("00:00:643".to_string()));
+        assert!(tl.get("test").is_none());
+    }
+
+    #[test]
+    fn xml_load_extrapolation_test() {
+        let xml = r#"
+<keyframes>
+    <pre_extrapolation>linear</pre_extrapolation>
+    <post_extrapolation>cycle_with_offset</post_extrapolation>
+    <key>
+        <easefunc>0</easefunc>
+        <easetype>0</easetype>
+        <time>00:00:01:000</time>
+        <value>0.0</value>
+    </key>
+    <key>
+        <easefunc>0</easefunc>
+        <easetype>0</easetype>
+        <time>00:00:02:000</time>
+        <value>1.0</value>
+    </key>
+</keyframes>"#;
+
+        let track = Track::<f32>::load_xml_str(xml).unwrap();
+        assert_eq!(track.pre_extrapolation, Extrapolation::Linear);
+        assert_eq!(track.post_extrapolation, Extrapolation::CycleWithOffset);
+        assert_eq!(track.get_value(Duration::from_millis(2500)), 1.5);
+
+        // defaults to constant when omitted
+        let track = Track::<f32>::load_xml_str(&xml.replace("<pre_extrapolation>linear</pre_extrapolation>", "")).unwrap();
+        assert_eq!(track.pre_extrapolation, Extrapolation::Constant);
+    }
+
+    #[test]
+    fn xml_load_rounding_test() {
+        let xml = r#"
+<keyframes>
+    <rounding>nearest</rounding>
+    <key>
+        <time>00:00:00:000</time>
+        <value>10</value>
+    </key>
+    <key>
+        <time>00:00:01:000</time>
+        <value>0</value>
+    </key>
+</keyframes>"#;
+
+        let track = Track::<i32>::load_xml_str(xml).unwrap();
+        assert_eq!(track.rounding, Rounding::Nearest);
+        assert_eq!(track.get_value(Duration::from_millis(40)), 10);
+        assert_eq!(Track::<i64>::load_xml_str(&xml.replace("nearest", "step")).unwrap().get_value(Duration::from_millis(960)), 10);
+    }
+
+    #[test]
+    fn xml_load_interpolation_test() {
+        let xml = r#"
+<keyframes>
+    <key>
+        <interpolation>constant</interpolation>
+        <time>00:00:00:000</time>
+        <value>0</value>
+    </key>
+    <key>
+        <interpolation>step_end</interpolation>
+        <time>00:00:01:000</time>
+        <value>5</value>
+    </key>
+    <key>
+        <interpolation>linear</interpolation>
+        <time>00:00:02:000</time>
+        <value>10</value>
+    </key>
+    <key>
+        <time>00:00:03:000</time>
+        <value>20</value>
+    </key>
+</keyframes>"#;
+
+        let track = Track::<i32>::load_xml_str(xml).unwrap();
+        let interpolations: Vec<_> = track.keyframes.iter().map(|k| k.interpolation).collect();
+        assert_eq!(interpolations, vec![Interpolation::Constant, Interpolation::StepEnd, Interpolation::Linear, Interpolation::Eased]);
+        assert_eq!(track.get_value(Duration::from_millis(900)), 0);
+        assert_eq!(track.get_value(Duration::from_millis(1100)), 10);
+        assert_eq!(track.get_value(Duration::from_millis(2500)), 15);
+
+        // "step" and "hold" are accepted for constant
+        let track = Track::<i32>::load_json_str(r#"{"keyframes": {"key": [{"time": "00:00:00:000", "value": 1, "interpolation": "hold"}]}}"#).unwrap();
+        assert_eq!(track.keyframes[0].interpolation, Interpolation::Constant);
+    }
+
+    #[test]
+    fn xml_write_test() {
+        let mut track = Track::<f32>::default();
+        track.add_keyframe(Keyframe { easing_function: EasingFunction::Cubic, easing_type: EasingType::InOut, ..Keyframe::new(Duration::from_millis(826), 0.14150393) });
+        track.add_keyframe(Keyframe::new(Duration::from_millis(3_723_004), 1.0));
+        let expected = r#"<keyframes>
+    <key>
+        <time>00:00:00:826</time>
+        <value>0.141503930</value>
+        <easefunc>4</easefunc>
+        <easetype>2</easetype>
+    </key>
+    <key>
+        <time>01:02:03:004</time>
+        <value>1.000000000</value>
+        <easefunc>0</easefunc>
+        <easetype>0</easetype>
+    </key>
+</keyframes>
+"#;
+        assert_eq!(track.to_xml_string(), expected);
+        assert_eq!(Track::<f32>::load_xml_str(expected).unwrap(), track);
+    }
+
+    #[test]
+    fn xml_round_trip_test() {
+        let mut track = Track::<i32> { post_extrapolation: Extrapolation::PingPong, rounding: Rounding::Ceil, ..Default::default() };
+        track.add_keyframe(Keyframe { interpolation: Interpolation::StepEnd, ..Keyframe::new(Duration::from_millis(0), -10) });
+        track.add_keyframe(Keyframe::new(Duration::from_millis(500), 7));
+        assert_eq!(Track::<i32>::load_xml_str(&track.to_xml_string()).unwrap(), track);
+
+        let mut track = Track::<f64>::default();
+        track.add_keyframe(Keyframe::new(Duration::from_millis(1), 1.0 / 3.0));
+        assert_eq!(Track::<f64>::load_xml_str(&track.to_xml_string()).unwrap(), track);
+
+        let mut track = Track::<Quat>::default();
+        track.add_keyframe(Keyframe::new(Duration::from_millis(0), Quat::from_euler_degrees(0.0, 90.0, 0.0)));
+        let q = Track::<Quat>::load_xml_str(&track.to_xml_string()).unwrap().keyframes[0].value;
+        assert!(q.dot(track.keyframes[0].value) > 1.0 - 1e-6);
+
+        let mut track = Track::<f32> { interpolation: TrackInterpolation::CatmullRom, ..Default::default() };
+        track.add_keyframe(Keyframe { interpolation: Interpolation::Bezier, ..Keyframe::new(Duration::from_millis(0), 0.5) });
+        track.add_keyframe(Keyframe::new(Duration::from_millis(1000), 1.0));
+        track.keyframes[0].handles = Some(BezierHandles { handle_type: HandleType::Broken, ..Default::default() });
+        track.set_in_handle(0, BezierHandle::new(-0.1, 2.0)).set_out_handle(0, BezierHandle::new(0.25, -0.5));
+        let xml = track.to_xml_string();
+        assert!(xml.contains("<handle_type>broken</handle_type>"));
+        assert_eq!(Track::<f32>::load_xml_str(&xml).unwrap(), track);
+
+        track.interpolation = TrackInterpolation::KochanekBartels { tension: 0.5, continuity: -1.0, bias: 0.25 };
+        assert_eq!(Track::<f32>::load_xml_str(&track.to_xml_string()).unwrap(), track);
+
+        let mut track = Track::<(f32, f32)>::default();
+        track.add_keyframe(Keyframe { interpolation: Interpolation::Bezier, ..Keyframe::new(Duration::from_millis(0), (1.0, 2.0)) });
+        track.set_out_handle(0, BezierHandle::new(0.5, (0.25, -0.5)));
+        assert_eq!(Track::<(f32, f32)>::load_xml_str(&track.to_xml_string()).unwrap(), track);
+    }
+
+    #[test]
+    fn xml_round_trip_color_space_test() {
+        let mut track = ColorTrack::new(ColorSpace::OkLch);
+        track.add_keyframe(Keyframe { interpolation: Interpolation::Bezier, ..Keyframe::new(Duration::from_millis(0), Color::rgb(1.0, 0.0, 0.0)) });
+        track.add_keyframe(Keyframe::new(Duration::from_millis(1000), Color::rgb(0.0, 0.0, 1.0)));
+        track.set_out_handle(0, BezierHandle::new(0.1, Color::new(0.1, -0.2, 0.3, 0.0)));
+        let xml = track.to_xml_string();
+        assert!(xml.contains("<color_space>ok_lch</color_space>"));
+        assert_eq!(ColorTrack::load_xml_str(&xml).unwrap(), track);
+
+        let mut tl = Timeline::new();
+        tl.add("color", track.clone());
+        tl.load_xml_str::<Color>("loaded", &tl.try_get("color").unwrap().to_xml_string().unwrap()).unwrap();
+        assert_eq!(tl.get("loaded").unwrap().as_color_track(), &track);
+
+        // other tracks ignore the color space
+        tl.load_xml_str::<f32>("float", "<keyframes><color_space>hsv</color_space><key><time>00:00:00:000</time><value>1</value></key></keyframes>").unwrap();
+        assert!(Track::<f32>::load_xml_str("<keyframes><key><time>00:00:00:000</time><value>1</value><in_handle><time>0</time><value>0</value></in_handle></key></keyframes>").is_err());
+    }
+
+    #[test]
+    fn xml_write_color_test() {
+        let mut track = Track::<Color>::default();
+        track.keyframes.push(Keyframe::new(Duration::from_millis(0), Color::from_rgba8(255, 128, 0, 255)));
+        let xml = track.to_xml_string();
+        assert!(xml.contains("<color>255, 128, 0, 255</color>"));
+        assert!(!xml.contains("easefunc"));
+        assert_eq!(Track::<Color>::load_xml_str(&xml).unwrap(), track);
+    }
+
+    #[test]
+    fn timeline_save_xml_test() {
+        let mut tl = Timeline::new();
+        tl.add("custom", crate::CustomTrack::new(Track::<f32>::default()));
+        assert!(tl.save_xml("missing", "unused.xml").is_err());
+        assert!(matches!(
+            tl.try_get("custom").unwrap().to_xml_string(),
+            Err(TimelineError::TypeMismatch { expected: "xml track", .. })
+        ));
+
+        let path = temp_path("save_xml_test.xml");
+        let path = path.to_str().unwrap();
+        tl.load_xml_str::<f32>("float", "<keyframes><key><time>00:00:01:000</time><value>0.5</value></key></keyframes>").unwrap();
+        tl.save_xml("float", path).unwrap();
+        assert_eq!(Track::<f32>::load_xml_str(&std::fs::read_to_string(path).unwrap()).unwrap().keyframes[0].value, 0.5);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn load_file_test() {
+        let track = Track::<f32>::load_xml(fixture("curves.xml")).unwrap();
+        assert_eq!(track.keyframes.len(), 3);
+        assert_eq!(track.keyframes[1].time, Duration::from_millis(826));
+        assert_eq!(track.keyframes[1].easing_function, EasingFunction::Cubic);
+        assert_eq!(Track::<f32>::load_json(fixture("curves.json")).unwrap(), track);
+        assert_eq!(Track::<f32>::load_xml_reader(File::open(fixture("curves.xml")).unwrap()).unwrap(), track);
+        assert_eq!(Track::<f32>::load_json_reader(File::open(fixture("curves.json")).unwrap()).unwrap(), track);
+
+        let mut tl = Timeline::new();
+        tl.load_xml::<f32>("xml", fixture("curves.xml")).unwrap();
+        tl.load_json::<f32>("json", fixture("curves.json").to_str().unwrap()).unwrap();
+        tl.load_xml_reader::<f32>("reader", std::fs::read_to_string(fixture("curves.xml")).unwrap().as_bytes()).unwrap();
+        for name in ["xml", "json", "reader"] {
+            assert_eq!(tl.get(name).unwrap().as_float_track(), &track);
+        }
+
+        assert!(Track::<f32>::load_xml(fixture("missing.xml")).is_err());
+        assert!(tl.load_json::<f32>("missing", fixture("missing.json")).is_err());
+    }
+
+    #[test]
+    fn xml_load_bool_test() {
+        let xml = r#"
+<keyframes>
+    <key><time>00:00:00:000</time><value>true</value></key>
+    <key><time>00:00:01:000</time><value>0</value></key>
+    <key><time>00:00:02:000</time><value>on</value></key>
+</keyframes>"#;
+
+        let track = Track::<bool>::load_xml_str(xml).unwrap();
+        let values: Vec<_> = track.keyframes.iter().map(|k| k.value).collect();
+        assert_eq!(values, vec![true, false, true]);
+        assert_eq!(Track::<bool>::load_xml_str(&track.to_xml_string()).unwrap(), track);
+        assert!(Track::<bool>::load_xml_str(&xml.replace("on", "maybe")).is_err());
+
+        let mut tl = Timeline::new();
+        tl.load_json_str::<bool>("bool", r#"{"keyframes": {"key": {"time": "00:00:00:000", "value": false}}}"#).unwrap();
+        assert!(!tl.get("bool").unwrap().as_bool_track().keyframes[0].value);
+    }
+
+    #[test]
+    fn xml_load_vec_test() {
+        let xml = r#"
+<keyframes>
+    <key>
+        <time>00:00:00:000</time>
+        <value><x>1</x><y>2.5</y><z>-3</z></value>
+    </key>
+    <key>
+        <time>00:00:01:000</time>
+        <value><x>0</x><y>0</y><z>0</z></value>
+    </key>
+</keyframes>"#;
+
+        let track = Track::<(f32, f32, f32)>::load_xml_str(xml).unwrap();
+        assert_eq!(track.keyframes[0].value, (1.0, 2.5, -3.0));
+        assert_eq!(track.get_value(Duration::from_millis(500)), (0.5, 1.25, -1.5));
+        assert_eq!(Track::<(f32, f32, f32)>::load_xml_str(&track.to_xml_string()).unwrap(), track);
+
+        let err = Track::<(f32, f32, f32, f32)>::load_xml_str(xml).unwrap_err();
+        assert_eq!(err.to_string(), "invalid vector: expected 4 components, found 3");
+
+        let json = r#"{"keyframes": {"key": [{"time": "00:00:00:000", "value": [1, 2]}, {"time": "00:00:01:000", "value": {"x": 3, "y": 4}}]}}"#;
+        let track = Track::<(f32, f32)>::load_json_str(json).unwrap();
+        assert_eq!(track.keyframes[1].value, (3.0, 4.0));
+        let mut tl = Timeline::new();
+        tl.load_json_str::<(f32, f32, f32, f32)>("vec4", r#"{"keyframes": {"key": {"time": "00:00:00:000", "value": [1, 2, 3, 4]}}}"#).unwrap();
+        assert_eq!(tl.get("vec4").unwrap().as_vec4_track().keyframes[0].value, (1.0, 2.0, 3.0, 4.0));
+    }
+
+    #[test]
+    fn xml_range_test() {
+        let xml = r#"
+<keyframes>
+    <range><min>-10</min><max>10</max><unit>deg</unit></range>
+    <key>
+        <time>00:00:00:000</time>
+        <value>0.25</value>
+    </key>
+</keyframes>"#;
+
+        let track = Track::<f32>::load_xml_str(xml).unwrap();
+        assert_eq!(track.range, Some(ValueRange { min: -10.0, max: 10.0, unit: "deg".to_string() }));
+        assert_eq!(track.get_mapped_value(Duration::ZERO), -5.0);
+        assert!(track.to_xml_string().contains("<range><min>-10</min><max>10</max><unit>deg</unit></range>"));
+        assert_eq!(Track::<f32>::load_xml_str(&track.to_xml_string()).unwrap(), track);
+        assert_eq!(Track::<f32>::load_xml_str(&xml.replace("<unit>deg</unit>", "")).unwrap().range.unwrap().unit, "");
+    }
+}
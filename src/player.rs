@@ -0,0 +1,581 @@
+use std::time::Duration;
+
+use crate::{Timeline, TimelineError, TimelineEvent, TrackGetter, TrackValue};
+
+/// What the player does when the playhead reaches the in/out point
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LoopMode {
+    /// stop at the out point (or the in point when playing in reverse)
+    #[default]
+    Once,
+    /// jump back to the other end and keep playing
+    Loop,
+    /// reverse the playback direction at each end
+    PingPong,
+}
+
+/// Result of `TimelinePlayer::update`
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PlayerUpdate {
+    /// how many times the playhead wrapped around (`Loop`) or bounced (`PingPong`) during this update
+    pub wrapped: u32,
+    /// true if playback reached the end during this update (`Once` only)
+    pub finished: bool,
+}
+
+/// Playhead moves (from, to, to included) crossed `repeat` times in a row, the full loops of an `update`
+///
+/// The moves use the half-open `EventTrack::events_between`. A `Loop` wrap also crosses the end it
+/// jumps from, so its move includes `to`.
+#[derive(Debug, Clone)]
+struct Pass {
+    spans: Vec<(Duration, Duration, bool)>,
+    repeat: u32,
+}
+
+impl Pass {
+    fn once(from: f64, to: f64, to_included: bool) -> Pass {
+        Pass { spans: vec![(secs(from), secs(to), to_included)], repeat: 1 }
+    }
+}
+
+/// Playback controller owning a playhead over a `Timeline`.
+///
+/// Call `update` once per frame with the elapsed time, then sample values at the playhead with `get_value`.
+#[derive(Debug)]
+pub struct TimelinePlayer {
+    pub timeline: Timeline,
+    time: Duration,
+    playing: bool,
+    finished: bool,
+    speed: f64,
+    /// +1.0 or -1.0, flipped on each bounce in `PingPong` mode
+    direction: f64,
+    loop_mode: LoopMode,
+    in_point: Option<Duration>,
+    out_point: Option<Duration>,
+    /// playhead moves covered by the last `update`, split at loop wraps and bounces
+    passes: Vec<Pass>,
+    /// set when the last `update` stopped exactly at an end (`Once`)
+    end_reached: Option<Duration>,
+}
+
+impl TimelinePlayer {
+    pub fn new(timeline: Timeline) -> TimelinePlayer {
+        TimelinePlayer {
+            timeline,
+            time: Duration::from_secs(0),
+            playing: false,
+            finished: false,
+            speed: 1.0,
+            direction: 1.0,
+            loop_mode: LoopMode::Once,
+            in_point: None,
+            out_point: None,
+            passes: vec![],
+            end_reached: None,
+        }
+    }
+
+    /// start (or resume) playback. restarts from the beginning if playback has finished
+    pub fn play(&mut self) -> &mut Self {
+        if self.finished {
+            self.rewind();
+        }
+        self.playing = true;
+        self
+    }
+
+    pub fn pause(&mut self) -> &mut Self {
+        self.playing = false;
+        self
+    }
+
+    /// pause and rewind to the in point
+    pub fn stop(&mut self) -> &mut Self {
+        self.playing = false;
+        self.time = self.get_in_point();
+        self.direction = 1.0;
+        self.finished = false;
+        self.clear_passes();
+        self
+    }
+
+    /// move the playhead, clamped to the in/out range. events in between are not crossed
+    pub fn seek(&mut self, time: Duration) -> &mut Self {
+        self.time = time.clamp(self.get_in_point(), self.get_out_point().max(self.get_in_point()));
+        self.finished = false;
+        self.clear_passes();
+        self
+    }
+
+    /// playback rate, 1.0 is realtime. negative values play in reverse.
+    /// panics if the speed is not finite (see `try_set_speed`)
+    pub fn set_speed(&mut self, speed: f64) -> &mut Self {
+        self.try_set_speed(speed).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_set_speed(&mut self, speed: f64) -> Result<&mut Self, TimelineError> {
+        if !speed.is_finite() {
+            return Err(TimelineError::InvalidData(format!("invalid speed: {}", speed)));
+        }
+        self.speed = speed;
+        Ok(self)
+    }
+
+    pub fn set_loop_mode(&mut self, loop_mode: LoopMode) -> &mut Self {
+        self.loop_mode = loop_mode;
+        self
+    }
+
+    /// `None` means the beginning of the timeline
+    pub fn set_in_point(&mut self, in_point: Option<Duration>) -> &mut Self {
+        self.in_point = in_point;
+        self.seek(self.time)
+    }
+
+    /// `None` means the end of the timeline (`Timeline::get_max_duration`)
+    pub fn set_out_point(&mut self, out_point: Option<Duration>) -> &mut Self {
+        self.out_point = out_point;
+        self.seek(self.time)
+    }
+
+    pub fn get_time(&self) -> Duration {
+        self.time
+    }
+
+    pub fn get_speed(&self) -> f64 {
+        self.speed
+    }
+
+    pub fn get_loop_mode(&self) -> LoopMode {
+        self.loop_mode
+    }
+
+    pub fn get_in_point(&self) -> Duration {
+        self.in_point.unwrap_or(Duration::from_secs(0))
+    }
+
+    pub fn get_out_point(&self) -> Duration {
+        self.out_point.unwrap_or_else(|| self.timeline.get_max_duration())
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// sample a track at the playhead. panics if the track is missing or empty (see `try_get_value`)
+    pub fn get_value(&self, name: &str) -> TrackValue {
+        self.timeline.get_value(name, self.time)
+    }
+
+    pub fn try_get_value(&self, name: &str) -> Result<TrackValue, TimelineError> {
+        self.timeline.try_get_value(name, self.time)
+    }
+
+    /// events of an event track crossed by the last `update`, in crossing order, once per loop.
+    /// panics if the track is missing or not an event track (see `try_get_events`)
+    pub fn get_events(&self, name: &str) -> Vec<&TimelineEvent> {
+        self.try_get_events(name).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_events(&self, name: &str) -> Result<Vec<&TimelineEvent>, TimelineError> {
+        let mut events = vec![];
+        self.for_each_crossed(name, |pass_events, repeat| {
+            for _ in 0..repeat {
+                events.extend_from_slice(&pass_events);
+            }
+        })?;
+        Ok(events)
+    }
+
+    /// same as `get_events`, each event once with the number of times it was crossed (in order of the first crossing).
+    /// the size does not grow with the number of loops, for fast playback of short loops
+    pub fn get_event_counts(&self, name: &str) -> Vec<(&TimelineEvent, u64)> {
+        self.try_get_event_counts(name).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_event_counts(&self, name: &str) -> Result<Vec<(&TimelineEvent, u64)>, TimelineError> {
+        let mut counts: Vec<(&TimelineEvent, u64)> = vec![];
+        self.for_each_crossed(name, |pass_events, repeat| {
+            for event in pass_events {
+                match counts.iter_mut().find(|(e, _)| std::ptr::eq(*e, event)) {
+                    Some((_, count)) => *count += repeat as u64,
+                    None => counts.push((event, repeat as u64)),
+                }
+            }
+        })?;
+        Ok(counts)
+    }
+
+    /// calls `f` with the events of each pass of the last `update` and its repeat count
+    fn for_each_crossed<'a>(&'a self, name: &str, mut f: impl FnMut(Vec<&'a TimelineEvent>, u32)) -> Result<(), TimelineError> {
+        let track = self.timeline.try_get(name)?.try_as_event_track()?;
+        for pass in &self.passes {
+            let mut events = vec![];
+            for &(from, to, to_included) in &pass.spans {
+                events.extend(track.events_between(from, to));
+                if to_included {
+                    events.extend(track.events_at(to));
+                }
+            }
+            f(events, pass.repeat);
+        }
+        if let Some(time) = self.end_reached {
+            f(track.events_at(time).iter().collect(), 1);
+        }
+        Ok(())
+    }
+
+    /// advance the playhead by `delta` (scaled by the speed)
+    pub fn update(&mut self, delta: Duration) -> PlayerUpdate {
+        let mut result = PlayerUpdate::default();
+        self.clear_passes();
+        if !self.playing {
+            return result;
+        }
+
+        let start = self.get_in_point().as_secs_f64();
+        let end = self.get_out_point().as_secs_f64().max(start);
+        let length = end - start;
+        let position = self.time.as_secs_f64();
+        let velocity = self.speed * self.direction;
+        let t = position + delta.as_secs_f64() * velocity;
+
+        // start of the last pass
+        let mut from = position;
+        let t = match self.loop_mode {
+            LoopMode::Once => {
+                if (t >= end && velocity > 0.0) || (t <= start && velocity < 0.0) {
+                    self.playing = false;
+                    self.finished = true;
+                    result.finished = true;
+                    self.end_reached = Some(secs(t.clamp(start, end)));
+                }
+                t.clamp(start, end)
+            }
+            _ if length <= 0.0 => start,
+            LoopMode::Loop => {
+                if t > end || t < start {
+                    let u = t - start;
+                    result.wrapped = (u / length).floor().abs() as u32;
+                    let (reached, restart) = if velocity > 0.0 { (end, start) } else { (start, end) };
+                    self.passes.push(Pass::once(position, reached, true));
+                    if result.wrapped > 1 {
+                        let full_loop = Pass::once(restart, reached, true);
+                        self.passes.push(Pass { repeat: result.wrapped - 1, ..full_loop });
+                    }
+                    from = restart;
+                    start + u.rem_euclid(length)
+                } else {
+                    t
+                }
+            }
+            LoopMode::PingPong => {
+                let u = t - start;
+                let n = (u / length).floor();
+                let r = u - n * length;
+                result.wrapped = n.abs() as u32;
+                if result.wrapped > 0 {
+                    let (first, second) = if velocity > 0.0 { (end, start) } else { (start, end) };
+                    self.passes.push(Pass::once(position, first, false));
+                    // the passes between the first and the last bounce, there and back
+                    let full = result.wrapped - 1;
+                    if full >= 2 {
+                        let spans = vec![(secs(first), secs(second), false), (secs(second), secs(first), false)];
+                        self.passes.push(Pass { spans, repeat: full / 2 });
+                    }
+                    if full % 2 == 1 {
+                        self.passes.push(Pass::once(first, second, false));
+                    }
+                    from = if result.wrapped % 2 == 1 { first } else { second };
+                }
+                if n.rem_euclid(2.0) == 0.0 {
+                    start + r
+                } else {
+                    self.direction = -self.direction;
+                    end - r
+                }
+            }
+        };
+
+        self.passes.push(Pass::once(from, t, false));
+
+        self.time = secs(t);
+        result
+    }
+
+    fn clear_passes(&mut self) {
+        self.passes.clear();
+        self.end_reached = None;
+    }
+
+    fn rewind(&mut self) {
+        self.direction = 1.0;
+        self.finished = false;
+        self.clear_passes();
+        self.time = if self.speed < 0.0 {
+            self.get_out_point()
+        } else {
+            self.get_in_point()
+        };
+    }
+}
+
+fn secs(t: f64) -> Duration {
+    Duration::from_secs_f64(t.max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EventTrack, Keyframe, TimelineTrack, Track};
+    use crate::test_util::ms;
+
+    use super::*;
+
+    fn create_player() -> TimelinePlayer {
+        let mut tl = Timeline::new();
+        let mut t = Track::<f32>::default();
+        t.add_keyframe(Keyframe::new(ms(0), 0.0));
+        t.add_keyframe(Keyframe::new(ms(1000), 1.0));
+        tl.add("test", t);
+        TimelinePlayer::new(tl)
+    }
+
+    #[test]
+    fn paused_player_does_not_advance_test() {
+        let mut player = create_player();
+        assert_eq!(player.update(ms(100)), PlayerUpdate::default());
+        assert_eq!(player.get_time(), ms(0));
+
+        player.play();
+        player.update(ms(100));
+        player.pause();
+        player.update(ms(100));
+        assert_eq!(player.get_time(), ms(100));
+    }
+
+    #[test]
+    fn play_once_test() {
+        let mut player = create_player();
+        player.play();
+        assert_eq!(player.update(ms(500)), PlayerUpdate { wrapped: 0, finished: false });
+        let v: f32 = player.get_value("test").into();
+        assert_eq!(v, 0.5);
+
+        assert_eq!(player.update(ms(700)), PlayerUpdate { wrapped: 0, finished: true });
+        assert_eq!(player.get_time(), ms(1000));
+        assert!(!player.is_playing());
+        assert!(player.is_finished());
+
+        // play again restarts from the beginning
+        player.play();
+        player.update(ms(200));
+        assert_eq!(player.get_time(), ms(200));
+    }
+
+    #[test]
+    fn play_loop_test() {
+        let mut player = create_player();
+        player.set_loop_mode(LoopMode::Loop).play();
+        player.update(ms(800));
+        assert_eq!(player.update(ms(400)), PlayerUpdate { wrapped: 1, finished: false });
+        assert_eq!(player.get_time(), ms(200));
+
+        assert_eq!(player.update(ms(2300)).wrapped, 2);
+        assert_eq!(player.get_time(), ms(500));
+    }
+
+    #[test]
+    fn play_reverse_loop_test() {
+        let mut player = create_player();
+        player.set_loop_mode(LoopMode::Loop).set_speed(-2.0).play();
+        assert_eq!(player.update(ms(100)).wrapped, 1);
+        assert_eq!(player.get_time(), ms(800));
+    }
+
+    #[test]
+    fn play_ping_pong_test() {
+        let mut player = create_player();
+        player.set_loop_mode(LoopMode::PingPong).play();
+        player.update(ms(800));
+        assert_eq!(player.update(ms(400)).wrapped, 1);
+        assert_eq!(player.get_time(), ms(800));
+
+        // now playing backward
+        player.update(ms(300));
+        assert_eq!(player.get_time(), ms(500));
+
+        assert_eq!(player.update(ms(700)).wrapped, 1);
+        assert_eq!(player.get_time(), ms(200));
+    }
+
+    #[test]
+    fn in_out_points_test() {
+        let mut player = create_player();
+        player
+            .set_in_point(Some(ms(200)))
+            .set_out_point(Some(ms(600)))
+            .set_loop_mode(LoopMode::Loop);
+        assert_eq!(player.get_time(), ms(200));
+
+        player.play();
+        assert_eq!(player.update(ms(500)).wrapped, 1);
+        assert_eq!(player.get_time(), ms(300));
+
+        player.seek(ms(900));
+        assert_eq!(player.get_time(), ms(600));
+
+        player.stop();
+        assert_eq!(player.get_time(), ms(200));
+        assert!(!player.is_playing());
+    }
+
+    fn create_event_player() -> TimelinePlayer {
+        let mut player = create_player();
+        let mut events = EventTrack::new();
+        for (t, label) in [(0, "a"), (250, "b"), (1000, "c")] {
+            events.add_event(TimelineEvent::labeled(ms(t), label));
+        }
+        player.timeline.add("events", events);
+        player
+    }
+
+    fn labels(events: Vec<&TimelineEvent>) -> Vec<&str> {
+        events.iter().map(|e| e.label.as_str()).collect()
+    }
+
+    #[test]
+    fn events_once_test() {
+        let mut player = create_event_player();
+        player.play();
+        player.update(ms(100));
+        assert_eq!(labels(player.get_events("events")), vec!["a"]);
+        player.update(ms(100));
+        assert!(player.get_events("events").is_empty());
+        player.update(ms(900));
+        // the event at the out point is crossed when playback finishes
+        assert_eq!(labels(player.get_events("events")), vec!["b", "c"]);
+        player.update(ms(100));
+        assert!(player.get_events("events").is_empty());
+    }
+
+    #[test]
+    fn events_loop_test() {
+        let mut player = create_event_player();
+        player.set_loop_mode(LoopMode::Loop).play();
+        player.update(ms(900));
+        assert_eq!(labels(player.get_events("events")), vec!["a", "b"]);
+        player.update(ms(2200));
+        // 900 -> 1000 with the out point, two full loops, 0 -> 100
+        assert_eq!(labels(player.get_events("events")), vec!["c", "a", "b", "c", "a", "b", "c", "a"]);
+        assert_eq!(player.get_time(), ms(100));
+    }
+
+    #[test]
+    fn events_reverse_loop_test() {
+        let mut player = create_event_player();
+        player.set_loop_mode(LoopMode::Loop).set_speed(-1.0).play();
+        player.seek(ms(500));
+        player.update(ms(600));
+        // 500 -> 0 with the in point, wrap, 1000 -> 900
+        assert_eq!(labels(player.get_events("events")), vec!["b", "a", "c"]);
+        assert_eq!(player.get_time(), ms(900));
+    }
+
+    #[test]
+    fn events_ping_pong_test() {
+        let mut player = create_event_player();
+        player.set_loop_mode(LoopMode::PingPong).play();
+        player.seek(ms(500));
+        player.update(ms(1600));
+        // 500 -> 1000, 1000 -> 0, 0 -> 100
+        assert_eq!(labels(player.get_events("events")), vec!["c", "b", "a"]);
+        assert_eq!(player.get_time(), ms(100));
+    }
+
+    #[test]
+    fn events_many_wraps_test() {
+        let mut player = create_event_player();
+        player.set_loop_mode(LoopMode::Loop).play();
+        player.seek(ms(500));
+        assert_eq!(player.update(ms(10_000)).wrapped, 10);
+        // 500 -> 1000, 9 full loops, 0 -> 500
+        assert_eq!(player.get_events("events").len(), 30);
+        let counts: Vec<(&str, u64)> = player.get_event_counts("events").iter().map(|(e, n)| (e.label.as_str(), *n)).collect();
+        assert_eq!(counts, vec![("c", 10), ("a", 10), ("b", 10)]);
+
+        player.set_loop_mode(LoopMode::PingPong).seek(ms(500));
+        assert_eq!(player.update(ms(10_000)).wrapped, 10);
+        // 500 -> 1000, 9 passes between the bounces, 0 -> 500
+        let events = labels(player.get_events("events"));
+        assert_eq!(events[..6], ["c", "b", "a", "b", "c", "b"]);
+        assert_eq!(events.len(), 20);
+        assert_eq!(events.iter().filter(|l| **l == "c").count(), 5);
+        assert_eq!(player.get_time(), ms(500));
+    }
+
+    #[test]
+    fn events_loop_every_crossing_test() {
+        let count = |player: &TimelinePlayer, label: &str| {
+            player.get_events("events").iter().filter(|e| e.label == label).count()
+        };
+
+        // small steps: the event at the out point fires once per loop
+        let mut player = create_event_player();
+        player.set_loop_mode(LoopMode::Loop).play();
+        let (mut a, mut b, mut c) = (0, 0, 0);
+        for _ in 0..45 {
+            player.update(ms(100));
+            (a, b, c) = (a + count(&player, "a"), b + count(&player, "b"), c + count(&player, "c"));
+        }
+        assert_eq!((a, b, c), (5, 5, 4));
+
+        // one big step: every loop in between is reported
+        let mut player = create_event_player();
+        player.set_loop_mode(LoopMode::Loop).play();
+        assert_eq!(player.update(ms(5300)).wrapped, 5);
+        assert_eq!((count(&player, "a"), count(&player, "b"), count(&player, "c")), (6, 6, 5));
+    }
+
+    #[test]
+    fn invalid_speed_test() {
+        let mut player = create_event_player();
+        assert!(player.try_set_speed(f64::INFINITY).is_err());
+        assert!(player.try_set_speed(f64::NAN).is_err());
+        assert_eq!(player.get_speed(), 1.0);
+
+        // a huge finite speed still wraps in bounded time and memory
+        player.set_loop_mode(LoopMode::Loop).set_speed(f64::MAX).play();
+        assert_eq!(player.update(ms(1000)).wrapped, u32::MAX);
+        let counts = player.get_event_counts("events");
+        assert_eq!(counts.len(), 3);
+        assert!(counts.iter().all(|(_, n)| *n >= u32::MAX as u64 - 1));
+    }
+
+    #[test]
+    fn events_seek_test() {
+        let mut player = create_event_player();
+        player.play();
+        player.update(ms(100));
+        player.seek(ms(800));
+        assert!(player.get_events("events").is_empty());
+        assert_eq!(
+            player.try_get_events("test"),
+            Err(TimelineError::TypeMismatch { expected: "event", found: "float" })
+        );
+    }
+
+    #[test]
+    fn missing_track_test() {
+        let player = create_player();
+        assert_eq!(
+            player.try_get_value("missing"),
+            Err(TimelineError::TrackNotFound("missing".to_string()))
+        );
+    }
+}
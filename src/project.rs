@@ -0,0 +1,360 @@
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::loader::{one_or_many, timecode_to_duration, xml_text, RangeEntity, XMLTrackLoader};
+use crate::{xml_to_json, Color, ColorTrack, EventTrack, SwitchTrack, TempoMap, TimeSignature, Timeline, TimelineError, Track, TrackVariant, ValueRange};
+
+/// Settings of an ofxTimeline project, read by `Timeline::load_project`
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectSettings {
+    /// name of the timeline, the track files are named `<name>_<track>.xml`
+    pub name: String,
+    pub duration: Duration,
+    /// start of the in/out (loop) range
+    pub in_time: Duration,
+    /// end of the in/out (loop) range, zero if not set
+    pub out_time: Duration,
+    /// zero if not set
+    pub bpm: f32,
+}
+
+/// An ofxTimeline page, with its track names in display order
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelinePage {
+    pub name: String,
+    pub tracks: Vec<String>,
+}
+
+impl TimelinePage {
+    pub fn new(name: &str) -> TimelinePage {
+        TimelinePage { name: name.to_string(), tracks: vec![] }
+    }
+}
+
+/// ofxTimeline track types (`ofxTLTrack::getTrackType`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProjectTrackType {
+    Curves,
+    Switches,
+    Bangs,
+    Flags,
+    Colors,
+}
+
+impl ProjectTrackType {
+    fn from_name(name: &str) -> Result<ProjectTrackType, TimelineError> {
+        match name.to_ascii_lowercase().as_str() {
+            "curves" => Ok(ProjectTrackType::Curves),
+            "switches" => Ok(ProjectTrackType::Switches),
+            "bangs" => Ok(ProjectTrackType::Bangs),
+            "flags" => Ok(ProjectTrackType::Flags),
+            "colors" => Ok(ProjectTrackType::Colors),
+            _ => Err(TimelineError::InvalidData(format!("unknown track type: {}", name))),
+        }
+    }
+
+    /// from the elements of the first `<key>`, curves if the track has no keys
+    fn infer(json: &serde_json::Value) -> ProjectTrackType {
+        let key = match &json["keyframes"]["key"] {
+            serde_json::Value::Array(keys) => keys.first().cloned().unwrap_or_default(),
+            key => key.clone(),
+        };
+        let has = |tag: &str| key.get(tag).is_some();
+        if has("startTime") || has("startMillis") {
+            ProjectTrackType::Switches
+        } else if has("color") || has("colorx") {
+            ProjectTrackType::Colors
+        } else if has("flag") {
+            ProjectTrackType::Flags
+        } else if key.is_null() || has("easefunc") || has("easetype") {
+            ProjectTrackType::Curves
+        } else {
+            ProjectTrackType::Bangs
+        }
+    }
+
+    /// a file without keys is an empty track
+    fn load(self, xml: &str, json: &serde_json::Value) -> Result<TrackVariant, TimelineError> {
+        if json["keyframes"]["key"].is_null() {
+            return Ok(match self {
+                ProjectTrackType::Curves => Track::<f32>::default().into(),
+                ProjectTrackType::Colors => Track::<Color>::default().into(),
+                ProjectTrackType::Switches => SwitchTrack::new().into(),
+                ProjectTrackType::Bangs | ProjectTrackType::Flags => EventTrack::new().into(),
+            });
+        }
+        Ok(match self {
+            ProjectTrackType::Curves => Track::<f32>::load_xml_str(xml)?.into(),
+            ProjectTrackType::Colors => ColorTrack::load_xml_str(xml)?.into(),
+            ProjectTrackType::Switches => SwitchTrack::load_xml_str(xml)?.into(),
+            ProjectTrackType::Bangs | ProjectTrackType::Flags => EventTrack::load_xml_str(xml)?.into(),
+        })
+    }
+}
+
+impl Timeline {
+    /// Loads an ofxTimeline save folder: the settings file (`<name>_settings.xml`) and one XML file per track
+    ///
+    /// The settings list the pages with the name and type (`Curves`, `Switches`, `Bangs`, `Flags`, `Colors`)
+    /// of their tracks. A `<range>` in a curves `<track>` sets its value range; ofxTimeline does not write it,
+    /// it is specific to this crate (see `ValueRange`). Tracks not listed, or all tracks if the folder has no settings file, are discovered
+    /// from the XML files of the folder, their type is inferred from the keys and they are added to the last page.
+    /// Missing or empty track files give empty tracks. A `bpm` setting gives a constant 4/4 `tempo_map`.
+    pub fn load_project(folder_path: impl AsRef<Path>) -> Result<Timeline, TimelineError> {
+        let folder = folder_path.as_ref();
+        let mut files: Vec<String> = std::fs::read_dir(folder)?
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|file| file.ends_with(".xml"))
+            .collect();
+        files.sort();
+
+        let mut tl = Timeline::new();
+        let settings_file = files.iter().find(|file| file.ends_with("_settings.xml")).cloned();
+        if let Some(settings_file) = &settings_file {
+            let default_name = settings_file.trim_end_matches("_settings.xml");
+            let entity: SettingsFileEntity = serde_json::from_value(xml_to_json::xml_str_to_json(
+                &std::fs::read_to_string(folder.join(settings_file))?,
+            )?)?;
+            let (settings, pages) = entity.timeline.into_settings(default_name)?;
+            if settings.bpm > 0.0 {
+                tl.tempo_map = Some(TempoMap::try_new(settings.bpm as f64, TimeSignature::default())?);
+            }
+            tl.settings = settings;
+            for (page, tracks) in pages {
+                for (name, track_type, range) in tracks {
+                    let file = folder.join(format!("{}_{}.xml", tl.settings.name, name));
+                    let xml = if file.exists() { std::fs::read_to_string(file)? } else { String::new() };
+                    let json = xml_json(&xml)?;
+                    let mut track = track_type.load(&xml, &json)?;
+                    if let (TrackVariant::FloatTrack(track), Some(range)) = (&mut track, range) {
+                        track.range = Some(range);
+                    }
+                    tl.add(&name, track);
+                }
+                tl.pages.push(page);
+            }
+        }
+
+        let prefix = if settings_file.is_some() { format!("{}_", tl.settings.name) } else { String::new() };
+        for file in &files {
+            let Some(name) = file.strip_prefix(&prefix).and_then(|f| f.strip_suffix(".xml")) else {
+                continue;
+            };
+            if ["_settings.xml", "_zoomer.xml", "_inout.xml"].iter().any(|s| file.ends_with(s)) || tl.tracks.contains_key(name) {
+                continue;
+            }
+            let xml = std::fs::read_to_string(folder.join(file))?;
+            let json = xml_json(&xml)?;
+            tl.add(name, ProjectTrackType::infer(&json).load(&xml, &json)?);
+            if tl.pages.is_empty() {
+                tl.pages.push(TimelinePage::new("Page One"));
+            }
+            tl.pages.last_mut().unwrap().tracks.push(name.to_string());
+        }
+        Ok(tl)
+    }
+}
+
+/// `Null` for an empty file
+fn xml_json(xml: &str) -> Result<serde_json::Value, TimelineError> {
+    if xml.trim().is_empty() {
+        return Ok(serde_json::Value::Null);
+    }
+    Ok(xml_to_json::xml_str_to_json(xml)?)
+}
+
+#[derive(Deserialize)]
+struct SettingsFileEntity {
+    timeline: SettingsEntity,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingsEntity {
+    #[serde(default)]
+    name: serde_json::Value,
+    duration: Option<String>,
+    in_time: Option<String>,
+    out_time: Option<String>,
+    #[serde(default)]
+    bpm: f32,
+    #[serde(default)]
+    pages: Option<PagesEntity>,
+}
+
+/// tracks with their type and the value range of curves
+type ProjectPage = (TimelinePage, Vec<(String, ProjectTrackType, Option<ValueRange>)>);
+
+impl SettingsEntity {
+    fn into_settings(self, default_name: &str) -> Result<(ProjectSettings, Vec<ProjectPage>), TimelineError> {
+        let time = |timecode: Option<String>| -> Result<Duration, TimelineError> {
+            Ok(timecode.map(|t| timecode_to_duration(&t)).transpose()?.unwrap_or_default())
+        };
+        let name = match xml_text(self.name) {
+            name if name.is_empty() => default_name.to_string(),
+            name => name,
+        };
+        let settings = ProjectSettings {
+            name,
+            duration: time(self.duration)?,
+            in_time: time(self.in_time)?,
+            out_time: time(self.out_time)?,
+            bpm: self.bpm,
+        };
+
+        let mut pages = vec![];
+        for page in self.pages.map(|p| p.page).unwrap_or_default() {
+            let mut timeline_page = TimelinePage::new(&xml_text(page.name));
+            let mut tracks = vec![];
+            for track in page.track {
+                let name = xml_text(track.name);
+                let track_type = ProjectTrackType::from_name(&xml_text(track.r#type))?;
+                tracks.push((name.clone(), track_type, track.range.map(ValueRange::from)));
+                timeline_page.tracks.push(name);
+            }
+            pages.push((timeline_page, tracks));
+        }
+        Ok((settings, pages))
+    }
+}
+
+#[derive(Deserialize)]
+struct PagesEntity {
+    #[serde(default, deserialize_with = "one_or_many")]
+    page: Vec<PageEntity>,
+}
+
+#[derive(Deserialize)]
+struct PageEntity {
+    #[serde(default)]
+    name: serde_json::Value,
+    #[serde(default, deserialize_with = "one_or_many")]
+    track: Vec<PageTrackEntity>,
+}
+
+#[derive(Deserialize)]
+struct PageTrackEntity {
+    name: serde_json::Value,
+    r#type: serde_json::Value,
+    /// curves only, overrides the range of the track file
+    #[serde(default)]
+    range: Option<RangeEntity>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TrackGetter;
+    use crate::test_util::{fixture, ms, temp_path};
+
+    use super::*;
+
+    fn create_folder(name: &str, files: &[(&str, &str)]) -> String {
+        let folder = temp_path(name);
+        std::fs::create_dir_all(&folder).unwrap();
+        for (file, xml) in files {
+            std::fs::write(folder.join(file), xml).unwrap();
+        }
+        folder.to_str().unwrap().to_string()
+    }
+
+    const CURVES: &str = "<keyframes><key><time>00:00:01:000</time><value>0.500000000</value><easefunc>0</easefunc><easetype>0</easetype></key></keyframes>";
+    const SWITCHES: &str = "<keyframes><key><time>00:00:00:100</time><value>0</value><startTime>00:00:00:100</startTime><endTime>00:00:00:200</endTime><switchName>on</switchName></key></keyframes>";
+    const BANGS: &str = "<keyframes><key><time>00:00:00:300</time><value>0.500000000</value></key></keyframes>";
+    const FLAGS: &str = "<keyframes><key><time>00:00:00:400</time><value>0</value><flag>go</flag></key></keyframes>";
+    const COLORS: &str = "<keyframes><key><time>00:00:00:000</time><colorx>0.1</colorx><colory>0.5</colory><color>255, 0, 0, 255</color></key></keyframes>";
+
+    #[test]
+    fn load_project_test() {
+        let settings = r#"
+<timeline>
+    <name>show</name>
+    <duration>00:01:00:000</duration>
+    <inTime>00:00:10:000</inTime>
+    <outTime>00:00:50:000</outTime>
+    <bpm>120</bpm>
+    <pages>
+        <page>
+            <name>Lights</name>
+            <track><name>dimmer</name><type>Curves</type><range><min>0</min><max>255</max></range></track>
+            <track><name>strobe</name><type>Switches</type></track>
+            <track><name>empty</name><type>Flags</type></track>
+        </page>
+        <page>
+            <name>Cues</name>
+            <track><name>cues</name><type>Flags</type></track>
+        </page>
+    </pages>
+</timeline>"#;
+        let folder = create_folder("project", &[
+            ("show_settings.xml", settings),
+            ("show_dimmer.xml", CURVES),
+            ("show_strobe.xml", SWITCHES),
+            ("show_cues.xml", FLAGS),
+            ("show_tint.xml", COLORS),
+            ("show_zoomer.xml", "<zoom><min>0</min><max>1</max></zoom>"),
+            ("other_dimmer.xml", CURVES),
+        ]);
+
+        let tl = Timeline::load_project(&folder).unwrap();
+        assert_eq!(tl.settings, ProjectSettings {
+            name: "show".to_string(),
+            duration: ms(60_000),
+            in_time: ms(10_000),
+            out_time: ms(50_000),
+            bpm: 120.0,
+        });
+        assert_eq!(tl.pages, vec![
+            TimelinePage { name: "Lights".to_string(), tracks: vec!["dimmer".to_string(), "strobe".to_string(), "empty".to_string()] },
+            // tracks not in the settings are added to the last page
+            TimelinePage { name: "Cues".to_string(), tracks: vec!["cues".to_string(), "tint".to_string()] },
+        ]);
+        assert_eq!(tl.tracks.len(), 5);
+        assert_eq!(tl.get("dimmer").unwrap().as_float_track().keyframes[0].value, 0.5);
+        assert_eq!(tl.try_get_mapped_value("dimmer", ms(1000)), Ok(127.5));
+        assert!(tl.get("strobe").unwrap().as_switch_track().is_on(ms(150)));
+        assert!(tl.get("empty").unwrap().as_event_track().get_events().is_empty());
+        assert_eq!(tl.get("cues").unwrap().as_event_track().get_events()[0].label, "go");
+        assert_eq!(tl.get("tint").unwrap().as_color_track().track.keyframes[0].value, Color::rgb(1.0, 0.0, 0.0));
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn load_project_without_settings_test() {
+        let folder = create_folder("project_discover", &[
+            ("a.xml", CURVES),
+            ("b.xml", BANGS),
+            ("c.xml", ""),
+            ("readme.txt", "not a track"),
+        ]);
+
+        let tl = Timeline::load_project(&folder).unwrap();
+        assert_eq!(tl.settings, ProjectSettings::default());
+        assert_eq!(tl.pages.len(), 1);
+        assert_eq!(tl.pages[0].tracks, vec!["a", "b", "c"]);
+        assert_eq!(tl.get("b").unwrap().as_event_track().get_events()[0].time, ms(300));
+        assert!(tl.get("c").unwrap().as_float_track().keyframes.is_empty());
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn load_project_unknown_type_test() {
+        let settings = "<timeline><pages><page><name>p</name><track><name>x</name><type>Audio</type></track></page></pages></timeline>";
+        let folder = create_folder("project_unknown", &[("t_settings.xml", settings)]);
+        let err = Timeline::load_project(&folder).unwrap_err();
+        assert_eq!(err.to_string(), "unknown track type: Audio");
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn load_project_fixture_test() {
+        let tl = Timeline::load_project(fixture("project")).unwrap();
+        assert_eq!(tl.settings.name, "demo");
+        assert_eq!(tl.settings.bpm, 90.0);
+        assert_eq!(tl.tempo_map, Some(TempoMap::new(90.0, TimeSignature::default())));
+        assert_eq!(tl.pages, vec![TimelinePage { name: "Page One".to_string(), tracks: vec!["curves".to_string(), "flags".to_string()] }]);
+        assert_eq!(tl.get("curves").unwrap().as_float_track().keyframes.len(), 3);
+        assert_eq!(tl.get("flags").unwrap().as_event_track().get_events().len(), 2);
+    }
+}
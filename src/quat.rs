@@ -0,0 +1,327 @@
+use std::ops::{Mul, Neg};
+
+use serde::{Deserialize, Deserializer};
+
+/// Unit quaternion used by rotation tracks
+///
+/// Interpolated with slerp along the shortest arc. Can be deserialized from
+/// a quaternion `{x, y, z, w}`, Euler angles in degrees `{x, y, z}`,
+/// or an array of either (`[x, y, z, w]` / `[x, y, z]`).
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Quat::IDENTITY
+    }
+}
+
+impl Quat {
+    pub const IDENTITY: Quat = Quat { x: 0.0, y: 0.0, z: 0.0, w: 1.0 };
+
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Quat {
+        Quat { x, y, z, w }
+    }
+
+    /// rotation around X, then Y, then Z (fixed axes, same as `glm::quat(vec3)`), in radians
+    pub fn from_euler(x: f32, y: f32, z: f32) -> Quat {
+        let (sx, cx) = (x * 0.5).sin_cos();
+        let (sy, cy) = (y * 0.5).sin_cos();
+        let (sz, cz) = (z * 0.5).sin_cos();
+        Quat {
+            x: sx * cy * cz - cx * sy * sz,
+            y: cx * sy * cz + sx * cy * sz,
+            z: cx * cy * sz - sx * sy * cz,
+            w: cx * cy * cz + sx * sy * sz,
+        }
+    }
+
+    /// same as `from_euler`, in degrees
+    pub fn from_euler_degrees(x: f32, y: f32, z: f32) -> Quat {
+        Quat::from_euler(x.to_radians(), y.to_radians(), z.to_radians())
+    }
+
+    /// `axis` does not need to be normalized
+    pub fn from_axis_angle(axis: (f32, f32, f32), angle: f32) -> Quat {
+        let length = (axis.0 * axis.0 + axis.1 * axis.1 + axis.2 * axis.2).sqrt();
+        if length == 0.0 {
+            return Quat::IDENTITY;
+        }
+        let (s, c) = (angle * 0.5).sin_cos();
+        let s = s / length;
+        Quat::new(axis.0 * s, axis.1 * s, axis.2 * s, c)
+    }
+
+    pub fn dot(self, other: Quat) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(self) -> Quat {
+        let length = self.length();
+        if length == 0.0 {
+            return Quat::IDENTITY;
+        }
+        Quat::new(self.x / length, self.y / length, self.z / length, self.w / length)
+    }
+
+    /// inverse of a unit quaternion
+    pub fn conjugate(self) -> Quat {
+        Quat::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    /// rotation angle (radians) between two rotations, along the shortest arc
+    pub fn angle_between(self, other: Quat) -> f32 {
+        2.0 * self.dot(other).abs().min(1.0).acos()
+    }
+
+    /// spherical interpolation along the shortest arc. `t` outside 0..1 extrapolates
+    pub fn slerp(self, other: Quat, t: f32) -> Quat {
+        let mut other = other;
+        let mut dot = self.dot(other);
+        if dot < 0.0 {
+            other = -other;
+            dot = -dot;
+        }
+
+        // almost the same rotation, avoid dividing by sin(0)
+        if dot > 0.9995 {
+            return Quat::new(
+                self.x + (other.x - self.x) * t,
+                self.y + (other.y - self.y) * t,
+                self.z + (other.z - self.z) * t,
+                self.w + (other.w - self.w) * t,
+            ).normalize();
+        }
+
+        let theta = dot.min(1.0).acos();
+        let sin_theta = theta.sin();
+        let a = ((1.0 - t) * theta).sin() / sin_theta;
+        let b = (t * theta).sin() / sin_theta;
+        Quat::new(
+            self.x * a + other.x * b,
+            self.y * a + other.y * b,
+            self.z * a + other.z * b,
+            self.w * a + other.w * b,
+        )
+    }
+
+    /// the same rotation applied `exponent` times
+    pub fn powf(self, exponent: f32) -> Quat {
+        let q = if self.w < 0.0 { -self } else { self };
+        let half_angle = q.w.clamp(-1.0, 1.0).acos();
+        let s = half_angle.sin();
+        if s.abs() < 1e-6 {
+            return Quat::IDENTITY;
+        }
+        Quat::from_axis_angle((q.x / s, q.y / s, q.z / s), 2.0 * half_angle * exponent)
+    }
+}
+
+impl Mul for Quat {
+    type Output = Quat;
+
+    /// `self * rhs` applies `rhs` first, then `self`
+    fn mul(self, rhs: Quat) -> Quat {
+        Quat::new(
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        )
+    }
+}
+
+impl Neg for Quat {
+    type Output = Quat;
+
+    fn neg(self) -> Quat {
+        Quat::new(-self.x, -self.y, -self.z, -self.w)
+    }
+}
+
+/// keeps an already normalized quaternion bit-exact, so saved documents load back unchanged
+fn normalize_loaded(q: Quat) -> Quat {
+    if (q.dot(q) - 1.0).abs() < 1e-6 { q } else { q.normalize() }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum QuatEntity {
+    Quaternion { x: f32, y: f32, z: f32, w: f32 },
+    Euler { x: f32, y: f32, z: f32 },
+    Array(Vec<f32>),
+}
+
+impl<'de> Deserialize<'de> for Quat {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match QuatEntity::deserialize(deserializer)? {
+            QuatEntity::Quaternion { x, y, z, w } => Ok(normalize_loaded(Quat::new(x, y, z, w))),
+            QuatEntity::Euler { x, y, z } => Ok(Quat::from_euler_degrees(x, y, z)),
+            QuatEntity::Array(v) => match v[..] {
+                [x, y, z, w] => Ok(normalize_loaded(Quat::new(x, y, z, w))),
+                [x, y, z] => Ok(Quat::from_euler_degrees(x, y, z)),
+                _ => Err(serde::de::Error::invalid_length(v.len(), &"3 (euler) or 4 (quaternion) values")),
+            },
+        }
+    }
+}
+
+#[cfg(feature="bevy")]
+impl From<Quat> for bevy::math::Quat {
+    fn from(q: Quat) -> Self {
+        bevy::math::Quat::from_xyzw(q.x, q.y, q.z, q.w)
+    }
+}
+
+#[cfg(feature="bevy")]
+impl From<bevy::math::Quat> for Quat {
+    fn from(q: bevy::math::Quat) -> Self {
+        Quat::new(q.x, q.y, q.z, q.w)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, PI};
+    use std::time::Duration;
+
+    use assert_float_eq::assert_float_absolute_eq;
+    use assert_float_eq::afe_is_absolute_eq;
+    use assert_float_eq::afe_absolute_error_msg;
+    use assert_float_eq::afe_abs;
+
+    use crate::easing::{EasingFunction, EasingType};
+    use crate::loader::{TimelineJsonLoader, XMLTrackLoader};
+    use crate::{Keyframe, Timeline, TimelineTrack, Track, TrackGetter};
+
+    use super::*;
+
+    fn assert_quat_eq(a: Quat, b: Quat) {
+        // q and -q are the same rotation
+        assert_float_absolute_eq!(a.dot(b).abs(), 1.0, 0.0001);
+    }
+
+    #[test]
+    fn euler_test() {
+        let q = Quat::from_euler_degrees(0.0, 0.0, 90.0);
+        assert_quat_eq(q, Quat::from_axis_angle((0.0, 0.0, 1.0), FRAC_PI_2));
+
+        // X first, then Y
+        let q = Quat::from_euler_degrees(90.0, 90.0, 0.0);
+        let expected = Quat::from_axis_angle((0.0, 1.0, 0.0), FRAC_PI_2) * Quat::from_axis_angle((1.0, 0.0, 0.0), FRAC_PI_2);
+        assert_quat_eq(q, expected);
+    }
+
+    #[test]
+    fn slerp_test() {
+        let a = Quat::IDENTITY;
+        let b = Quat::from_axis_angle((0.0, 0.0, 1.0), FRAC_PI_2);
+        assert_quat_eq(a.slerp(b, 0.5), Quat::from_axis_angle((0.0, 0.0, 1.0), FRAC_PI_2 / 2.0));
+        assert_float_absolute_eq!(a.slerp(b, 0.3).length(), 1.0, 0.0001);
+    }
+
+    #[test]
+    fn slerp_shortest_path_test() {
+        let a = Quat::from_axis_angle((0.0, 0.0, 1.0), PI * 0.9);
+        let b = Quat::from_axis_angle((0.0, 0.0, 1.0), -PI * 0.9);
+        // 0.2 PI apart through 180 degrees, not 1.8 PI back through 0
+        assert_quat_eq(a.slerp(b, 0.5), Quat::from_axis_angle((0.0, 0.0, 1.0), PI));
+    }
+
+    #[test]
+    fn powf_test() {
+        let q = Quat::from_axis_angle((1.0, 0.0, 0.0), 0.5);
+        assert_quat_eq(q.powf(3.0), Quat::from_axis_angle((1.0, 0.0, 0.0), 1.5));
+        assert_quat_eq(q.powf(-1.0), q.conjugate());
+    }
+
+    #[test]
+    fn quat_track_test() {
+        let mut t = Track::<Quat>::default();
+        t.add_keyframe(Keyframe::new(Duration::from_secs(0), Quat::IDENTITY));
+        t.add_keyframe(Keyframe {
+            time: Duration::from_secs(1),
+            value: Quat::from_euler_degrees(0.0, 90.0, 0.0),
+            easing_function: EasingFunction::Linear,
+            easing_type: EasingType::In,
+            ..Default::default()
+        });
+        let q = t.get_value(Duration::from_millis(500));
+        assert_quat_eq(q, Quat::from_euler_degrees(0.0, 45.0, 0.0));
+        assert_float_absolute_eq!(q.length(), 1.0, 0.0001);
+    }
+
+    #[test]
+    fn quat_track_easing_test() {
+        let mut t = Track::<Quat>::default();
+        t.add_keyframe(Keyframe {
+            time: Duration::from_secs(0),
+            value: Quat::IDENTITY,
+            easing_function: EasingFunction::Quadratic,
+            easing_type: EasingType::In,
+            ..Default::default()
+        });
+        t.add_keyframe(Keyframe::new(Duration::from_secs(1), Quat::from_euler_degrees(0.0, 0.0, 80.0)));
+        // quadratic in: a quarter of the angle at half time
+        assert_quat_eq(t.get_value(Duration::from_millis(500)), Quat::from_euler_degrees(0.0, 0.0, 20.0));
+    }
+
+    #[test]
+    fn quat_xml_load_test() {
+        let xml = r#"
+<keyframes>
+    <key>
+        <easefunc>0</easefunc>
+        <easetype>0</easetype>
+        <time>00:00:00:000</time>
+        <value><x>0</x><y>0</y><z>0</z></value>
+    </key>
+    <key>
+        <easefunc>0</easefunc>
+        <easetype>0</easetype>
+        <time>00:00:01:000</time>
+        <value><x>90.0</x><y>0</y><z>0</z></value>
+    </key>
+</keyframes>"#;
+
+        let track = Track::<Quat>::load_xml_str(xml).unwrap();
+        assert_quat_eq(track.keyframes[1].value, Quat::from_euler_degrees(90.0, 0.0, 0.0));
+        assert_quat_eq(track.get_value(Duration::from_millis(500)), Quat::from_euler_degrees(45.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn quat_json_load_test() {
+        let q: Quat = serde_json::from_str("[0, 0, 0, 2]").unwrap();
+        assert_quat_eq(q, Quat::IDENTITY);
+        let q: Quat = serde_json::from_str(r#"{"x": 0, "y": 0, "z": 90}"#).unwrap();
+        assert_quat_eq(q, Quat::from_euler_degrees(0.0, 0.0, 90.0));
+        assert!(serde_json::from_str::<Quat>("[1, 2]").is_err());
+    }
+
+    #[test]
+    fn quat_timeline_json_load_test() {
+        let json = r#"{"keyframes": {"key": [
+            {"easefunc": 0, "easetype": 0, "time": "00:00:00:000", "value": [0, 0, 0]},
+            {"easefunc": 0, "easetype": 0, "time": "00:00:02:000", "value": [0, 0, 170]}
+        ]}}"#;
+
+        let mut tl = Timeline::new();
+        tl.load_json_str::<Quat>("rotation", json).unwrap();
+        let q: Quat = tl.get_value("rotation", Duration::from_secs(1)).into();
+        assert_quat_eq(q, Quat::from_euler_degrees(0.0, 0.0, 85.0));
+        assert!(tl.try_get("rotation").unwrap().try_as_quat_track().is_ok());
+        assert!(tl.try_get("rotation").unwrap().try_as_vec4_track().is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::easing::{self, EasingFunction, EasingType};
+use crate::{TimelineError, TimelineTrack, Track};
+
+/// Value range of a track, like the `ofRange` given to `ofxTimeline::addCurves`
+///
+/// Keyframe values are stored normalized (0..1), like in ofxTimeline XML.
+/// `get_value` returns the normalized value, `get_mapped_value` the value in `min..max`.
+///
+/// ofxTimeline and loaf_timeline do not save the range, their apps set it in code: call `set_range`
+/// after loading their files. The `<range>` element written by `Track::to_xml_string` is specific
+/// to this crate, ofxTimeline does not read it.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueRange {
+    pub min: f64,
+    pub max: f64,
+    /// display unit (`"cm"`, `"%"`), not used for sampling
+    #[serde(default)]
+    pub unit: String,
+}
+
+impl ValueRange {
+    pub fn new(min: f64, max: f64) -> ValueRange {
+        ValueRange { min, max, unit: String::new() }
+    }
+
+    /// normalized value to `min..max`, not clamped (`easing::map`)
+    pub fn map(&self, normalized: f64) -> f64 {
+        easing::map(normalized, 0.0, 1.0, self.min, self.max, EasingFunction::Linear, EasingType::In)
+    }
+
+    /// normalized value to `min..max`, clamped (`easing::map_clamp`)
+    pub fn map_clamp(&self, normalized: f64) -> f64 {
+        easing::map_clamp(normalized, 0.0, 1.0, self.min, self.max, EasingFunction::Linear, EasingType::In)
+    }
+
+    /// inverse of `map`. 0 for an empty range
+    pub fn normalize(&self, value: f64) -> f64 {
+        if self.min == self.max {
+            return 0.0;
+        }
+        easing::map(value, self.min, self.max, 0.0, 1.0, EasingFunction::Linear, EasingType::In)
+    }
+}
+
+macro_rules! impl_track_range {
+    ($t:ty) => {
+        impl Track<$t> {
+            pub fn set_range(&mut self, range: ValueRange) -> &mut Self {
+                self.range = Some(range);
+                self
+            }
+
+            /// panics if the track has no keyframes (see `try_get_mapped_value`)
+            pub fn get_mapped_value(&self, time: Duration) -> $t {
+                self.try_get_mapped_value(time).unwrap_or_else(|e| panic!("{}", e))
+            }
+
+            /// the value mapped to the range, the value itself if the track has no range
+            pub fn try_get_mapped_value(&self, time: Duration) -> Result<$t, TimelineError> {
+                let value = self.try_get_value(time)?;
+                Ok(self.range.as_ref().map_or(value, |range| range.map(value as f64) as $t))
+            }
+
+            /// same as `try_get_mapped_value`, clamped to the range
+            pub fn try_get_mapped_value_clamped(&self, time: Duration) -> Result<$t, TimelineError> {
+                let value = self.try_get_value(time)?;
+                Ok(self.range.as_ref().map_or(value, |range| range.map_clamp(value as f64) as $t))
+            }
+        }
+    };
+}
+
+impl_track_range!(f32);
+impl_track_range!(f64);
+
+#[cfg(test)]
+mod tests {
+    use crate::{Extrapolation, Keyframe, Timeline, TrackValue};
+    use crate::test_util::ms;
+
+    use super::*;
+
+    fn create_track() -> Track<f32> {
+        let mut track = Track::<f32> { post_extrapolation: Extrapolation::Linear, ..Default::default() };
+        track.add_keyframe(Keyframe::new(ms(0), 0.0))
+            .add_keyframe(Keyframe::new(ms(1000), 0.5))
+            .set_range(ValueRange { unit: "cm".to_string(), ..ValueRange::new(-100.0, 100.0) });
+        track
+    }
+
+    #[test]
+    fn value_range_test() {
+        let range = ValueRange::new(10.0, 20.0);
+        assert_eq!(range.map(0.25), 12.5);
+        assert_eq!(range.map(1.5), 25.0);
+        assert_eq!(range.map_clamp(1.5), 20.0);
+        assert_eq!(range.normalize(15.0), 0.5);
+        assert_eq!(ValueRange::new(1.0, 1.0).normalize(1.0), 0.0);
+    }
+
+    #[test]
+    fn mapped_value_test() {
+        let mut track = create_track();
+        assert_eq!(track.get_value(ms(1000)), 0.5);
+        assert_eq!(track.get_mapped_value(ms(1000)), 0.0);
+        assert_eq!(track.get_mapped_value(ms(500)), -50.0);
+        // extrapolated past 1.0
+        assert_eq!(track.get_mapped_value(ms(4000)), 300.0);
+        assert_eq!(track.try_get_mapped_value_clamped(ms(4000)), Ok(100.0));
+
+        track.range = None;
+        assert_eq!(track.get_mapped_value(ms(4000)), 2.0);
+        assert_eq!(Track::<f64>::default().try_get_mapped_value(ms(0)), Err(TimelineError::EmptyTrack));
+    }
+
+    #[test]
+    fn timeline_mapped_value_test() {
+        let mut tl = Timeline::new();
+        tl.add("float", create_track());
+        tl.add("int", Track::<i32>::default());
+        assert_eq!(tl.get_value("float", ms(500)), TrackValue::Float(0.25));
+        assert_eq!(tl.try_get_mapped_value("float", ms(500)), Ok(-50.0));
+        assert_eq!(tl.try_get_range("float").unwrap().map(|r| r.unit.as_str()), Some("cm"));
+        assert!(tl.try_get_mapped_value("int", ms(0)).is_err());
+    }
+}
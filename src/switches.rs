@@ -0,0 +1,374 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::loader::{read_to_string, duration_to_timecode, escape_xml, one_or_many, timecode_to_duration, xml_text};
+use crate::{xml_to_json, TimelineError};
+
+/// A time range where a switch is on, from `start` (inclusive) to `end` (exclusive)
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SwitchRange {
+    pub start: Duration,
+    pub end: Duration,
+    /// label shown in ofxTimeline (`switchName`)
+    pub name: String,
+}
+
+impl SwitchRange {
+    pub fn new(start: Duration, end: Duration) -> SwitchRange {
+        SwitchRange { start, end, name: String::new() }
+    }
+
+    pub fn named(start: Duration, end: Duration, name: &str) -> SwitchRange {
+        SwitchRange { start, end, name: name.to_string() }
+    }
+
+    pub fn contains(&self, time: Duration) -> bool {
+        self.start <= time && time < self.end
+    }
+}
+
+/// On/off track, stored as sorted, non-overlapping on-ranges (ofxTLSwitches)
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SwitchTrack {
+    ranges: Vec<SwitchRange>,
+}
+
+impl SwitchTrack {
+    pub fn new() -> SwitchTrack {
+        SwitchTrack { ranges: vec![] }
+    }
+
+    /// ranges overlapping or touching existing ones are merged (the first name is kept).
+    /// empty ranges are ignored
+    pub fn add_range(&mut self, range: SwitchRange) -> &mut Self {
+        if range.end <= range.start {
+            return self;
+        }
+
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+        if first == last {
+            self.ranges.insert(first, range);
+            return self;
+        }
+
+        let mut merged: Vec<SwitchRange> = self.ranges.drain(first..last).collect();
+        let start = merged[0].start.min(range.start);
+        let end = merged[merged.len() - 1].end.max(range.end);
+        let mut result = if merged[0].start <= range.start { merged.swap_remove(0) } else { range };
+        result.start = start;
+        result.end = end;
+        self.ranges.insert(first, result);
+        self
+    }
+
+    /// switches off between `start` and `end`, splitting ranges if needed
+    pub fn remove_range(&mut self, start: Duration, end: Duration) -> &mut Self {
+        if end <= start {
+            return self;
+        }
+
+        let first = self.ranges.partition_point(|r| r.end <= start);
+        let last = self.ranges.partition_point(|r| r.start < end);
+        let removed: Vec<SwitchRange> = self.ranges.drain(first..last).collect();
+        let mut rest = vec![];
+        if let Some(r) = removed.first().filter(|r| r.start < start) {
+            rest.push(SwitchRange { end: start, ..r.clone() });
+        }
+        if let Some(r) = removed.last().filter(|r| r.end > end) {
+            rest.push(SwitchRange { start: end, ..r.clone() });
+        }
+        self.ranges.splice(first..first, rest);
+        self
+    }
+
+    pub fn get_ranges(&self) -> &[SwitchRange] {
+        &self.ranges
+    }
+
+    /// end of the last range
+    pub fn get_duration(&self) -> Duration {
+        self.ranges.last().map(|r| r.end).unwrap_or_default()
+    }
+
+    /// moves range starts and ends to `snap(time)` (`FrameRate::snap`, `TempoMap::snap`).
+    /// ranges snapped to an empty range disappear, ranges meeting are merged
+    pub fn snap_times(&mut self, snap: impl Fn(Duration) -> Duration) -> &mut Self {
+        for range in std::mem::take(&mut self.ranges) {
+            self.add_range(SwitchRange { start: snap(range.start), end: snap(range.end), ..range });
+        }
+        self
+    }
+
+    pub fn is_on(&self, time: Duration) -> bool {
+        self.get_range_at(time).is_some()
+    }
+
+    pub fn get_range_at(&self, time: Duration) -> Option<&SwitchRange> {
+        let index = self.ranges.partition_point(|r| r.start <= time);
+        self.ranges[..index].last().filter(|r| r.contains(time))
+    }
+
+    /// ranges overlapping `t0..t1`
+    pub fn ranges_in(&self, t0: Duration, t1: Duration) -> &[SwitchRange] {
+        let first = self.ranges.partition_point(|r| r.end <= t0);
+        let last = self.ranges.partition_point(|r| r.start < t1);
+        &self.ranges[first..last.max(first)]
+    }
+
+    /// true if the switch turned on when moving the playhead from `t0` to `t1`.
+    ///
+    /// range starts and ends are crossed like events (`EventTrack::events_between`): forward
+    /// `t0 <= time < t1`, so a range starting at 0 switches on when playback starts at 0.
+    /// with `t1 < t0` (reverse playback) entering a range from its end counts as switching on
+    pub fn switched_on_between(&self, t0: Duration, t1: Duration) -> bool {
+        let boundary: fn(&SwitchRange) -> Duration = if t0 <= t1 { |r| r.start } else { |r| r.end };
+        self.crossed(t0, t1, boundary)
+    }
+
+    /// true if the switch turned off when moving the playhead from `t0` to `t1`, see `switched_on_between`
+    pub fn switched_off_between(&self, t0: Duration, t1: Duration) -> bool {
+        let boundary: fn(&SwitchRange) -> Duration = if t0 <= t1 { |r| r.end } else { |r| r.start };
+        self.crossed(t0, t1, boundary)
+    }
+
+    /// true if a `boundary` of a range is crossed, the ranges are sorted by both boundaries
+    fn crossed(&self, t0: Duration, t1: Duration, boundary: fn(&SwitchRange) -> Duration) -> bool {
+        if t0 <= t1 {
+            self.ranges.partition_point(|r| boundary(r) < t0) < self.ranges.partition_point(|r| boundary(r) < t1)
+        } else {
+            self.ranges.partition_point(|r| boundary(r) <= t1) < self.ranges.partition_point(|r| boundary(r) <= t0)
+        }
+    }
+
+    pub fn load_xml(xml_path: impl AsRef<Path>) -> Result<SwitchTrack, TimelineError> {
+        SwitchTrack::load_xml_reader(File::open(xml_path)?)
+    }
+
+    pub fn load_xml_reader(reader: impl Read) -> Result<SwitchTrack, TimelineError> {
+        SwitchTrack::load_xml_str(&read_to_string(reader)?)
+    }
+
+    /// ofxTLSwitches format
+    pub fn load_xml_str(xml: &str) -> Result<SwitchTrack, TimelineError> {
+        let json = xml_to_json::xml_str_to_json_with_texts(xml, &["/keyframes/key/switchName"])?;
+        let entity: SwitchesEntity = serde_json::from_value(json)?;
+
+        let mut track = SwitchTrack::new();
+        for key in entity.keyframes.map(|k| k.key).unwrap_or_default() {
+            let start = switch_time(key.start_time, key.start_millis, "startTime")?;
+            let end = switch_time(key.end_time, key.end_millis, "endTime")?;
+            track.add_range(SwitchRange { start, end, name: xml_text(key.switch_name) });
+        }
+        Ok(track)
+    }
+
+    pub fn save_xml(&self, xml_path: impl AsRef<Path>) -> Result<(), TimelineError> {
+        std::fs::write(xml_path, self.to_xml_string())?;
+        Ok(())
+    }
+
+    /// ofxTLSwitches format
+    pub fn to_xml_string(&self) -> String {
+        let mut xml = String::from("<keyframes>\n");
+        for range in &self.ranges {
+            let start = duration_to_timecode(range.start);
+            xml += "    <key>\n";
+            xml += &format!("        <time>{}</time>\n", start);
+            xml += "        <value>0.000000000</value>\n";
+            xml += &format!("        <startTime>{}</startTime>\n", start);
+            xml += &format!("        <endTime>{}</endTime>\n", duration_to_timecode(range.end));
+            xml += &format!("        <switchName>{}</switchName>\n", escape_xml(&range.name));
+            xml += "    </key>\n";
+        }
+        xml += "</keyframes>\n";
+        xml
+    }
+}
+
+/// timecode, or milliseconds as written by old ofxTimeline versions
+fn switch_time(timecode: Option<String>, millis: Option<f64>, tag: &str) -> Result<Duration, TimelineError> {
+    match (timecode, millis) {
+        (Some(timecode), _) => Ok(timecode_to_duration(&timecode)?),
+        (None, Some(millis)) => Ok(Duration::from_secs_f64(millis.max(0.0) / 1000.0)),
+        (None, None) => Err(TimelineError::InvalidData(format!("switch without {}", tag))),
+    }
+}
+
+#[derive(Deserialize)]
+struct SwitchesEntity {
+    #[serde(default)]
+    keyframes: Option<SwitchesBodyEntity>,
+}
+
+#[derive(Deserialize)]
+struct SwitchesBodyEntity {
+    #[serde(default, deserialize_with = "one_or_many")]
+    key: Vec<SwitchEntity>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SwitchEntity {
+    start_time: Option<String>,
+    end_time: Option<String>,
+    start_millis: Option<f64>,
+    end_millis: Option<f64>,
+    #[serde(default)]
+    switch_name: serde_json::Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FrameRate, Timeline, TrackGetter, TrackValue};
+    use crate::test_util::{fixture, ms};
+
+    use super::*;
+
+    fn create_track() -> SwitchTrack {
+        let mut t = SwitchTrack::new();
+        t.add_range(SwitchRange::new(ms(1000), ms(2000)))
+            .add_range(SwitchRange::named(ms(3000), ms(4000), "b"));
+        t
+    }
+
+    #[test]
+    fn is_on_test() {
+        let t = create_track();
+        assert!(!t.is_on(ms(999)));
+        assert!(t.is_on(ms(1000)));
+        assert!(t.is_on(ms(1999)));
+        assert!(!t.is_on(ms(2000)));
+        assert!(!t.is_on(ms(2500)));
+        assert_eq!(t.get_range_at(ms(3500)).unwrap().name, "b");
+        assert!(!t.is_on(ms(5000)));
+        assert!(!SwitchTrack::new().is_on(ms(0)));
+    }
+
+    #[test]
+    fn add_range_merge_test() {
+        let mut t = create_track();
+        t.add_range(SwitchRange::named(ms(1500), ms(3000), "a"));
+        assert_eq!(t.get_ranges(), &[SwitchRange::new(ms(1000), ms(4000))]);
+
+        t.add_range(SwitchRange::new(ms(0), ms(100)));
+        t.add_range(SwitchRange::new(ms(5000), ms(5000)));
+        assert_eq!(t.get_ranges().len(), 2);
+        assert_eq!(t.get_duration(), ms(4000));
+    }
+
+    #[test]
+    fn snap_to_frames_test() {
+        let mut t = create_track();
+        t.add_range(SwitchRange::new(ms(2010), ms(2030)))
+            .add_range(SwitchRange::new(ms(5005), ms(5015)));
+        t.snap_times(|time| FrameRate::Fps25.snap(time));
+        // 2010..2030 snaps to 2000..2040 and is merged, 5005..5015 snaps to an empty range
+        assert_eq!(t.get_ranges(), &[SwitchRange::new(ms(1000), ms(2040)), SwitchRange::named(ms(3000), ms(4000), "b")]);
+    }
+
+    #[test]
+    fn remove_range_test() {
+        let mut t = create_track();
+        t.remove_range(ms(1500), ms(3500));
+        assert_eq!(t.get_ranges(), &[
+            SwitchRange::new(ms(1000), ms(1500)),
+            SwitchRange::named(ms(3500), ms(4000), "b"),
+        ]);
+
+        t.remove_range(ms(0), ms(10000));
+        assert!(t.get_ranges().is_empty());
+    }
+
+    #[test]
+    fn ranges_in_test() {
+        let t = create_track();
+        assert_eq!(t.ranges_in(ms(0), ms(1000)).len(), 0);
+        assert_eq!(t.ranges_in(ms(0), ms(1001)).len(), 1);
+        assert_eq!(t.ranges_in(ms(1500), ms(3500)).len(), 2);
+        assert_eq!(t.ranges_in(ms(2000), ms(3000)).len(), 0);
+        assert_eq!(t.ranges_in(ms(3500), ms(3600))[0].name, "b");
+    }
+
+    #[test]
+    fn switched_between_test() {
+        let t = create_track();
+        // half-open like events: the start is crossed by the update starting there
+        assert!(!t.switched_on_between(ms(500), ms(1000)));
+        assert!(t.switched_on_between(ms(1000), ms(1500)));
+        assert!(!t.switched_on_between(ms(1500), ms(2500)));
+        assert!(t.switched_off_between(ms(1500), ms(2500)));
+        assert!(!t.switched_off_between(ms(2500), ms(2900)));
+
+        // reverse playback
+        assert!(t.switched_on_between(ms(4500), ms(3500)));
+        assert!(!t.switched_off_between(ms(4500), ms(3500)));
+        assert!(t.switched_off_between(ms(3500), ms(2500)));
+        assert!(t.switched_off_between(ms(3000), ms(2500)));
+        assert!(!t.switched_on_between(ms(3000), ms(2500)));
+
+        // a range at the start switches on when playback starts at 0
+        let mut t = SwitchTrack::new();
+        t.add_range(SwitchRange::new(ms(0), ms(1000)));
+        assert!(t.switched_on_between(ms(0), ms(100)));
+        assert!(!t.switched_on_between(ms(0), ms(0)));
+        assert!(t.switched_off_between(ms(900), ms(1100)));
+    }
+
+    #[test]
+    fn xml_round_trip_test() {
+        let mut t = create_track();
+        t.add_range(SwitchRange::named(ms(3_723_456), ms(3_800_000), "<a & b>"))
+            .add_range(SwitchRange::named(ms(4_000_000), ms(4_100_000), "007"))
+            .add_range(SwitchRange::named(ms(4_200_000), ms(4_300_000), "1.50 "));
+        let xml = t.to_xml_string();
+        assert!(xml.contains("<startTime>01:02:03:456</startTime>"));
+        assert_eq!(SwitchTrack::load_xml_str(&xml).unwrap(), t);
+    }
+
+    #[test]
+    fn xml_load_test() {
+        let xml = r#"
+<keyframes>
+    <key>
+        <time>00:00:00:500</time>
+        <value>0.000000000</value>
+        <startTime>00:00:00:500</startTime>
+        <endTime>00:00:01:250</endTime>
+        <switchName>1</switchName>
+    </key>
+</keyframes>"#;
+
+        let t = SwitchTrack::load_xml_str(xml).unwrap();
+        assert_eq!(t.get_ranges(), &[SwitchRange::named(ms(500), ms(1250), "1")]);
+
+        let legacy = r#"<keyframes><key><startMillis>100</startMillis><endMillis>200</endMillis></key></keyframes>"#;
+        let t = SwitchTrack::load_xml_str(legacy).unwrap();
+        assert_eq!(t.get_ranges(), &[SwitchRange::new(ms(100), ms(200))]);
+
+        assert!(SwitchTrack::load_xml_str("<keyframes></keyframes>").unwrap().get_ranges().is_empty());
+        assert!(SwitchTrack::load_xml_str("<keyframes><key><endTime>00:00:01:000</endTime></key></keyframes>").is_err());
+    }
+
+    #[test]
+    fn timeline_switch_test() {
+        let mut tl = Timeline::new();
+        tl.add("switch", create_track());
+        assert_eq!(tl.get_value("switch", ms(1500)), TrackValue::Bool(true));
+        assert_eq!(tl.get_value("switch", ms(2500)), TrackValue::Bool(false));
+        assert_eq!(tl.get_max_duration(), ms(4000));
+        assert!(tl.try_get("switch").unwrap().as_switch_track().is_on(ms(3000)));
+    }
+
+    #[test]
+    fn xml_load_file_test() {
+        let path = fixture("switches.xml");
+        let t = SwitchTrack::load_xml(&path).unwrap();
+        assert_eq!(t.get_ranges(), &[SwitchRange::named(ms(1000), ms(2500), "intro")]);
+        assert_eq!(SwitchTrack::load_xml_reader(File::open(path).unwrap()).unwrap(), t);
+    }
+}
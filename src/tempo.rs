@@ -0,0 +1,334 @@
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::TimelineError;
+
+/// Subdivisions of a beat in `MusicalTime::tick` (the MIDI PPQ most sequencers use)
+pub const TICKS_PER_BEAT: u32 = 480;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TimeSignature {
+    /// beats per bar
+    pub numerator: u32,
+    /// note value of a beat (4 for quarter notes)
+    pub denominator: u32,
+}
+
+impl TimeSignature {
+    pub fn new(numerator: u32, denominator: u32) -> TimeSignature {
+        TimeSignature { numerator, denominator }
+    }
+}
+
+/// 4/4
+impl Default for TimeSignature {
+    fn default() -> Self {
+        TimeSignature::new(4, 4)
+    }
+}
+
+/// Tempo and time signature from the start of `bar` on
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TempoChange {
+    /// 0-based
+    pub bar: u32,
+    /// beats (of the time signature) per minute
+    pub bpm: f64,
+    pub time_signature: TimeSignature,
+}
+
+/// A position in bars, beats and ticks, all 0-based (displayed 1-based, `1.1.000`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MusicalTime {
+    pub bar: u32,
+    pub beat: u32,
+    /// `0..TICKS_PER_BEAT`
+    pub tick: u32,
+}
+
+impl MusicalTime {
+    pub fn new(bar: u32, beat: u32, tick: u32) -> MusicalTime {
+        MusicalTime { bar, beat, tick }
+    }
+}
+
+impl fmt::Display for MusicalTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{:03}", self.bar + 1, self.beat + 1, self.tick)
+    }
+}
+
+/// Tempo changes of a `Timeline`, to convert between time and bars/beats
+///
+/// Changes happen at bar starts, the first one at bar 0. Beat positions (`get_beat`) count
+/// the beats of each time signature from the start of the timeline.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TempoMap {
+    changes: Vec<TempoChange>,
+}
+
+/// a tempo change with its start in beats and seconds
+struct Segment {
+    change: TempoChange,
+    beat: f64,
+    seconds: f64,
+}
+
+impl TempoMap {
+    /// constant tempo. panics on an invalid tempo or time signature (see `try_new`)
+    pub fn new(bpm: f64, time_signature: TimeSignature) -> TempoMap {
+        TempoMap::try_new(bpm, time_signature).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// `TimelineError::InvalidData` if `bpm` is not finite and positive, or the time signature has a zero
+    pub fn try_new(bpm: f64, time_signature: TimeSignature) -> Result<TempoMap, TimelineError> {
+        let change = TempoChange { bar: 0, bpm, time_signature };
+        change.validate()?;
+        Ok(TempoMap { changes: vec![change] })
+    }
+
+    /// panics on an invalid tempo or time signature (see `try_add_change`)
+    pub fn add_change(&mut self, bar: u32, bpm: f64, time_signature: TimeSignature) -> &mut Self {
+        self.try_add_change(bar, bpm, time_signature).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// replaces the change at the same bar (at bar 0, the initial tempo). validated like `try_new`
+    pub fn try_add_change(&mut self, bar: u32, bpm: f64, time_signature: TimeSignature) -> Result<&mut Self, TimelineError> {
+        let change = TempoChange { bar, bpm, time_signature };
+        change.validate()?;
+        match self.changes.binary_search_by_key(&bar, |c| c.bar) {
+            Ok(index) => self.changes[index] = change,
+            Err(index) => self.changes.insert(index, change),
+        }
+        Ok(self)
+    }
+
+    pub fn get_changes(&self) -> &[TempoChange] {
+        &self.changes
+    }
+
+    /// tempo change in effect at `time`
+    pub fn get_change_at(&self, time: Duration) -> &TempoChange {
+        let seconds = time.as_secs_f64();
+        let segments = self.get_segments();
+        let index = segments.partition_point(|s| s.seconds <= seconds).max(1) - 1;
+        &self.changes[index]
+    }
+
+    /// beats since the start of the timeline, fractional
+    pub fn get_beat(&self, time: Duration) -> f64 {
+        let seconds = time.as_secs_f64();
+        let segment = self.find_segment(|s| s.seconds <= seconds);
+        segment.beat + (seconds - segment.seconds) * segment.change.bpm / 60.0
+    }
+
+    /// panics if the time does not fit in a `Duration` (see `try_get_time`)
+    pub fn get_time(&self, beat: f64) -> Duration {
+        self.try_get_time(beat).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// inverse of `get_beat`. negative beats are clamped to zero, `TimelineError::InvalidData` past `Duration::MAX`
+    pub fn try_get_time(&self, beat: f64) -> Result<Duration, TimelineError> {
+        let segment = self.find_segment(|s| s.beat <= beat);
+        let seconds = segment.seconds + (beat - segment.beat) * 60.0 / segment.change.bpm;
+        Duration::try_from_secs_f64(seconds.max(0.0))
+            .map_err(|_| TimelineError::InvalidData(format!("beat out of range: {}", beat)))
+    }
+
+    /// bar, beat and tick at `time`, the tick rounded to the nearest
+    pub fn get_position(&self, time: Duration) -> MusicalTime {
+        let beat = self.get_beat(time);
+        let segment = self.find_segment(|s| s.beat <= beat);
+        let ticks = ((beat - segment.beat) * TICKS_PER_BEAT as f64).round().max(0.0) as u64;
+        let ticks_per_bar = segment.change.time_signature.numerator as u64 * TICKS_PER_BEAT as u64;
+        MusicalTime {
+            bar: segment.change.bar.saturating_add((ticks / ticks_per_bar).try_into().unwrap_or(u32::MAX)),
+            beat: (ticks % ticks_per_bar / TICKS_PER_BEAT as u64) as u32,
+            tick: (ticks % TICKS_PER_BEAT as u64) as u32,
+        }
+    }
+
+    /// panics if the time does not fit in a `Duration` (see `try_get_position_time`)
+    pub fn get_position_time(&self, position: MusicalTime) -> Duration {
+        self.try_get_position_time(position).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// inverse of `get_position`. beats and ticks past the end of the bar carry over
+    pub fn try_get_position_time(&self, position: MusicalTime) -> Result<Duration, TimelineError> {
+        let segment = self.find_segment(|s| s.change.bar <= position.bar);
+        let bars = (position.bar - segment.change.bar) as f64;
+        let beats = bars * segment.change.time_signature.numerator as f64
+            + position.beat as f64
+            + position.tick as f64 / TICKS_PER_BEAT as f64;
+        self.try_get_time(segment.beat + beats)
+    }
+
+    /// nearest `1 / subdivision` of a beat (1 for beats, 2 for half beats, ...), `Duration::MAX` past its last beat
+    pub fn snap(&self, time: Duration, subdivision: u32) -> Duration {
+        let subdivision = subdivision.max(1) as f64;
+        self.try_get_time((self.get_beat(time) * subdivision).round() / subdivision).unwrap_or(Duration::MAX)
+    }
+
+    fn get_segments(&self) -> Vec<Segment> {
+        let mut segments: Vec<Segment> = Vec::with_capacity(self.changes.len());
+        for change in &self.changes {
+            let (beat, seconds) = match segments.last() {
+                Some(prev) => {
+                    let beats = (change.bar - prev.change.bar) as f64 * prev.change.time_signature.numerator as f64;
+                    (prev.beat + beats, prev.seconds + beats * 60.0 / prev.change.bpm)
+                }
+                None => (0.0, 0.0),
+            };
+            segments.push(Segment { change: *change, beat, seconds });
+        }
+        segments
+    }
+
+    /// last segment matching `starts_before`, the first if none does
+    fn find_segment(&self, starts_before: impl Fn(&Segment) -> bool) -> Segment {
+        let mut segments = self.get_segments();
+        let index = segments.partition_point(starts_before).max(1) - 1;
+        segments.swap_remove(index)
+    }
+}
+
+impl TempoChange {
+    fn validate(&self) -> Result<(), TimelineError> {
+        if !(self.bpm.is_finite() && self.bpm > 0.0) {
+            return Err(TimelineError::InvalidData(format!("invalid tempo: {} bpm", self.bpm)));
+        }
+        let TimeSignature { numerator, denominator } = self.time_signature;
+        if numerator == 0 || denominator == 0 {
+            return Err(TimelineError::InvalidData(format!("invalid time signature: {}/{}", numerator, denominator)));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct TempoMapEntity {
+    changes: Vec<TempoChange>,
+}
+
+/// validated like `try_new` / `try_add_change`: at least one change, the first at bar 0, bars increasing
+impl<'de> Deserialize<'de> for TempoMap {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let TempoMapEntity { changes } = TempoMapEntity::deserialize(deserializer)?;
+        if changes.first().map(|c| c.bar) != Some(0) {
+            return Err(serde::de::Error::custom("a tempo map needs a change at bar 0"));
+        }
+        if changes.windows(2).any(|w| w[0].bar >= w[1].bar) {
+            return Err(serde::de::Error::custom("tempo changes must be sorted by bar"));
+        }
+        for change in &changes {
+            change.validate().map_err(serde::de::Error::custom)?;
+        }
+        Ok(TempoMap { changes })
+    }
+}
+
+/// 120 bpm, 4/4
+impl Default for TempoMap {
+    fn default() -> Self {
+        TempoMap::new(120.0, TimeSignature::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_float_eq::{afe_abs, afe_absolute_error_msg, afe_is_absolute_eq, assert_float_absolute_eq};
+    use crate::test_util::ms;
+
+    use super::*;
+
+    /// 2 bars of 4/4 at 120 bpm, then 3/4 at 60 bpm from bar 2
+    fn create_map() -> TempoMap {
+        let mut map = TempoMap::default();
+        map.add_change(2, 60.0, TimeSignature::new(3, 4));
+        map
+    }
+
+    #[test]
+    fn constant_tempo_test() {
+        let map = TempoMap::new(90.0, TimeSignature::default());
+        assert_float_absolute_eq!(map.get_beat(ms(2000)), 3.0, 1e-9);
+        assert_eq!(map.get_time(3.0), ms(2000));
+        assert_eq!(map.get_position(ms(3000)), MusicalTime::new(1, 0, 240));
+        assert_eq!(map.get_position(ms(3000)).to_string(), "2.1.240");
+        assert_eq!(map.get_position_time(MusicalTime::new(1, 0, 240)), ms(3000));
+    }
+
+    #[test]
+    fn tempo_change_test() {
+        let map = create_map();
+        // bar 2 starts after 8 beats at 120 bpm
+        assert_eq!(map.get_position_time(MusicalTime::new(2, 0, 0)), ms(4000));
+        assert_float_absolute_eq!(map.get_beat(ms(5500)), 9.5, 1e-9);
+        assert_eq!(map.get_time(9.5), ms(5500));
+        assert_eq!(map.get_position(ms(8000)), MusicalTime::new(3, 1, 0));
+        assert_eq!(map.get_position_time(MusicalTime::new(3, 1, 0)), ms(8000));
+        assert_eq!(map.get_change_at(ms(3999)).bpm, 120.0);
+        assert_eq!(map.get_change_at(ms(4000)).bpm, 60.0);
+
+        let mut map = map;
+        map.add_change(0, 60.0, TimeSignature::default());
+        assert_eq!(map.get_changes().len(), 2);
+        assert_eq!(map.get_position_time(MusicalTime::new(2, 0, 0)), ms(8000));
+    }
+
+    #[test]
+    fn snap_test() {
+        let map = create_map();
+        assert_eq!(map.snap(ms(1100), 1), ms(1000));
+        assert_eq!(map.snap(ms(1100), 4), ms(1125));
+        assert_eq!(map.snap(ms(4600), 2), ms(4500));
+        assert_eq!(map.snap(ms(4600), 0), ms(5000));
+        assert_eq!(map.snap(Duration::MAX, 1), Duration::MAX);
+    }
+
+    #[test]
+    fn invalid_tempo_test() {
+        for bpm in [0.0, -60.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(TempoMap::try_new(bpm, TimeSignature::default()), Err(TimelineError::InvalidData(_))));
+        }
+        assert!(TempoMap::try_new(120.0, TimeSignature::new(0, 4)).is_err());
+
+        let mut map = create_map();
+        assert!(map.try_add_change(4, 0.0, TimeSignature::default()).is_err());
+        assert!(map.try_add_change(4, 60.0, TimeSignature::new(0, 4)).is_err());
+        assert_eq!(map, create_map());
+
+        let map = TempoMap::default();
+        assert!(map.try_get_time(1e300).is_err());
+        assert!(map.try_get_time(f64::INFINITY).is_err());
+        assert_eq!(map.try_get_time(f64::NAN), Ok(ms(0)));
+        assert!(map.try_get_position_time(MusicalTime::new(u32::MAX, 0, 0)).is_ok());
+        assert!(TempoMap::new(1e-300, TimeSignature::default()).try_get_position_time(MusicalTime::new(1, 0, 0)).is_err());
+        assert_eq!(TempoMap::new(1e-300, TimeSignature::default()).get_position(ms(1000)), MusicalTime::new(0, 0, 0));
+        assert_eq!(TempoMap::new(1e300, TimeSignature::default()).get_position(ms(1000)).bar, u32::MAX);
+    }
+
+    #[test]
+    fn deserialize_test() {
+        let map = create_map();
+        assert_eq!(serde_json::from_str::<TempoMap>(&serde_json::to_string(&map).unwrap()).unwrap(), map);
+
+        let change = |bar: u32, bpm: f64, numerator: u32| {
+            format!(r#"{{"bar": {}, "bpm": {}, "time_signature": {{"numerator": {}, "denominator": 4}}}}"#, bar, bpm, numerator)
+        };
+        for changes in [
+            String::new(),
+            change(1, 120.0, 4),
+            change(0, 0.0, 4),
+            change(0, 120.0, 0),
+            format!("{}, {}", change(0, 120.0, 4), change(0, 60.0, 4)),
+        ] {
+            assert!(serde_json::from_str::<TempoMap>(&format!(r#"{{"changes": [{}]}}"#, changes)).is_err(), "{}", changes);
+        }
+    }
+}
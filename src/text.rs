@@ -0,0 +1,296 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::loader::{read_to_string, duration_to_timecode, escape_xml, one_or_many, timecode_to_duration, xml_name, xml_text};
+use crate::{xml_to_json, TimelineError};
+
+/// How a text keyframe changes into the next one
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextInterpolation {
+    /// hold the text until the next keyframe
+    #[default]
+    Step,
+    /// typewriter: erase the characters not shared with the next text, then type the next text
+    Reveal,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TextKeyframe {
+    pub time: Duration,
+    pub text: String,
+    /// applies from this keyframe to the next one
+    pub interpolation: TextInterpolation,
+}
+
+impl TextKeyframe {
+    pub fn new(time: Duration, text: &str) -> TextKeyframe {
+        TextKeyframe { time, text: text.to_string(), interpolation: TextInterpolation::Step }
+    }
+
+    pub fn reveal(time: Duration, text: &str) -> TextKeyframe {
+        TextKeyframe { time, text: text.to_string(), interpolation: TextInterpolation::Reveal }
+    }
+}
+
+/// Track of strings (subtitles, labels), sorted by time
+///
+/// Before the first keyframe the first text is held, after the last keyframe the last one.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TextTrack {
+    keyframes: Vec<TextKeyframe>,
+}
+
+impl TextTrack {
+    pub fn new() -> TextTrack {
+        TextTrack { keyframes: vec![] }
+    }
+
+    /// keyframes at the same time keep their insertion order
+    pub fn add_keyframe(&mut self, keyframe: TextKeyframe) -> &mut Self {
+        let index = self.keyframes.partition_point(|k| k.time <= keyframe.time);
+        self.keyframes.insert(index, keyframe);
+        self
+    }
+
+    pub fn get_keyframes(&self) -> &[TextKeyframe] {
+        &self.keyframes
+    }
+
+    pub fn get_duration(&self) -> Duration {
+        self.keyframes.last().map(|k| k.time).unwrap_or_default()
+    }
+
+    /// moves every keyframe to `snap(time)` (`FrameRate::snap`, `TempoMap::snap`)
+    pub fn snap_times(&mut self, snap: impl Fn(Duration) -> Duration) -> &mut Self {
+        for keyframe in &mut self.keyframes {
+            keyframe.time = snap(keyframe.time);
+        }
+        self
+    }
+
+    /// panics if the track has no keyframes (see `try_get_value`)
+    pub fn get_value(&self, time: Duration) -> &str {
+        self.try_get_value(time).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_value(&self, time: Duration) -> Result<&str, TimelineError> {
+        let index = self.keyframes.partition_point(|k| k.time <= time);
+        let Some(prev) = self.keyframes[..index].last() else {
+            return self.keyframes.first().map(|k| k.text.as_str()).ok_or(TimelineError::EmptyTrack);
+        };
+        let Some(next) = self.keyframes.get(index) else {
+            return Ok(&prev.text);
+        };
+
+        match prev.interpolation {
+            TextInterpolation::Step => Ok(&prev.text),
+            TextInterpolation::Reveal => {
+                let progress = (time - prev.time).as_secs_f64() / (next.time - prev.time).as_secs_f64();
+                Ok(reveal(&prev.text, &next.text, progress))
+            }
+        }
+    }
+
+    pub fn load_xml(xml_path: impl AsRef<Path>) -> Result<TextTrack, TimelineError> {
+        TextTrack::load_xml_reader(File::open(xml_path)?)
+    }
+
+    pub fn load_xml_reader(reader: impl Read) -> Result<TextTrack, TimelineError> {
+        TextTrack::load_xml_str(&read_to_string(reader)?)
+    }
+
+    pub fn load_xml_str(xml: &str) -> Result<TextTrack, TimelineError> {
+        TextTrack::from_json_value(xml_to_json::xml_str_to_json_with_texts(xml, &["/keyframes/key/value"])?)
+    }
+
+    pub fn load_json(json_path: impl AsRef<Path>) -> Result<TextTrack, TimelineError> {
+        TextTrack::load_json_reader(File::open(json_path)?)
+    }
+
+    pub fn load_json_reader(reader: impl Read) -> Result<TextTrack, TimelineError> {
+        TextTrack::from_json_value(serde_json::from_reader(reader)?)
+    }
+
+    /// same layout as the XML: `{"keyframes": {"key": [{"time": "00:00:01:000", "value": "text", "interpolation": "reveal"}]}}`
+    pub fn load_json_str(json: &str) -> Result<TextTrack, TimelineError> {
+        TextTrack::from_json_value(serde_json::from_str(json)?)
+    }
+
+    pub fn save_xml(&self, xml_path: impl AsRef<Path>) -> Result<(), TimelineError> {
+        std::fs::write(xml_path, self.to_xml_string())?;
+        Ok(())
+    }
+
+    /// same layout as `load_xml_str`
+    pub fn to_xml_string(&self) -> String {
+        let mut xml = String::from("<keyframes>\n");
+        for keyframe in &self.keyframes {
+            xml += "    <key>\n";
+            xml += &format!("        <time>{}</time>\n", duration_to_timecode(keyframe.time));
+            xml += &format!("        <value>{}</value>\n", escape_xml(&keyframe.text));
+            if keyframe.interpolation != TextInterpolation::Step {
+                xml += &format!("        <interpolation>{}</interpolation>\n", xml_name(keyframe.interpolation));
+            }
+            xml += "    </key>\n";
+        }
+        xml += "</keyframes>\n";
+        xml
+    }
+
+    fn from_json_value(json: serde_json::Value) -> Result<TextTrack, TimelineError> {
+        let entity: TextKeyframesEntity = serde_json::from_value(json)?;
+        let mut track = TextTrack::new();
+        for key in entity.keyframes.key {
+            track.add_keyframe(TextKeyframe {
+                time: timecode_to_duration(&key.time)?,
+                text: xml_text(key.value),
+                interpolation: key.interpolation,
+            });
+        }
+        Ok(track)
+    }
+}
+
+/// the state of a typewriter going from `from` to `to`, `progress` in 0..1.
+/// always a prefix of one of the two texts
+fn reveal<'a>(from: &'a str, to: &'a str, progress: f64) -> &'a str {
+    let common = from.chars().zip(to.chars()).take_while(|(a, b)| a == b).count();
+    let erase = from.chars().count() - common;
+    let write = to.chars().count() - common;
+    let steps = ((erase + write) as f64 * progress.clamp(0.0, 1.0)).floor() as usize;
+
+    let prefix = |s: &'a str, chars: usize| s.char_indices().nth(chars).map_or(s, |(i, _)| &s[..i]);
+    if steps <= erase {
+        prefix(from, common + erase - steps)
+    } else {
+        prefix(to, common + steps - erase)
+    }
+}
+
+#[derive(Deserialize)]
+struct TextKeyframesEntity {
+    keyframes: TextKeyframesBodyEntity,
+}
+
+#[derive(Deserialize)]
+struct TextKeyframesBodyEntity {
+    #[serde(default, deserialize_with = "one_or_many")]
+    key: Vec<TextKeyframeEntity>,
+}
+
+#[derive(Deserialize)]
+struct TextKeyframeEntity {
+    time: String,
+    #[serde(default)]
+    value: serde_json::Value,
+    #[serde(default)]
+    interpolation: TextInterpolation,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Timeline;
+    use crate::test_util::{fixture, ms};
+
+    use super::*;
+
+    #[test]
+    fn step_test() {
+        let mut t = TextTrack::new();
+        t.add_keyframe(TextKeyframe::new(ms(1000), "second"))
+            .add_keyframe(TextKeyframe::new(ms(0), "first"));
+        assert_eq!(t.get_value(ms(0)), "first");
+        assert_eq!(t.get_value(ms(999)), "first");
+        assert_eq!(t.get_value(ms(1000)), "second");
+        assert_eq!(t.get_value(ms(5000)), "second");
+        assert_eq!(TextTrack::new().try_get_value(ms(0)), Err(TimelineError::EmptyTrack));
+    }
+
+    #[test]
+    fn reveal_test() {
+        assert_eq!(reveal("", "hello", 0.0), "");
+        assert_eq!(reveal("", "hello", 0.4), "he");
+        assert_eq!(reveal("", "hello", 1.0), "hello");
+        // erase "ld", then type "p"
+        assert_eq!(reveal("world", "worp", 1.0 / 3.0), "worl");
+        assert_eq!(reveal("world", "worp", 2.0 / 3.0), "wor");
+        assert_eq!(reveal("world", "worp", 0.99), "wor");
+        // multibyte characters are not split
+        assert_eq!(reveal("", "こんにちは", 0.5), "こん");
+    }
+
+    #[test]
+    fn reveal_track_test() {
+        let mut t = TextTrack::new();
+        t.add_keyframe(TextKeyframe::reveal(ms(0), ""))
+            .add_keyframe(TextKeyframe::new(ms(1000), "abcd"));
+        assert_eq!(t.get_value(ms(0)), "");
+        assert_eq!(t.get_value(ms(500)), "ab");
+        assert_eq!(t.get_value(ms(1000)), "abcd");
+    }
+
+    #[test]
+    fn xml_load_test() {
+        let xml = r#"
+<keyframes>
+    <key>
+        <time>00:00:00:000</time>
+        <value>Hello</value>
+        <interpolation>reveal</interpolation>
+    </key>
+    <key>
+        <time>00:00:02:000</time>
+        <value>Hello world</value>
+    </key>
+    <key>
+        <time>00:00:03:000</time>
+        <value>42</value>
+    </key>
+</keyframes>"#;
+
+        let t = TextTrack::load_xml_str(xml).unwrap();
+        assert_eq!(t.get_value(ms(1000)), "Hello wo");
+        assert_eq!(t.get_value(ms(2500)), "Hello world");
+        assert_eq!(t.get_value(ms(3000)), "42");
+    }
+
+    #[test]
+    fn xml_round_trip_test() {
+        let mut t = TextTrack::new();
+        t.add_keyframe(TextKeyframe::reveal(ms(0), "<b>Tom & Jerry</b>"))
+            .add_keyframe(TextKeyframe::new(ms(1500), "7"))
+            .add_keyframe(TextKeyframe::new(ms(1600), "007"))
+            .add_keyframe(TextKeyframe::new(ms(1700), "1.50"))
+            .add_keyframe(TextKeyframe::new(ms(1800), "true"))
+            .add_keyframe(TextKeyframe::new(ms(1900), "  padded "))
+            .add_keyframe(TextKeyframe::new(ms(2000), ""));
+        assert_eq!(TextTrack::load_xml_str(&t.to_xml_string()).unwrap(), t);
+    }
+
+    #[test]
+    fn json_load_test() {
+        let json = r#"{"keyframes": {"key": [
+            {"time": "00:00:00:000", "value": "a"},
+            {"time": "00:00:01:000", "value": "b"}
+        ]}}"#;
+
+        let mut tl = Timeline::new();
+        tl.add("subtitle", TextTrack::load_json_str(json).unwrap());
+        assert_eq!(tl.get_text("subtitle", ms(1500)), "b");
+        assert_eq!(tl.get_max_duration(), ms(1000));
+        assert!(tl.try_get_value("subtitle", ms(0)).is_err());
+    }
+
+    #[test]
+    fn xml_load_file_test() {
+        let path = fixture("text.xml");
+        let t = TextTrack::load_xml(&path).unwrap();
+        assert_eq!(t.get_value(ms(1000)), "Hello wo");
+        assert_eq!(TextTrack::load_xml_reader(File::open(path).unwrap()).unwrap(), t);
+    }
+}
@@ -0,0 +1,427 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::TimelineError;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Frame rates of SMPTE timecodes, or any rational rate (`Custom`) for `Timeline::frame_rate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FrameRate {
+    #[serde(rename = "24")]
+    Fps24,
+    #[serde(rename = "25")]
+    Fps25,
+    /// NTSC 30000/1001 fps, drop-frame numbering (`HH:MM:SS;FF`)
+    #[serde(rename = "29.97df")]
+    Fps29_97Df,
+    #[serde(rename = "30")]
+    Fps30,
+    #[serde(rename = "50")]
+    Fps50,
+    #[serde(rename = "60")]
+    Fps60,
+    /// `num / den` fps (`24000 / 1001` for 23.976, `12 / 1` for animation on twos). a zero counts as 1.
+    /// SMPTE timecodes count `ceil(num / den)` frames per second, without dropping frames
+    #[serde(rename = "custom")]
+    Custom { num: u32, den: u32 },
+}
+
+impl FrameRate {
+    /// frames per second as a fraction (numerator, denominator)
+    pub fn get_ratio(self) -> (u128, u128) {
+        match self {
+            FrameRate::Fps24 => (24, 1),
+            FrameRate::Fps25 => (25, 1),
+            FrameRate::Fps29_97Df => (30000, 1001),
+            FrameRate::Fps30 => (30, 1),
+            FrameRate::Fps50 => (50, 1),
+            FrameRate::Fps60 => (60, 1),
+            FrameRate::Custom { num, den } => (num.max(1) as u128, den.max(1) as u128),
+        }
+    }
+
+    pub fn get_fps(self) -> f64 {
+        let (num, den) = self.get_ratio();
+        num as f64 / den as f64
+    }
+
+    /// frames per second of the numbering (30 for 29.97 drop-frame)
+    pub fn get_nominal_fps(self) -> u64 {
+        match self {
+            FrameRate::Fps24 => 24,
+            FrameRate::Fps25 => 25,
+            FrameRate::Fps29_97Df | FrameRate::Fps30 => 30,
+            FrameRate::Fps50 => 50,
+            FrameRate::Fps60 => 60,
+            FrameRate::Custom { .. } => {
+                let (num, den) = self.get_ratio();
+                num.div_ceil(den) as u64
+            }
+        }
+    }
+
+    pub fn is_drop_frame(self) -> bool {
+        self == FrameRate::Fps29_97Df
+    }
+
+    /// start of the frame, rounded to the nanosecond. `Duration::MAX` past it (slow custom rates)
+    pub fn frame_to_duration(self, frame: u64) -> Duration {
+        let (num, den) = self.get_ratio();
+        let nanos = (frame as u128 * den * NANOS_PER_SEC + num / 2) / num;
+        match u64::try_from(nanos / NANOS_PER_SEC) {
+            Ok(secs) => Duration::new(secs, (nanos % NANOS_PER_SEC) as u32),
+            Err(_) => Duration::MAX,
+        }
+    }
+
+    /// frame containing `duration`, saturating at `u64::MAX` (fast custom rates)
+    pub fn duration_to_frame(self, duration: Duration) -> u64 {
+        saturate(self.duration_to_frame_u128(duration))
+    }
+
+    /// `duration_to_frame` without saturation, the frames of `Duration::MAX` do not fit in a u64
+    fn duration_to_frame_u128(self, duration: Duration) -> u128 {
+        let (num, den) = self.get_ratio();
+        // + 1ns so the start of a frame (rounded by `frame_to_duration`) is not floored to the previous frame
+        (duration.as_nanos() + 1) * num / (den * NANOS_PER_SEC)
+    }
+
+    /// start of the frame containing `duration`
+    pub fn quantize(self, duration: Duration) -> Duration {
+        self.frame_to_duration(self.duration_to_frame(duration))
+    }
+
+    /// nearest frame boundary
+    pub fn snap(self, duration: Duration) -> Duration {
+        let (num, den) = self.get_ratio();
+        let unit = den * NANOS_PER_SEC;
+        self.frame_to_duration(saturate((duration.as_nanos() * num + unit / 2) / unit))
+    }
+}
+
+fn saturate(frame: u128) -> u64 {
+    u64::try_from(frame).unwrap_or(u64::MAX)
+}
+
+/// How a `Timecode` is written and parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimecodeFormat {
+    /// `HH:MM:SS:mmm`, as written by ofxTimeline. the milliseconds can have a fraction when parsing
+    Millis,
+    /// SMPTE `HH:MM:SS:FF`, `HH:MM:SS;FF` for drop-frame
+    Smpte(FrameRate),
+    /// plain seconds, `12.5`
+    Seconds,
+}
+
+/// A time with the format it is written in
+///
+/// Formatting truncates to the precision of the format (milliseconds or frames),
+/// parsing checks every field (`TimelineError::InvalidTimecode` / `TimecodeOutOfRange`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timecode {
+    pub duration: Duration,
+    pub format: TimecodeFormat,
+}
+
+impl Timecode {
+    pub fn new(duration: Duration, format: TimecodeFormat) -> Timecode {
+        Timecode { duration, format }
+    }
+
+    pub fn millis(duration: Duration) -> Timecode {
+        Timecode::new(duration, TimecodeFormat::Millis)
+    }
+
+    pub fn smpte(duration: Duration, rate: FrameRate) -> Timecode {
+        Timecode::new(duration, TimecodeFormat::Smpte(rate))
+    }
+
+    pub fn seconds(duration: Duration) -> Timecode {
+        Timecode::new(duration, TimecodeFormat::Seconds)
+    }
+
+    pub fn get_duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn parse(timecode: &str, format: TimecodeFormat) -> Result<Timecode, TimelineError> {
+        let duration = match format {
+            TimecodeFormat::Millis => parse_millis(timecode)?,
+            TimecodeFormat::Smpte(rate) => parse_smpte(timecode, rate)?,
+            TimecodeFormat::Seconds => parse_seconds(timecode)?,
+        };
+        Ok(Timecode { duration, format })
+    }
+}
+
+/// `HH:MM:SS:mmm` or plain seconds (SMPTE needs a frame rate, see `Timecode::parse`)
+impl FromStr for Timecode {
+    type Err = TimelineError;
+
+    fn from_str(s: &str) -> Result<Timecode, TimelineError> {
+        let format = if s.contains(':') { TimecodeFormat::Millis } else { TimecodeFormat::Seconds };
+        Timecode::parse(s, format)
+    }
+}
+
+impl From<Timecode> for Duration {
+    fn from(timecode: Timecode) -> Duration {
+        timecode.duration
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.format {
+            TimecodeFormat::Millis => {
+                let millis = self.duration.as_millis();
+                write!(f, "{:02}:{:02}:{:02}:{:03}", millis / 3_600_000, millis / 60_000 % 60, millis / 1000 % 60, millis % 1000)
+            }
+            TimecodeFormat::Smpte(rate) => {
+                let fps = rate.get_nominal_fps() as u128;
+                let mut frame = rate.duration_to_frame_u128(self.duration);
+                if rate.is_drop_frame() {
+                    // add back the frame numbers dropped in each minute but every tenth
+                    let (tens, rest) = (frame / 17982, frame % 17982);
+                    frame += 18 * tens + if rest > 1 { 2 * ((rest - 2) / 1798) } else { 0 };
+                }
+                let separator = if rate.is_drop_frame() { ';' } else { ':' };
+                let seconds = frame / fps;
+                write!(f, "{:02}:{:02}:{:02}{}{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60, separator, frame % fps)
+            }
+            TimecodeFormat::Seconds => write!(f, "{}", self.duration.as_secs_f64()),
+        }
+    }
+}
+
+/// hours, minutes and seconds of `HH:MM:SS`, and the last field
+fn split_timecode<'a>(timecode: &'a str, separators: &[char]) -> Result<(u64, u64, u64, &'a str), TimelineError> {
+    let invalid = || TimelineError::InvalidTimecode(timecode.to_string());
+    let parts: Vec<&str> = timecode.trim().split(separators).collect();
+    let [hours, minutes, seconds, last] = parts[..] else {
+        return Err(invalid());
+    };
+    let field = |s: &str| {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        s.parse::<u64>().map_err(|_| invalid())
+    };
+    let (hours, minutes, seconds) = (field(hours)?, field(minutes)?, field(seconds)?);
+    check_field(timecode, minutes < 60, "minutes")?;
+    check_field(timecode, seconds < 60, "seconds")?;
+    Ok((hours, minutes, seconds, last))
+}
+
+fn check_field(timecode: &str, valid: bool, field: &'static str) -> Result<(), TimelineError> {
+    if valid {
+        Ok(())
+    } else {
+        Err(out_of_range(timecode, field))
+    }
+}
+
+fn out_of_range(timecode: &str, field: &'static str) -> TimelineError {
+    TimelineError::TimecodeOutOfRange { timecode: timecode.to_string(), field }
+}
+
+fn parse_millis(timecode: &str) -> Result<Duration, TimelineError> {
+    let invalid = || TimelineError::InvalidTimecode(timecode.to_string());
+    let (hours, minutes, seconds, millis) = split_timecode(timecode, &[':'])?;
+    let (whole, fraction) = millis.split_once('.').unwrap_or((millis, ""));
+    if whole.is_empty() || !whole.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let whole: u64 = whole.parse().map_err(|_| invalid())?;
+    check_field(timecode, whole < 1000, "milliseconds")?;
+    // fraction of a millisecond, rounded to the nanosecond
+    let fraction = if fraction.is_empty() { 0.0 } else { format!("0.{}", fraction).parse::<f64>().map_err(|_| invalid())? };
+    let nanos = whole * 1_000_000 + (fraction * 1_000_000.0).round() as u64;
+    // too many hours for a `Duration`
+    hours.checked_mul(3600)
+        .and_then(|secs| secs.checked_add(minutes * 60 + seconds))
+        .and_then(|secs| Duration::from_secs(secs).checked_add(Duration::from_nanos(nanos)))
+        .ok_or_else(|| out_of_range(timecode, "hours"))
+}
+
+fn parse_smpte(timecode: &str, rate: FrameRate) -> Result<Duration, TimelineError> {
+    let invalid = || TimelineError::InvalidTimecode(timecode.to_string());
+    let (hours, minutes, seconds, frames) = split_timecode(timecode, &[':', ';'])?;
+    if frames.is_empty() || !frames.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let frames: u64 = frames.parse().map_err(|_| invalid())?;
+    let fps = rate.get_nominal_fps();
+    check_field(timecode, frames < fps, "frames")?;
+
+    // too many hours to count the frames in a u64
+    let total_minutes = hours.checked_mul(60)
+        .and_then(|m| m.checked_add(minutes))
+        .ok_or_else(|| out_of_range(timecode, "hours"))?;
+    let mut frame = total_minutes.checked_mul(60)
+        .and_then(|secs| secs.checked_add(seconds))
+        .and_then(|secs| secs.checked_mul(fps))
+        .and_then(|frame| frame.checked_add(frames))
+        .ok_or_else(|| out_of_range(timecode, "hours"))?;
+    if rate.is_drop_frame() {
+        // frames 00 and 01 do not exist at the start of each minute but every tenth
+        check_field(timecode, !(seconds == 0 && frames < 2 && minutes % 10 != 0), "frames")?;
+        frame -= 2 * (total_minutes - total_minutes / 10);
+    }
+    Ok(rate.frame_to_duration(frame))
+}
+
+fn parse_seconds(timecode: &str) -> Result<Duration, TimelineError> {
+    let invalid = || TimelineError::InvalidTimecode(timecode.to_string());
+    let seconds: f64 = timecode.trim().parse().map_err(|_| invalid())?;
+    Duration::try_from_secs_f64(seconds).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::ms;
+
+    use super::*;
+
+    #[test]
+    fn millis_test() {
+        let t = Timecode::parse("01:02:03:004", TimecodeFormat::Millis).unwrap();
+        assert_eq!(t.duration, ms(3_723_004));
+        assert_eq!(t.to_string(), "01:02:03:004");
+        // fractional milliseconds are kept
+        assert_eq!("00:00:01:500.25".parse::<Timecode>().unwrap().duration, Duration::new(1, 500_250_000));
+        assert_eq!(Timecode::millis(Duration::new(1, 500_999_999)).to_string(), "00:00:01:500");
+        assert_eq!(Timecode::millis(ms(100 * 3_600_000)).to_string(), "100:00:00:000");
+    }
+
+    #[test]
+    fn invalid_test() {
+        let invalid = |s: &str| TimelineError::InvalidTimecode(s.to_string());
+        let out_of_range = |s: &str, field| TimelineError::TimecodeOutOfRange { timecode: s.to_string(), field };
+        assert_eq!("00:00:643".parse::<Timecode>(), Err(invalid("00:00:643")));
+        assert_eq!("00:00:-1:000".parse::<Timecode>(), Err(invalid("00:00:-1:000")));
+        assert_eq!("00:00:01:".parse::<Timecode>(), Err(invalid("00:00:01:")));
+        assert_eq!("00:61:00:000".parse::<Timecode>(), Err(out_of_range("00:61:00:000", "minutes")));
+        assert_eq!("00:00:00:1000".parse::<Timecode>(), Err(out_of_range("00:00:00:1000", "milliseconds")));
+        assert_eq!(
+            Timecode::parse("00:00:00:25", TimecodeFormat::Smpte(FrameRate::Fps25)),
+            Err(out_of_range("00:00:00:25", "frames"))
+        );
+        assert_eq!("-1".parse::<Timecode>(), Err(invalid("-1")));
+
+        for s in ["99999999999999999:00:00:000", "5124095576030431:00:16:000"] {
+            assert_eq!(s.parse::<Timecode>(), Err(out_of_range(s, "hours")));
+        }
+        for s in ["99999999999999999:00:00:00", "307445734561825860:59:59:00"] {
+            for rate in [FrameRate::Fps24, FrameRate::Fps29_97Df, FrameRate::Fps60] {
+                assert_eq!(Timecode::parse(s, TimecodeFormat::Smpte(rate)), Err(out_of_range(s, "hours")));
+            }
+        }
+        assert!(Timecode::parse("5124095576030431:00:15:999", TimecodeFormat::Millis).is_ok());
+    }
+
+    #[test]
+    fn smpte_test() {
+        let t = Timecode::parse("00:00:01:12", TimecodeFormat::Smpte(FrameRate::Fps24)).unwrap();
+        assert_eq!(t.duration, ms(1500));
+        assert_eq!(Timecode::smpte(ms(1500), FrameRate::Fps50).to_string(), "00:00:01:25");
+        assert_eq!(Timecode::smpte(ms(1999), FrameRate::Fps60).to_string(), "00:00:01:59");
+        for rate in [FrameRate::Fps24, FrameRate::Fps25, FrameRate::Fps30, FrameRate::Fps50, FrameRate::Fps60] {
+            let s = "10:59:59:23";
+            assert_eq!(Timecode::parse(s, TimecodeFormat::Smpte(rate)).unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn drop_frame_test() {
+        let df = TimecodeFormat::Smpte(FrameRate::Fps29_97Df);
+        // 00:01:00;00 and ;01 are dropped, 00:10:00;00 is not
+        assert_eq!(Timecode::parse("00:00:59;29", df).unwrap().to_string(), "00:00:59;29");
+        let next = FrameRate::Fps29_97Df.frame_to_duration(1800);
+        assert_eq!(Timecode::new(next, df).to_string(), "00:01:00;02");
+        assert_eq!(Timecode::parse("00:01:00;02", df).unwrap().duration, next);
+        assert!(Timecode::parse("00:01:00;01", df).is_err());
+        assert_eq!(Timecode::parse("00:10:00;00", df).unwrap().to_string(), "00:10:00;00");
+
+        // one hour of drop-frame timecode is 107892 frames, 3600 seconds within 3.6ms
+        let hour = Timecode::parse("01:00:00;00", df).unwrap().duration;
+        assert_eq!(FrameRate::Fps29_97Df.duration_to_frame(hour), 107_892);
+        assert!(hour.as_secs_f64() - 3600.0 < 0.0036);
+
+        for frame in [0, 1, 1799, 1800, 17981, 17982, 17983, 107_891, 1_000_000] {
+            let t = Timecode::new(FrameRate::Fps29_97Df.frame_to_duration(frame), df);
+            assert_eq!(Timecode::parse(&t.to_string(), df).unwrap(), t);
+        }
+    }
+
+    #[test]
+    fn smpte_max_duration_test() {
+        // more frames than a u64 holds. drop-frame numbering runs ahead of the clock by 86.4ms a day
+        let t = Timecode::smpte(Duration::MAX, FrameRate::Fps29_97Df);
+        assert_eq!(t.to_string(), "5124100700131131:08:08;03");
+    }
+
+    #[test]
+    fn frame_test() {
+        let rate = FrameRate::Fps30;
+        assert_eq!(rate.frame_to_duration(45), ms(1500));
+        assert_eq!(rate.duration_to_frame(ms(1533)), 45);
+        assert_eq!(rate.quantize(ms(1533)), ms(1500));
+        assert_eq!(rate.snap(ms(1533)), rate.frame_to_duration(46));
+        assert_eq!(rate.snap(ms(1516)), ms(1500));
+
+        let ntsc = FrameRate::Fps29_97Df;
+        for frame in [1, 29, 30, 1799, 107_892] {
+            let time = ntsc.frame_to_duration(frame);
+            assert_eq!(ntsc.duration_to_frame(time), frame);
+            assert_eq!(ntsc.quantize(time), time);
+            assert_eq!(ntsc.snap(time), time);
+        }
+    }
+
+    #[test]
+    fn custom_rate_test() {
+        let twos = FrameRate::Custom { num: 12, den: 1 };
+        assert_eq!(twos.get_fps(), 12.0);
+        assert_eq!(twos.frame_to_duration(6), ms(500));
+        assert_eq!(twos.duration_to_frame(ms(499)), 5);
+        assert_eq!(twos.quantize(ms(499)), ms(416) + Duration::from_nanos(666_667));
+        assert_eq!(twos.snap(ms(499)), ms(500));
+
+        let film = FrameRate::Custom { num: 24000, den: 1001 };
+        assert_eq!(film.get_nominal_fps(), 24);
+        assert!(!film.is_drop_frame());
+        for frame in [1, 23, 24, 86_400, 1_000_000] {
+            assert_eq!(film.duration_to_frame(film.frame_to_duration(frame)), frame);
+        }
+        let smpte = TimecodeFormat::Smpte(film);
+        let t = Timecode::parse("00:00:01:23", smpte).unwrap();
+        assert_eq!(t.duration, film.frame_to_duration(47));
+        assert_eq!(t.to_string(), "00:00:01:23");
+
+        // zero counts as 1, extreme rates saturate
+        assert_eq!(FrameRate::Custom { num: 0, den: 0 }.frame_to_duration(2), ms(2000));
+        assert_eq!(FrameRate::Custom { num: 1, den: u32::MAX }.frame_to_duration(u64::MAX), Duration::MAX);
+        assert_eq!(FrameRate::Custom { num: u32::MAX, den: 1 }.duration_to_frame(Duration::MAX), u64::MAX);
+
+        let json = serde_json::to_string(&film).unwrap();
+        assert_eq!(json, r#"{"custom":{"num":24000,"den":1001}}"#);
+        assert_eq!(serde_json::from_str::<FrameRate>(&json).unwrap(), film);
+        assert_eq!(serde_json::from_str::<FrameRate>(r#""25""#).unwrap(), FrameRate::Fps25);
+    }
+
+    #[test]
+    fn seconds_test() {
+        let t: Timecode = "12.5".parse().unwrap();
+        assert_eq!(t, Timecode::seconds(ms(12500)));
+        assert_eq!(t.to_string(), "12.5");
+        assert_eq!(Duration::from(t), ms(12500));
+        assert!("abc".parse::<Timecode>().is_err());
+    }
+}
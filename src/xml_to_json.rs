@@ -1,4 +1,8 @@
-use quickxml_to_serde::Config;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use minidom::Element;
+use quickxml_to_serde::{Config, JsonArray, JsonType};
 use lazy_static::lazy_static;
 
 lazy_static! {
@@ -7,14 +11,78 @@ lazy_static! {
 
 pub fn xml_str_to_json(xml: &str) -> Result<serde_json::Value, minidom::error::Error> {
     let config = &*XML_CONFIG_DEFAULT;
-    let json = quickxml_to_serde::xml_str_to_json(xml, config);
-    json
+    let mut json = quickxml_to_serde::xml_str_to_json(xml, config)?;
+    negative_integers_to_i64(&mut json);
+    Ok(json)
+}
+
+/// `xml_str_to_json`, keeping the texts of the elements at `text_paths` (`/keyframes/key/value`) as written:
+/// always strings (`007` is not read as `7`), with their leading and trailing whitespace
+pub fn xml_str_to_json_with_texts(xml: &str, text_paths: &[&str]) -> Result<serde_json::Value, minidom::error::Error> {
+    let config = text_paths.iter().fold(Config::new_with_defaults(), |config, path| {
+        config.add_json_type_override(*path, JsonArray::Infer(JsonType::AlwaysString))
+    });
+    let mut json = quickxml_to_serde::xml_str_to_json(xml, &config)?;
+    negative_integers_to_i64(&mut json);
+
+    let root = Element::from_str(xml)?;
+    if let Some(value) = json.get_mut(root.name()) {
+        restore_texts(&root, value, "", text_paths);
+    }
+    Ok(json)
+}
+
+/// quickxml_to_serde trims texts (and converts blank ones to empty objects), puts the untrimmed texts back
+fn restore_texts(element: &Element, value: &mut serde_json::Value, parent_path: &str, text_paths: &[&str]) {
+    let path = format!("{}/{}", parent_path, element.name());
+    if text_paths.contains(&path.as_str()) {
+        let is_text = match value {
+            serde_json::Value::String(_) => true,
+            serde_json::Value::Object(o) => o.is_empty(),
+            _ => false,
+        };
+        if is_text && element.children().next().is_none() {
+            *value = serde_json::Value::String(element.text());
+        }
+        return;
+    }
+
+    let serde_json::Value::Object(object) = value else {
+        return;
+    };
+    // repeated elements are converted to an array, in document order
+    let mut indices: HashMap<&str, usize> = HashMap::new();
+    for child in element.children() {
+        let index = indices.entry(child.name()).or_default();
+        let child_value = match object.get_mut(child.name()) {
+            Some(serde_json::Value::Array(a)) => a.get_mut(*index),
+            v => v,
+        };
+        *index += 1;
+        if let Some(child_value) = child_value {
+            restore_texts(child, child_value, &path, text_paths);
+        }
+    }
 }
 
+/// quickxml_to_serde parses negative integers as floats, which integer values can not be read from
+fn negative_integers_to_i64(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Number(n) => {
+            if let Some(f) = n.as_f64().filter(|f| *f < 0.0 && f.fract() == 0.0 && *f >= i64::MIN as f64) {
+                *n = (f as i64).into();
+            }
+        }
+        serde_json::Value::Array(a) => a.iter_mut().for_each(negative_integers_to_i64),
+        serde_json::Value::Object(o) => o.values_mut().for_each(negative_integers_to_i64),
+        _ => {}
+    }
+}
+
+#[allow(dead_code)]
 pub fn xml_string_to_json(xml: String) -> Result<serde_json::Value, minidom::error::Error> {
     let config = &*XML_CONFIG_DEFAULT;
-    let json = quickxml_to_serde::xml_string_to_json(xml, config);
-    json
+    quickxml_to_serde::xml_string_to_json(xml, config)
 }
 
 #[cfg(test)]
@@ -34,6 +102,27 @@ mod tests {
         assert_eq!(json, expected);
     }
 
+    #[test]
+    fn test_xml_negative_to_json() {
+        let xml = r#"<root><a>-10</a><b>-0.5</b></root>"#;
+        let json = xml_str_to_json(xml).unwrap();
+        assert_eq!(json, serde_json::json!({"root": {"a": -10, "b": -0.5}}));
+        assert!(json["root"]["a"].is_i64());
+    }
+
+    #[test]
+    fn test_xml_texts_to_json() {
+        let xml = "<root><a>007</a><a>  padded </a><a>   </a><b>007</b><c><a>1.50</a></c></root>";
+        let json = xml_str_to_json_with_texts(xml, &["/root/a"]).unwrap();
+        assert_eq!(json, serde_json::json!({
+            "root": {
+                "a": ["007", "  padded ", "   "],
+                "b": 7,
+                "c": {"a": 1.5}
+            }
+        }));
+    }
+
     #[test]
     fn test_xml_attr_to_json() {
         let xml = r#"<root a="1" b="2"></root>"#;